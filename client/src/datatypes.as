@@ -126,6 +126,15 @@ class MatchConfiguration {
     uint itemsTickMultiplier = 1000;
     uint rallyLength = 600;
     uint jailLength = 600;
+    uint inventorySize = 1;
+    bool sharedInventory = false;
+    uint64 seed;
+    PracticeGoal practiceGoal = PracticeGoal::None;
+    bool medalLadder = false;
+    MapFilters filters;
+    bool avoidRecentMaps = false;
+    array<MapSource> mapSources;
+    array<CustomMap> customMaps;
     MatchConfiguration() {}
 }
 namespace MatchConfiguration {
@@ -152,6 +161,23 @@ namespace MatchConfiguration {
         value["items_tick_multiplier"] = cls.itemsTickMultiplier;
         value["rally_length"] = cls.rallyLength;
         value["jail_length"] = cls.jailLength;
+        value["inventory_size"] = cls.inventorySize;
+        value["shared_inventory"] = cls.sharedInventory;
+        value["seed"] = cls.seed;
+        value["practice_goal"] = int(cls.practiceGoal);
+        value["medal_ladder"] = cls.medalLadder;
+        value["filters"] = MapFilters::Serialize(cls.filters);
+        value["avoid_recent_maps"] = cls.avoidRecentMaps;
+        array<Json::Value@> mapSources = {};
+        for (uint i = 0; i < cls.mapSources.Length; i++) {
+            mapSources.InsertLast(MapSource::Serialize(cls.mapSources[i]));
+        }
+        value["map_sources"] = mapSources;
+        array<Json::Value@> customMaps = {};
+        for (uint i = 0; i < cls.customMaps.Length; i++) {
+            customMaps.InsertLast(CustomMap::Serialize(cls.customMaps[i]));
+        }
+        value["custom_maps"] = customMaps;
 
         return value;
     }
@@ -181,6 +207,19 @@ namespace MatchConfiguration {
         cls.itemsTickMultiplier = value["items_tick_multiplier"];
         cls.rallyLength = value["rally_length"];
         cls.jailLength = value["jail_length"];
+        cls.inventorySize = value["inventory_size"];
+        cls.sharedInventory = value["shared_inventory"];
+        if (value["seed"].GetType() != Json::Type::Null) cls.seed = value["seed"];
+        cls.practiceGoal = PracticeGoal(int(value["practice_goal"]));
+        cls.medalLadder = value["medal_ladder"];
+        cls.filters = MapFilters::Deserialize(value["filters"]);
+        cls.avoidRecentMaps = value["avoid_recent_maps"];
+        for (uint i = 0; i < value["map_sources"].Length; i++) {
+            cls.mapSources.InsertLast(MapSource::Deserialize(value["map_sources"][i]));
+        }
+        for (uint i = 0; i < value["custom_maps"].Length; i++) {
+            cls.customMaps.InsertLast(CustomMap::Deserialize(value["custom_maps"][i]));
+        }
 
         return cls;
     }
@@ -222,6 +261,111 @@ namespace FrenzyItemSettings {
     }
 }
 
+/* One of the map selections mixed together on a board, with its share of the maps. */
+class MapSource {
+    MapMode selection = MapMode::RandomTMX;
+    uint weight = 1;
+    uint mappackId;
+    int mapTag;
+    MapSource() {}
+}
+namespace MapSource {
+    Json::Value@ Serialize(MapSource cls) {
+        auto value = Json::Object();
+        value["selection"] = int(cls.selection);
+        value["weight"] = cls.weight;
+        value["mappack_id"] = cls.mappackId;
+        value["map_tag"] = cls.mapTag;
+
+        return value;
+    }
+
+    MapSource Deserialize(Json::Value@ value) {
+        auto cls = MapSource();
+        cls.selection = MapMode(int(value["selection"]));
+        cls.weight = value["weight"];
+        if (value["mappack_id"].GetType() != Json::Type::Null) cls.mappackId = value["mappack_id"];
+        if (value["map_tag"].GetType() != Json::Type::Null) cls.mapTag = value["map_tag"];
+
+        return cls;
+    }
+}
+
+/* A map picked by the host for a custom board, by UID or TMX id, optionally placed on a cell numbered from 1. */
+class CustomMap {
+    string uid;
+    uint tmxid;
+    uint position;
+    CustomMap() {}
+}
+namespace CustomMap {
+    Json::Value@ Serialize(CustomMap cls) {
+        auto value = Json::Object();
+        value["uid"] = cls.uid;
+        value["tmxid"] = cls.tmxid;
+        value["position"] = cls.position;
+
+        return value;
+    }
+
+    CustomMap Deserialize(Json::Value@ value) {
+        auto cls = CustomMap();
+        if (value["uid"].GetType() != Json::Type::Null) cls.uid = value["uid"];
+        if (value["tmxid"].GetType() != Json::Type::Null) cls.tmxid = value["tmxid"];
+        if (value["position"].GetType() != Json::Type::Null) cls.position = value["position"];
+
+        return cls;
+    }
+}
+
+/* Restrictions on the maps drawn from the map cache. */
+class MapFilters {
+    uint minAuthorTime;
+    uint maxAuthorTime;
+    array<int> includeTags;
+    array<int> excludeTags;
+    string style;
+    uint64 uploadedAfter;
+    uint64 uploadedBefore;
+    array<string> excludedAuthors;
+    MapFilters() {}
+}
+namespace MapFilters {
+    Json::Value@ Serialize(MapFilters cls) {
+        auto value = Json::Object();
+        value["min_author_time"] = cls.minAuthorTime;
+        value["max_author_time"] = cls.maxAuthorTime;
+        value["include_tags"] = cls.includeTags;
+        value["exclude_tags"] = cls.excludeTags;
+        value["style"] = cls.style;
+        value["uploaded_after"] = cls.uploadedAfter;
+        value["uploaded_before"] = cls.uploadedBefore;
+        value["excluded_authors"] = cls.excludedAuthors;
+
+        return value;
+    }
+
+    MapFilters Deserialize(Json::Value@ value) {
+        auto cls = MapFilters();
+        if (value["min_author_time"].GetType() != Json::Type::Null) cls.minAuthorTime = value["min_author_time"];
+        if (value["max_author_time"].GetType() != Json::Type::Null) cls.maxAuthorTime = value["max_author_time"];
+        for (uint i = 0; i < value["include_tags"].Length; i++) {
+            cls.includeTags.InsertLast(value["include_tags"][i]);
+        }
+        for (uint i = 0; i < value["exclude_tags"].Length; i++) {
+            cls.excludeTags.InsertLast(value["exclude_tags"][i]);
+        }
+        if (value["style"].GetType() != Json::Type::Null) cls.style = value["style"];
+        if (value["uploaded_after"].GetType() != Json::Type::Null) cls.uploadedAfter = value["uploaded_after"];
+        if (value["uploaded_before"].GetType() != Json::Type::Null) cls.uploadedBefore = value["uploaded_before"];
+        for (uint i = 0; i < value["excluded_authors"].Length; i++) {
+            cls.excludedAuthors.InsertLast(value["excluded_authors"][i]);
+        }
+
+        return cls;
+    }
+}
+
 /* Request to open a connection by the client using an exisiting token. */
 class HandshakeRequest {
     string version;
@@ -410,6 +554,9 @@ enum MapMode {
     Tags,
     Mappack,
     Campaign,
+    Mixed,
+    Local,
+    Custom,
 }
 
 /* A Trackmania medal ranking. */
@@ -426,6 +573,7 @@ enum Medal {
 enum Gamemode {
     Standard,
     Frenzy,
+    Points,
 }
 
 /* When a connection to the server fails, give the client a hint of what it should do. */
@@ -445,3 +593,11 @@ enum Powerup {
     RainbowTile,
     GoldenDice,
 }
+
+/* The objective of a solo practice match. */
+enum PracticeGoal {
+    None,
+    Bingo,
+    Blackout,
+    TimeAttack,
+}
@@ -14,6 +14,7 @@ class GameServer {
     bool canReroll = false;
     EndState endState;
     LoadStatus maploadStatus;
+    array<string> maploadWarnings;
 
     // Local state
     int currentTileIndex = -1;
@@ -30,6 +31,26 @@ class GameServer {
         return null;
     }
 
+    /* The medal the local player must reach to claim a map, after their own and their team's handicap. */
+    Medal GetTargetMedal() {
+        Player @self = GetSelf();
+        if (self !is null && self.handicap.targetMedal != Medal::None)
+            return self.handicap.targetMedal;
+        Team @team = (self !is null ? GetTeamWithId(self.team.id) : null);
+        if (team !is null && team.handicap.targetMedal != Medal::None)
+            return team.handicap.targetMedal;
+        return config.targetMedal;
+    }
+
+    /* Milliseconds the server adds to the local player's runs, from their own and their team's handicap. */
+    int GetTimeAllowance() {
+        Player @self = GetSelf();
+        if (self is null)
+            return 0;
+        Team @team = GetTeamWithId(self.team.id);
+        return self.handicap.timeAllowance + (team !is null ? team.handicap.timeAllowance : 0);
+    }
+
     Team @GetTeamWithId(int id) {
         for (uint i = 0; i < teams.Length; i++) {
             if (teams[i].id == id)
@@ -163,7 +184,8 @@ class GameTile {
     }
 
     void LoadThumbnail() {
-        if (@map !is null) {
+        // maps that are not on TMX have no thumbnail
+        if (@map !is null && map.id > 0) {
             @this.thumbnail =
                 Image("https://trackmania.exchange/maps/screenshot_normal/" + map.id);
         } else {
@@ -194,7 +216,7 @@ class GameTile {
             auto current = attemptRanking[i - 1];
             if (current.player.profile.uid == claim.player.profile.uid) {
                 attemptRanking.RemoveAt(i - 1);
-            } else if (claim.result.time >= current.result.time) {
+            } else if (!MapClaim::Beats(claim, current, Match.config.medalLadder)) {
                 break;
             }
             i -= 1;
@@ -264,11 +286,36 @@ class RunResult {
 }
 
 
+class Handicap {
+    Medal targetMedal = Medal::None;
+    int timeAllowance;
+    uint headStart;
+
+    Handicap() {}
+}
+
+namespace Handicap {
+    Handicap Deserialize(Json::Value @value) {
+        Handicap handicap();
+        if (@value is null || value.GetType() != Json::Type::Object)
+            return handicap;
+        if (value.HasKey("target_medal") && value["target_medal"].GetType() != Json::Type::Null)
+            handicap.targetMedal = Medal(int(value["target_medal"]));
+        if (value.HasKey("time_allowance"))
+            handicap.timeAllowance = int(value["time_allowance"]);
+        if (value.HasKey("head_start"))
+            handicap.headStart = uint(value["head_start"]);
+        return handicap;
+    }
+}
+
 class Team {
 
     string name;
     int id;
     vec3 color;
+    int score;
+    Handicap handicap;
 
     Team() {}
 
@@ -283,10 +330,13 @@ class Team {
 
 namespace Team {
     Team Deserialize(Json::Value @value) {
-        return Team(
+        Team team(
             value["id"],
             value["name"],
             vec3(value["color"][0] / 255., value["color"][1] / 255., value["color"][2] / 255.));
+        if (value.HasKey("handicap"))
+            team.handicap = Handicap::Deserialize(value["handicap"]);
+        return team;
     }
 
     Json::Value @Serialize(Team team) {
@@ -301,13 +351,22 @@ namespace Team {
     }
 }
 
+class InventoryItem {
+    int id;
+    Powerup powerup = Powerup::Empty;
+    int64 expireTimestamp;
+}
+
 class Player {
     PlayerProfile profile;
     string name;
     Team team;
     Powerup holdingPowerup = Powerup::Empty;
+    int holdingItemId = -1;
     int64 powerupExpireTimestamp;
+    array<InventoryItem> inventory;
     bool isMvp;
+    Handicap handicap;
 
     Player() {}
 
@@ -331,6 +390,45 @@ class Player {
     }
 }
 
+namespace Player {
+    /* Keep the inventory of a player, holding the item they had selected if they still have it, or the first one otherwise. */
+    void EquipInventory(Player@ player, Json::Value@ items) {
+        player.inventory = {};
+        if (@items !is null && items.GetType() == Json::Type::Array) {
+            for (uint i = 0; i < items.Length; i++) {
+                Json::Value@ value = items[i];
+                InventoryItem item;
+                item.id = int(value["id"]);
+                item.powerup = Powerup(int(value["powerup"]));
+                if (value["expires"].GetType() != Json::Type::Null)
+                    item.expireTimestamp = Time::Now + (int64(value["expires"]) - int64(Time::Stamp)) * 1000;
+                player.inventory.InsertLast(item);
+            }
+        }
+
+        uint selected = 0;
+        for (uint i = 0; i < player.inventory.Length; i++) {
+            if (player.inventory[i].id == player.holdingItemId)
+                selected = i;
+        }
+        SelectItem(player, selected);
+    }
+
+    /* Hold the item at the given index of the inventory, it is the one used on the next activation. */
+    void SelectItem(Player@ player, uint index) {
+        if (index >= player.inventory.Length) {
+            player.holdingPowerup = Powerup::Empty;
+            player.holdingItemId = -1;
+            return;
+        }
+
+        InventoryItem item = player.inventory[index];
+        player.holdingPowerup = item.powerup;
+        player.holdingItemId = item.id;
+        player.powerupExpireTimestamp = item.expireTimestamp;
+    }
+}
+
 /**
  * Makes sure the player handle given as an argument is not null.
  * If it is, replace it by a "BROKEN PLAYER" handle.
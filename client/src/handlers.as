@@ -180,8 +180,10 @@ namespace NetworkHandlers {
 
             for (uint j = 0; j < t["members"].Length; j++) {
                 Json::Value @m = t["members"][j];
-                PlayerProfile profile = PlayerProfile::Deserialize(m);
-                Match.players.InsertLast(Player(profile, team));
+                Player player(PlayerProfile::Deserialize(m), team);
+                if (m.HasKey("handicap"))
+                    player.handicap = Handicap::Deserialize(m["handicap"]);
+                Match.players.InsertLast(player);
             }
         }
     }
@@ -313,6 +315,46 @@ namespace NetworkHandlers {
         Gamemaster::HandleGameEnd();
     }
 
+    void ScoresUpdate(Json::Value @data) {
+        for (uint i = 0; i < data["scores"].Length; i++) {
+            Team @team = Match.GetTeamWithId(int(data["scores"][i]["team"]));
+            if (team !is null)
+                team.score = int(data["scores"][i]["score"]);
+        }
+    }
+
+    void AnnounceWinByPoints(Json::Value @data) {
+        ScoresUpdate(data);
+        Team team = Match.GetTeamWithId(int(data["team"]));
+        string teamName = "\\$" + UIColor::GetHex(team.color) + team.name;
+        UI::ShowNotification(
+            Icons::HourglassEnd + " Game End",
+            teamName + "\\$z has won the game with the highest score, with a total of " +
+                team.score + " points!",
+            vec4(.9, .6, 0, 1),
+            20000);
+
+        if (data.HasKey("end_state")) {
+            HandleMatchEndInfo(data["end_state"]);
+        }
+
+        Match.endState.endTime = Time::Now;
+        @Match.endState.team = team;
+        Gamemaster::SetPhase(GamePhase::Ended);
+        Gamemaster::HandleGameEnd();
+    }
+
+    void PracticeEnd(Json::Value @data) {
+        string summary = PracticeResult::Deserialize(data["result"]).Summary();
+        string bestSummary = PracticeResult::Deserialize(data["personal_best"]).Summary();
+        UIPractice::BestsLoad = LoadStatus::NotLoaded;
+        UI::ShowNotification(Icons::Trophy + " Practice Result",
+                             bool(data["new_best"]) ? "New personal best: " + summary
+                                                    : summary + "\nPersonal best: " + bestSummary,
+                             vec4(.2, .6, .9, 1),
+                             20000);
+    }
+
     void MatchTeamCreated(Json::Value @data) {
         Match.teams.InsertLast(
             Team(data["id"],
@@ -465,18 +507,26 @@ namespace NetworkHandlers {
         Powerups::TriggerPowerup(usedPowerup, powerupUser, boardIndex, forwards, targetPlayer, duration);
     }
 
-    void ItemSlotEquip(Json::Value @data) {
+    void InventoryUpdate(Json::Value @data) {
         if (!Gamemaster::IsBingoActive()) {
-            logwarn("[NetworkHandlers::ItemSlotEquip] Bingo is not active, ignoring this event.");
+            logwarn("[NetworkHandlers::InventoryUpdate] Bingo is not active, ignoring this event.");
             return;
         }
 
-        Player @equipUser = Match.GetPlayer(int(data["uid"]));
-        if (@equipUser is null) {
-            logwarn("[NetworkHandlers::ItemSlotEquip] Player is null, ignoring this event. This means something is likely broken!");
+        if (data.HasKey("uid")) {
+            Player @equipUser = Match.GetPlayer(int(data["uid"]));
+            if (@equipUser is null) {
+                logwarn("[NetworkHandlers::InventoryUpdate] Player is null, ignoring this event. This means something is likely broken!");
+                return;
+            }
+            Player::EquipInventory(equipUser, data["items"]);
+        } else {
+            int teamId = int(data["team"]);
+            for (uint i = 0; i < Match.players.Length; i++) {
+                Player @member = Match.players[i];
+                if (member.team.id == teamId) Player::EquipInventory(member, data["items"]);
+            }
         }
-        equipUser.holdingPowerup = Powerup(int(data["powerup"]));
-        equipUser.powerupExpireTimestamp = Time::Now + Match.config.itemsExpire * 1000;
     }
 
     void RallyResolved(Json::Value @data) {
@@ -576,5 +626,28 @@ namespace NetworkHandlers {
     void RoomExtrasUpdate(Json::Value @data) {
         Match.verificationLocked = bool(data["locked"]);
         Match.maploadStatus = LoadStatus(int(data["load_status"]));
+        Match.maploadWarnings = {};
+        if (data.HasKey("load_warnings")) {
+            for (uint i = 0; i < data["load_warnings"].Length; i++) {
+                Match.maploadWarnings.InsertLast(data["load_warnings"][i]);
+            }
+        }
+    }
+
+    void MaintenanceNotice(Json::Value @data) {
+        UIMaintenance::Message = data["message"];
+        UIMaintenance::Deadline =
+            data["deadline"].GetType() != Json::Type::Null ? int64(data["deadline"]) : 0;
+        UI::ShowNotification(Icons::Wrench + " Bingo: Server maintenance",
+                             UIMaintenance::Message,
+                             vec4(.8, .5, 0., 1.),
+                             15000);
+    }
+
+    void Announcement(Json::Value @data) {
+        UI::ShowNotification(Icons::Bullhorn + " Bingo: Announcement",
+                             string(data["message"]),
+                             vec4(.1, .4, .8, 1.),
+                             20000);
     }
 }
@@ -89,6 +89,7 @@ void Render() {
     UIEditSettings::Render();
     UIItemSettings::Render();
     UIItemSelect::Render();
+    UIHandicap::Render();
     UIChat::Render();
 
     for (uint i = 0; i < Polls.Length; i++) {
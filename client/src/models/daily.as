@@ -0,0 +1,20 @@
+
+/* A player's best result in the leaderboard of a daily challenge. */
+class DailyLeaderboardEntry {
+    int playerUid;
+    string name;
+    int tilesClaimed;
+    int64 firstBingoMillis = -1;
+}
+
+namespace DailyLeaderboardEntry {
+    DailyLeaderboardEntry Deserialize(Json::Value @value) {
+        auto entry = DailyLeaderboardEntry();
+        entry.playerUid = value["player_uid"];
+        entry.name = value["name"];
+        entry.tilesClaimed = value["tiles_claimed"];
+        if (value["first_bingo_millis"].GetType() != Json::Type::Null)
+            entry.firstBingoMillis = int64(value["first_bingo_millis"]);
+        return entry;
+    }
+}
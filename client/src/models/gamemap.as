@@ -21,6 +21,7 @@ class GameMap {
     int updatedTimestamp;
     string tags;
     string style;
+    string downloadUrl;
 }
 
 namespace GameMap {
@@ -59,6 +60,8 @@ namespace GameMap {
         value["updated_at"] = map.updatedTimestamp;
         value["tags"] = map.tags;
         value["style"] = map.style;
+        if (map.downloadUrl != "")
+            value["download_url"] = map.downloadUrl;
         return value;
     }
 
@@ -88,6 +91,8 @@ namespace GameMap {
             map.tags = value["tags"];
         if (value["style"].GetType() != Json::Type::Null)
             map.style = value["style"];
+        if (value.HasKey("download_url"))
+            map.downloadUrl = value["download_url"];
         return map;
     }
 }
@@ -19,7 +19,10 @@ namespace LiveMatch {
             for (uint j = 0; j < t["members"].Length; j++) {
                 auto playerJson = t["members"][j];
                 Player player(PlayerProfile::Deserialize(playerJson), team);
-                player.holdingPowerup = Powerup(int(playerJson["holding_powerup"]));
+                if (playerJson.HasKey("handicap"))
+                    player.handicap = Handicap::Deserialize(playerJson["handicap"]);
+                Json::Value @inventory = (match.config.sharedInventory ? t["inventory"] : playerJson["inventory"]);
+                Player::EquipInventory(player, inventory);
 
                 match.players.InsertLast(player);
             }
@@ -45,6 +48,14 @@ namespace LiveMatch {
                 cell.claimant = match.GetTeamWithId(int(cell_json["claimant"]));
             match.tiles.InsertLast(cell);
         }
+
+        if (value.HasKey("scores")) {
+            for (uint i = 0; i < value["scores"].Length; i++) {
+                Team @team = match.GetTeamWithId(int(value["scores"][i]["team"]));
+                if (team !is null)
+                    team.score = int(value["scores"][i]["score"]);
+            }
+        }
         return match;
     }
 }
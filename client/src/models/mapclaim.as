@@ -7,6 +7,13 @@ class MapClaim {
 
 namespace MapClaim {
 
+    /* Whether a claim ranks before another one, see `MapClaim::beats` on the server. */
+    bool Beats(MapClaim claim, MapClaim other, bool medalLadder) {
+        if (medalLadder && claim.result.medal != other.result.medal)
+            return int(claim.result.medal) < int(other.result.medal);
+        return claim.result.time < other.result.time;
+    }
+
     Json::Value @Serialize(MapClaim mapClaim) {
         auto value = Json::Object();
         value["player"] = mapClaim.player.AsRef();
@@ -0,0 +1,27 @@
+
+/* A player's result at the end of a practice match. */
+class PracticeResult {
+    string configKey;
+    PracticeGoal goal;
+    bool completed;
+    int tilesClaimed;
+    int64 durationMillis;
+    uint64 createdTimestamp;
+
+    string Summary() {
+        return tilesClaimed + " maps claimed in " + Time::Format(durationMillis);
+    }
+}
+
+namespace PracticeResult {
+    PracticeResult Deserialize(Json::Value @value) {
+        auto result = PracticeResult();
+        result.configKey = value["config_key"];
+        result.goal = PracticeGoal(int(value["goal"]));
+        result.completed = value["completed"];
+        result.tilesClaimed = value["tiles_claimed"];
+        result.durationMillis = int64(value["duration_millis"]);
+        result.createdTimestamp = uint64(value["created_at"]);
+        return result;
+    }
+}
@@ -0,0 +1,25 @@
+/* Room settings saved on the server, by the player or by the server itself. */
+class RoomPreset {
+    int id;
+    string name;
+    string shareCode;
+    bool official;
+    RoomConfiguration config;
+    MatchConfiguration matchConfig;
+    uint64 updatedTimestamp;
+}
+
+namespace RoomPreset {
+    RoomPreset Deserialize(Json::Value @value) {
+        auto preset = RoomPreset();
+        preset.id = value["id"];
+        preset.name = value["name"];
+        preset.official = value["owner_uid"].GetType() == Json::Type::Null;
+        if (value["share_code"].GetType() != Json::Type::Null)
+            preset.shareCode = value["share_code"];
+        preset.config = RoomConfiguration::Deserialize(value["config"]);
+        preset.matchConfig = MatchConfiguration::Deserialize(value["match_config"]);
+        preset.updatedTimestamp = uint64(value["updated_at"]);
+        return preset;
+    }
+}
@@ -0,0 +1,50 @@
+
+/* A room scheduled by a host to open and start at a later time. */
+class ScheduledRoom {
+    string joinCode;
+    int hostUid;
+    RoomConfiguration config;
+    MatchConfiguration matchConfig;
+    array<Team> teams;
+    uint64 startTimestamp;
+    uint minPlayers;
+    dictionary registrations; // player uid -> team index
+
+    /* Index of the team the local player registered to, or -1. */
+    int LocalPlayerTeam() {
+        if (@Profile is null || !registrations.Exists(tostring(Profile.uid)))
+            return -1;
+        return int(registrations[tostring(Profile.uid)]);
+    }
+
+    uint RegisteredCount(uint teamIndex) {
+        uint count = 0;
+        auto keys = registrations.GetKeys();
+        for (uint i = 0; i < keys.Length; i++) {
+            if (int(registrations[keys[i]]) == int(teamIndex))
+                count += 1;
+        }
+        return count;
+    }
+}
+
+namespace ScheduledRoom {
+    ScheduledRoom Deserialize(Json::Value @value) {
+        auto room = ScheduledRoom();
+        room.joinCode = value["join_code"];
+        room.hostUid = value["host_uid"];
+        room.config = RoomConfiguration::Deserialize(value["config"]);
+        room.matchConfig = MatchConfiguration::Deserialize(value["match_config"]);
+        for (uint i = 0; i < value["teams"].Length; i++) {
+            room.teams.InsertLast(Team::Deserialize(value["teams"][i]));
+        }
+        room.startTimestamp = uint64(value["start_at"]);
+        room.minPlayers = uint(value["min_players"]);
+        for (uint i = 0; i < value["registrations"].Length; i++) {
+            Json::Value @registration = value["registrations"][i];
+            room.registrations.Set(tostring(int(registration["player_uid"])),
+                                   int(registration["team_index"]));
+        }
+        return room;
+    }
+}
@@ -56,6 +56,7 @@ namespace Network {
 
     void Connect() {
         SetOfflineMode(false);
+        UIMaintenance::Clear();
 
         Timings::LastPingSent = Time::Now;
         Timings::LastPingReceived = Time::Now;
@@ -225,6 +226,12 @@ namespace Network {
             NetworkHandlers::AnnounceWinByCellCount(body);
         } else if (event == "AnnounceDraw") {
             NetworkHandlers::AnnounceDraw(body);
+        } else if (event == "AnnounceWinByPoints") {
+            NetworkHandlers::AnnounceWinByPoints(body);
+        } else if (event == "ScoresUpdate") {
+            NetworkHandlers::ScoresUpdate(body);
+        } else if (event == "PracticeEnd") {
+            NetworkHandlers::PracticeEnd(body);
         } else if (event == "MatchTeamCreated") {
             NetworkHandlers::MatchTeamCreated(body);
         } else if (event == "MatchPlayerJoin") {
@@ -243,14 +250,18 @@ namespace Network {
             NetworkHandlers::PowerupSpawn(body);
         } else if (event == "PowerupActivated") {
             NetworkHandlers::PowerupActivated(body);
-        } else if (event == "ItemSlotEquip") {
-            NetworkHandlers::ItemSlotEquip(body);
+        } else if (event == "InventoryUpdate") {
+            NetworkHandlers::InventoryUpdate(body);
         } else if (event == "RallyResolved") {
             NetworkHandlers::RallyResolved(body);
         } else if (event == "JailResolved") {
             NetworkHandlers::JailResolved(body);
         } else if (event == "RoomExtrasUpdate") {
             NetworkHandlers::RoomExtrasUpdate(body);
+        } else if (event == "MaintenanceNotice") {
+            NetworkHandlers::MaintenanceNotice(body);
+        } else if (event == "Announcement") {
+            NetworkHandlers::Announcement(body);
         } else {
             logwarn("[Network] Unknown event: " + string(body["event"]));
         }
@@ -446,7 +457,11 @@ namespace Network {
             joinedMatch.isLocalPlayerHost = Match.isLocalPlayerHost;
             joinedMatch.joinCode = Match.joinCode;
         }
+        EnterMatch(joinedMatch);
+    }
 
+    /* Start playing a match that was just joined. */
+    void EnterMatch(GameServer @joinedMatch) {
         Gamemaster::SetBingoActive(true);
         UITeams::CloseContext();
         UIChat::ClearHistory();
@@ -471,6 +486,12 @@ namespace Network {
             rooms.InsertLast(NetworkRoom::Deserialize(response["rooms"][i]));
         }
         UIRoomMenu::PublicRooms = rooms;
+
+        auto scheduled = array<ScheduledRoom>();
+        for (uint i = 0; i < response["scheduled"].Length; i++) {
+            scheduled.InsertLast(ScheduledRoom::Deserialize(response["scheduled"][i]));
+        }
+        UISchedule::ListedRooms = scheduled;
         UIRoomMenu::RoomsLoad = LoadStatus::Ok;
     }
 
@@ -567,9 +588,186 @@ namespace Network {
         Network::Post("ChangePlayerTeam", body, false);
     }
 
+    /* Set the handicap of NetParams::TeamSelectId, or of NetParams::PlayerSelectUid if no team is selected. */
+    void SetHandicap() {
+        auto body = Json::Object();
+        if (NetParams::TeamSelectId >= 0)
+            body["team_id"] = NetParams::TeamSelectId;
+        else
+            body["player_uid"] = NetParams::PlayerSelectUid;
+        auto handicap = Json::Object();
+        if (NetParams::HandicapMedal != Medal::None)
+            handicap["target_medal"] = int(NetParams::HandicapMedal);
+        handicap["time_allowance"] = NetParams::HandicapTimeAllowance;
+        handicap["head_start"] = NetParams::HandicapHeadStart;
+        body["handicap"] = handicap;
+        Network::Post("SetHandicap", body, true);
+    }
+
+    void GetPresets() {
+        auto response = Post("GetPresets", Json::Object(), false);
+        if (response is null) {
+            logtrace("[Network] GetPresets - No reply from server.");
+            UIPresets::PresetsLoad = LoadStatus::Error;
+            return;
+        }
+
+        auto presets = array<RoomPreset>();
+        for (uint i = 0; i < response["presets"].Length; i++) {
+            presets.InsertLast(RoomPreset::Deserialize(response["presets"][i]));
+        }
+        UIPresets::Presets = presets;
+        UIPresets::PresetsLoad = LoadStatus::Ok;
+    }
+
+    /* Start a solo match on today's daily challenge board. */
+    void JoinDailyChallenge() {
+        auto response = Post("JoinDailyChallenge", Json::Object(), true);
+        if (response is null) {
+            logtrace("[Network] JoinDailyChallenge - No reply from server.");
+            return;
+        }
+
+        GameServer @dailyMatch = LiveMatch::Deserialize(response["state"]);
+        dailyMatch.roomConfig.name = "Daily Challenge";
+        EnterMatch(dailyMatch);
+    }
+
+    void GetDailyLeaderboard() {
+        auto response = Post("GetDailyLeaderboard", Json::Object(), false);
+        if (response is null) {
+            logtrace("[Network] GetDailyLeaderboard - No reply from server.");
+            UIDaily::LeaderboardLoad = LoadStatus::Error;
+            return;
+        }
+
+        auto entries = array<DailyLeaderboardEntry>();
+        for (uint i = 0; i < response["entries"].Length; i++) {
+            entries.InsertLast(DailyLeaderboardEntry::Deserialize(response["entries"][i]));
+        }
+        UIDaily::Date = response["date"];
+        UIDaily::Leaderboard = entries;
+        UIDaily::LeaderboardLoad = LoadStatus::Ok;
+    }
+
+    void GetPersonalBests() {
+        auto response = Post("GetPersonalBests", Json::Object(), false);
+        if (response is null) {
+            logtrace("[Network] GetPersonalBests - No reply from server.");
+            UIPractice::BestsLoad = LoadStatus::Error;
+            return;
+        }
+
+        auto bests = array<PracticeResult>();
+        for (uint i = 0; i < response["bests"].Length; i++) {
+            bests.InsertLast(PracticeResult::Deserialize(response["bests"][i]));
+        }
+        UIPractice::Bests = bests;
+        UIPractice::BestsLoad = LoadStatus::Ok;
+    }
+
+    Json::Value @PresetBody() {
+        auto body = Json::Object();
+        body["name"] = NetParams::PresetName;
+        body["config"] = RoomConfiguration::Serialize(RoomConfig);
+        body["match_config"] = MatchConfiguration::Serialize(MatchConfig);
+        auto teams = Json::Array();
+        for (uint i = 0; i < TeamPresets.Length; i++) {
+            teams.Add(Team::Serialize(TeamPresets[i]));
+        }
+        body["teams"] = teams;
+        return body;
+    }
+
+    /* Save the current settings as a new preset named NetParams::PresetName. */
+    void CreatePreset() {
+        if (Post("CreatePreset", PresetBody(), true) !is null)
+            GetPresets();
+    }
+
+    /* Replace the settings of preset NetParams::PresetId with the current ones. */
+    void UpdatePreset() {
+        auto body = PresetBody();
+        body["id"] = NetParams::PresetId;
+        if (Post("UpdatePreset", body, true) !is null)
+            GetPresets();
+    }
+
+    void DeletePreset() {
+        auto body = Json::Object();
+        body["id"] = NetParams::PresetId;
+        if (Post("DeletePreset", body, true) !is null)
+            GetPresets();
+    }
+
+    void ImportPreset() {
+        auto body = Json::Object();
+        body["share_code"] = NetParams::PresetShareCode;
+        if (Post("ImportPreset", body, true) !is null)
+            GetPresets();
+    }
+
+    /* Schedule a room with the current settings to start at NetParams::ScheduleStartTimestamp. */
+    void ScheduleRoom() {
+        MatchConfig.game = CURRENT_GAME;
+
+        if (TeamPresets.Length < 2) {
+            PersistantStorage::LoadTeamEditor();
+        }
+
+        auto body = Json::Object();
+        body["config"] = RoomConfiguration::Serialize(RoomConfig);
+        body["match_config"] = MatchConfiguration::Serialize(MatchConfig);
+        auto teams = Json::Array();
+        for (uint i = 0; i < TeamPresets.Length; i++) {
+            teams.Add(Team::Serialize(TeamPresets[i]));
+        }
+        body["teams"] = teams;
+        body["start_at"] = NetParams::ScheduleStartTimestamp;
+        body["min_players"] = NetParams::ScheduleMinPlayers;
+
+        auto response = Post("ScheduleRoom", body, true);
+        if (response is null) {
+            logtrace("[Network] ScheduleRoom - No reply from server.");
+            return;
+        }
+        UISchedule::UpdateRoom(ScheduledRoom::Deserialize(response));
+    }
+
+    /* Register to team NetParams::ScheduleTeamIndex of scheduled room NetParams::JoinCode, -1 to unregister. */
+    void RegisterScheduledRoom() {
+        auto body = Json::Object();
+        body["join_code"] = NetParams::JoinCode;
+        if (NetParams::ScheduleTeamIndex >= 0)
+            body["team"] = NetParams::ScheduleTeamIndex;
+
+        auto response = Post("RegisterScheduledRoom", body, true);
+        if (response is null) {
+            logtrace("[Network] RegisterScheduledRoom - No reply from server.");
+            return;
+        }
+        UISchedule::UpdateRoom(ScheduledRoom::Deserialize(response));
+    }
+
+    void CancelScheduledRoom() {
+        auto body = Json::Object();
+        body["join_code"] = NetParams::JoinCode;
+        if (Post("CancelScheduledRoom", body, true) !is null)
+            UISchedule::RemoveRoom(NetParams::JoinCode);
+    }
+
+    void BlacklistMap() {
+        auto body = Json::Object();
+        body["tmxid"] = NetParams::BlacklistTmxId;
+        body["reason"] = NetParams::BlacklistReason;
+        Network::Post("BlacklistMap", body, true);
+    }
+
     void ActivatePowerup() {
         auto body = Json::Object();
         body["powerup"] = int(NetParams::Powerup);
+        Player @self = Match.GetSelf();
+        if (@self !is null && self.holdingItemId >= 0) body["item_id"] = self.holdingItemId;
         body["board_index"] = NetParams::PowerupBoardIndex;
         body["forwards"] = NetParams::PowerupBoardIsForward;
         body["player_uid"] = NetParams::PlayerSelectUid;
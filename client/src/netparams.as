@@ -16,4 +16,15 @@ namespace NetParams {
     int PowerupBoardIndex;
     int PowerupChoiceIndex;
     bool PowerupBoardIsForward;
+    Medal HandicapMedal = Medal::None;
+    int HandicapTimeAllowance;
+    uint HandicapHeadStart;
+    int PresetId;
+    string PresetName;
+    string PresetShareCode;
+    uint64 ScheduleStartTimestamp;
+    uint ScheduleMinPlayers;
+    int ScheduleTeamIndex = -1;
+    int BlacklistTmxId;
+    string BlacklistReason;
 }
@@ -118,8 +118,9 @@ namespace Playground {
 
         MatchConfiguration config = Gamemaster::GetConfiguration();
 
+        // claims include the time allowance of the player's handicap
         MapClaim @myRun = currentTile.GetLocalPlayerRun();
-        if (myRun !is null && myRun.result.time <= result.time)
+        if (myRun !is null && myRun.result.time <= result.time + Match.GetTimeAllowance())
             return;
 
         int minimumTime = Playground::GetCurrentTimeToBeat(true).time;
@@ -146,12 +147,19 @@ namespace Playground {
         if (currentTile.map is null)
             return null;
 
+        // The server adds the handicap time allowance to our runs, so the time to beat is shifted by it
+        int allowance = Match.GetTimeAllowance();
+
         // Map is claimed, return the top run
-        if (!ignorePlayerClaims && currentTile.HasRunSubmissions())
-            return currentTile.LeadingRun().result;
+        if (!ignorePlayerClaims && currentTile.HasRunSubmissions()) {
+            RunResult leading = currentTile.LeadingRun().result;
+            return RunResult(leading.time - allowance, leading.medal);
+        }
 
         // Map is not claimed, get the target medal time
-        return RunResult(objectiveOf(Match.config.targetMedal, currentTile.map), Match.config.targetMedal);
+        Medal targetMedal = Match.GetTargetMedal();
+        int objective = objectiveOf(targetMedal, currentTile.map);
+        return RunResult(objective == -1 ? -1 : objective - allowance, targetMedal);
     }
 
     Medal CalculateMedal(int time, int author, int gold, int silver, int bronze) {
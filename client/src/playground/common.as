@@ -36,7 +36,10 @@ namespace Playground {
     void PlayMap(GameMap map) {
         switch (map.type) {
         case MapType::TMX:
-            PlayMap("https://trackmania.exchange/maps/download/" + map.id,
+            // maps from a local manifest can be downloaded from elsewhere than TMX
+            PlayMap(map.downloadUrl != ""
+                        ? map.downloadUrl
+                        : "https://trackmania.exchange/maps/download/" + map.id,
                     "TrackMania/Bingo_PlayMap_Local");
             break;
         default:
@@ -0,0 +1,71 @@
+
+namespace UIDaily {
+    LoadStatus LeaderboardLoad = LoadStatus::NotLoaded;
+    array<DailyLeaderboardEntry> Leaderboard;
+    string Date;
+
+    /* The daily challenge in the Play tab: a button to play today's board and its leaderboard. */
+    void Render() {
+        UITools::SectionHeader("Daily Challenge");
+        UI::TextWrapped("\\$aaaA new board every day, the same for everyone. Claim as many maps as "
+                        "you can on your own!");
+
+        UI::BeginDisabled(!Config::CanPlay || !Network::IsConnected() || Network::IsUISuspended());
+        UIColor::Orange();
+        if (UI::Button(Icons::CalendarCheckO + " Play Today's Challenge")) {
+            Gamemaster::SetBingoActive(false);
+            startnew(Network::JoinDailyChallenge);
+        }
+        UIColor::Reset();
+        UI::EndDisabled();
+        UITools::ErrorMessage("JoinDailyChallenge");
+
+        if (UI::CollapsingHeader(Icons::ListOl + " Leaderboard")) {
+            if (LeaderboardLoad == LoadStatus::NotLoaded && Network::IsConnected()) {
+                LeaderboardLoad = LoadStatus::Loading;
+                startnew(Network::GetDailyLeaderboard);
+            }
+            LeaderboardView();
+        }
+        UI::NewLine();
+    }
+
+    void LeaderboardView() {
+        if (LeaderboardLoad == LoadStatus::Loading) {
+            UI::Text("\\$aa4" + Icons::Hourglass + " \\$zLoading leaderboard...");
+            return;
+        }
+        if (LeaderboardLoad == LoadStatus::Error) {
+            UI::Text("\\$888No leaderboard is available for today yet.");
+            UI::SameLine();
+            if (UI::Button(Icons::Refresh + " Retry"))
+                LeaderboardLoad = LoadStatus::NotLoaded;
+            return;
+        }
+        if (Leaderboard.Length == 0) {
+            UI::Text("\\$888Nobody has played the challenge of " + Date + " yet.");
+            return;
+        }
+
+        if (!UI::BeginTable("bingodailyleaderboard", 3, UI::TableFlags::SizingFixedFit))
+            return;
+        UI::TableSetupColumn("player", UI::TableColumnFlags::WidthStretch);
+        for (uint i = 0; i < Leaderboard.Length; i++) {
+            DailyLeaderboardEntry entry = Leaderboard[i];
+            bool isSelf = @Profile !is null && entry.playerUid == Profile.uid;
+            UI::TableNextColumn();
+            UI::Text((isSelf ? "\\$ff8" : "") + (i + 1) + ". " + entry.name);
+
+            UI::TableNextColumn();
+            UI::Text(entry.tilesClaimed + " maps");
+
+            UI::TableNextColumn();
+            if (entry.firstBingoMillis >= 0)
+                UI::Text("\\$888First bingo " + Time::Format(entry.firstBingoMillis));
+        }
+        UI::EndTable();
+
+        if (UI::Button(Icons::Refresh + " Refresh"))
+            LeaderboardLoad = LoadStatus::NotLoaded;
+    }
+}
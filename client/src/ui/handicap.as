@@ -0,0 +1,75 @@
+
+namespace UIHandicap {
+    bool Visible;
+    string TargetName;
+
+    /* Open the editor for a team's handicap. */
+    void EditTeam(Team team) {
+        NetParams::TeamSelectId = team.id;
+        TargetName = "\\$" + UIColor::GetHex(team.color) + team.name;
+        Load(team.handicap);
+    }
+
+    /* Open the editor for a player's handicap. */
+    void EditPlayer(Player player) {
+        NetParams::TeamSelectId = -1;
+        NetParams::PlayerSelectUid = player.profile.uid;
+        TargetName = player.name;
+        Load(player.handicap);
+    }
+
+    void Load(Handicap handicap) {
+        NetParams::HandicapMedal = handicap.targetMedal;
+        NetParams::HandicapTimeAllowance = handicap.timeAllowance;
+        NetParams::HandicapHeadStart = handicap.headStart;
+        Visible = true;
+    }
+
+    void Render() {
+        if (!Visible)
+            return;
+        if (@Match is null || !Match.isLocalPlayerHost || Gamemaster::IsBingoActive()) {
+            Visible = false;
+            return;
+        }
+
+        UI::SetNextWindowSize(360, 200, UI::Cond::FirstUseEver);
+        UI::Begin(Icons::BalanceScale + " Handicap###bingohandicap", Visible);
+        UI::Text("Handicap of " + TargetName);
+        UI::Separator();
+
+        UI::SetNextItemWidth(160);
+        if (UI::BeginCombo("Target medal", NetParams::HandicapMedal == Medal::None
+                                               ? "Match default"
+                                               : stringof(NetParams::HandicapMedal))) {
+            if (UI::Selectable("Match default", NetParams::HandicapMedal == Medal::None))
+                NetParams::HandicapMedal = Medal::None;
+            array<Medal> medals = {Medal::WR, Medal::Author, Medal::Gold, Medal::Silver, Medal::Bronze};
+            for (uint i = 0; i < medals.Length; i++) {
+                if (UI::Selectable(stringof(medals[i]), NetParams::HandicapMedal == medals[i]))
+                    NetParams::HandicapMedal = medals[i];
+            }
+            UI::EndCombo();
+        }
+
+        UI::SetNextItemWidth(160);
+        NetParams::HandicapTimeAllowance =
+            UI::InputInt("Time allowance (ms)", NetParams::HandicapTimeAllowance, 100);
+        UI::SetItemTooltip("Added to every submitted time. Negative values reduce them.");
+
+        UI::SetNextItemWidth(160);
+        int cellCount = int(Match.config.gridSize * Match.config.gridSize);
+        NetParams::HandicapHeadStart = uint(
+            Math::Clamp(UI::InputInt("Head start tiles", int(NetParams::HandicapHeadStart)), 0, cellCount));
+
+        if (UI::Button(Icons::Check + " Apply"))
+            startnew(Network::SetHandicap);
+        UI::SameLine();
+        if (UI::Button(Icons::Times + " Clear")) {
+            Load(Handicap());
+            startnew(Network::SetHandicap);
+        }
+        UITools::ErrorMessage("SetHandicap");
+        UI::End();
+    }
+}
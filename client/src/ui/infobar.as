@@ -39,7 +39,7 @@ namespace UIInfoBar {
 
     void MapLeaderboard(GameTile map) {
         vec4 geometry = SubwindowBegin("Bingo Map Leaderboard");
-        if (map.attemptRanking.Length == 0 && Match.config.targetMedal == Medal::None) {
+        if (map.attemptRanking.Length == 0 && Match.GetTargetMedal() == Medal::None) {
             UI::Text("\\$888Complete this map to claim it!");
             SubwindowEnd(geometry);
             return;
@@ -67,7 +67,7 @@ namespace UIInfoBar {
 
             Font::Unset();
         }
-        if (Match.config.targetMedal != Medal::None) {
+        if (Match.GetTargetMedal() != Medal::None) {
             if (map.attemptRanking.Length > 0) {
                 Layout::MoveTo(MAP_LEADERBOARD_SIDE_MARGIN);
             }
@@ -372,6 +372,27 @@ namespace UIInfoBar {
             // duplicated map selector
             UIMapList::Visible = false;
         }
+
+        if (@localPlayer !is null && localPlayer.inventory.Length > 1)
+            InventoryItems(localPlayer);
+    }
+
+    /* The other items of the inventory next to the item slot, clicking one holds it instead. */
+    void InventoryItems(Player@ player) {
+        for (uint i = 0; i < player.inventory.Length; i++) {
+            InventoryItem item = player.inventory[i];
+            if (item.id == player.holdingItemId)
+                continue;
+
+            UI::SameLine();
+            UI::Image(Powerups::GetPowerupTexture(item.powerup),
+                      vec2(POWERUP_FRAME_SIZE, POWERUP_FRAME_SIZE) * .75);
+            if (UI::IsItemClicked()) {
+                Player::SelectItem(player, i);
+                UIItemSelect::Visible = false;
+            }
+            UI::SetItemTooltip("Hold this item to use it next.");
+        }
     }
 
     void MvpTextFrame() {
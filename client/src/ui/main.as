@@ -37,6 +37,7 @@ namespace UIMainWindow {
 
             UI::Dummy(vec2(0, 20));
         }
+        UIMaintenance::Render();
 
         UI::SetCursorPos(UI::GetCursorPos() - vec2(0, 10));
         if (@Profile != null) {
@@ -95,6 +96,9 @@ namespace UIMainWindow {
     }
 
     void CreateTab() {
+        UIPresets::Render();
+        UISchedule::Render();
+        UIPractice::Render();
         UIRoomSettings::SettingsView();
         CreateRoomButton();
         UITools::ConnectingIndicator();
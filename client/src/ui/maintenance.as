@@ -0,0 +1,31 @@
+
+namespace UIMaintenance {
+    // Set when the server announces a restart, until the next connection
+    string Message;
+    int64 Deadline;
+
+    void Clear() {
+        Message = "";
+        Deadline = 0;
+    }
+
+    /* Banner shown at the top of the main window while the server is about to restart. */
+    void Render() {
+        if (Message == "")
+            return;
+
+        UI::PushStyleColor(UI::Col::ChildBg, vec4(.5, .3, 0., .9));
+        UI::PushStyleVar(UI::StyleVar::ChildBorderSize, .5f);
+        UI::BeginChild("###bingomaintenance", vec2(0, 70), true);
+        UI::TextWrapped("\\$fd8" + Icons::Wrench + "  \\$z" + Message);
+        if (Deadline > 0) {
+            int64 remaining = Math::Max(Deadline - Time::Stamp, 0);
+            UI::Text("\\$fd8Restarting in " + Time::Format(remaining * 1000, false, true, true));
+        }
+        UI::EndChild();
+        UI::PopStyleVar();
+        UI::PopStyleColor();
+
+        UI::Dummy(vec2(0, 10));
+    }
+}
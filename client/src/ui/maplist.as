@@ -234,7 +234,7 @@ namespace UIMapList {
                 UI::Text(tile.LeadingRun().result.Display());
             }
         } else {
-            Medal targetMedal = (@Match !is null ? Match.config.targetMedal : Medal::None);
+            Medal targetMedal = (@Match !is null ? Match.GetTargetMedal() : Medal::None);
             
             if (targetMedal == Medal::None || isSecret) {
                 UI::TextDisabled("This map has not been claimed yet!");
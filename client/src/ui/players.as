@@ -71,9 +71,14 @@ namespace UIPlayers {
                              (canSwitch && (ownTeam is null || team != ownTeam)
                                   ? "  \\$888(Click to join)"
                                   : ""));
+                    if (canDragPlayers)
+                        UI::TextDisabled("Right click to set a handicap");
                     UI::EndTooltip();
                 }
 
+                if (canDragPlayers && UI::IsItemClicked(UI::MouseButton::Right))
+                    UIHandicap::EditTeam(team);
+
                 if (canSwitch && UI::IsItemClicked()) {
                     startnew(function(ref @team) { Network::JoinTeam(cast<Team>(team)); }, team);
 
@@ -164,6 +169,9 @@ namespace UIPlayers {
         if (canBeSelected && UI::IsItemClicked()) {
             @UIGameRoom::DraggedPlayer = player;
         }
+        if (canBeSelected && UI::IsItemClicked(UI::MouseButton::Right)) {
+            UIHandicap::EditPlayer(player);
+        }
         if (UIItemSelect::HookingPlayerClick && UI::IsItemClicked()) {
             UIItemSelect::OnPlayerClicked(player);
         }
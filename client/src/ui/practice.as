@@ -0,0 +1,50 @@
+
+namespace UIPractice {
+    LoadStatus BestsLoad = LoadStatus::NotLoaded;
+    array<PracticeResult> Bests;
+
+    /* Personal bests of the local player in practice matches, shown while a practice goal is selected. */
+    void Render() {
+        if (MatchConfig.practiceGoal == PracticeGoal::None)
+            return;
+        if (!UI::CollapsingHeader(Icons::Trophy + " Personal Bests"))
+            return;
+        if (BestsLoad == LoadStatus::NotLoaded && Network::IsConnected()) {
+            BestsLoad = LoadStatus::Loading;
+            startnew(Network::GetPersonalBests);
+        }
+
+        if (BestsLoad == LoadStatus::Loading) {
+            UI::Text("\\$aa4" + Icons::Hourglass + " \\$zLoading personal bests...");
+        } else if (BestsLoad == LoadStatus::Error) {
+            UI::Text("\\$888Personal bests failed to load.");
+            UI::SameLine();
+            UITools::ReconnectButton();
+        } else if (Bests.Length == 0) {
+            UI::Text("\\$888You have not finished any practice match yet.");
+        } else {
+            BestsTable();
+        }
+        UI::Separator();
+    }
+
+    void BestsTable() {
+        if (!UI::BeginTable("bingopracticebests", 3, UI::TableFlags::SizingFixedFit))
+            return;
+        UI::TableSetupColumn("settings", UI::TableColumnFlags::WidthStretch);
+        for (uint i = 0; i < Bests.Length; i++) {
+            PracticeResult best = Bests[i];
+            UI::TableNextColumn();
+            UI::Text(stringof(best.goal));
+            // the key lists the settings the result is compared against
+            UI::SetItemTooltip(best.configKey);
+
+            UI::TableNextColumn();
+            UI::Text((best.completed ? "\\$8f8" + Icons::Check + " \\$z" : "") + best.Summary());
+
+            UI::TableNextColumn();
+            UI::Text("\\$888" + Time::FormatString("%Y-%m-%d", best.createdTimestamp));
+        }
+        UI::EndTable();
+    }
+}
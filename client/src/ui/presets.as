@@ -0,0 +1,107 @@
+
+namespace UIPresets {
+    LoadStatus PresetsLoad = LoadStatus::NotLoaded;
+    array<RoomPreset> Presets;
+    string NewPresetName;
+    string ImportCodeInput;
+
+    /* Apply the settings of a preset to the Create tab. */
+    void LoadPreset(RoomPreset preset) {
+        RoomConfig = preset.config;
+        MatchConfig = preset.matchConfig;
+        UIRoomSettings::SaveConfiguredSettings();
+    }
+
+    void Render() {
+        if (!UI::CollapsingHeader(Icons::Bookmark + " Presets"))
+            return;
+        if (PresetsLoad == LoadStatus::NotLoaded && Network::IsConnected()) {
+            PresetsLoad = LoadStatus::Loading;
+            startnew(Network::GetPresets);
+        }
+
+        if (PresetsLoad == LoadStatus::Loading) {
+            UI::Text("\\$aa4" + Icons::Hourglass + " \\$zLoading presets...");
+        } else if (PresetsLoad == LoadStatus::Error) {
+            UI::Text("\\$888Presets failed to load.");
+            UI::SameLine();
+            UITools::ReconnectButton();
+        } else {
+            PresetList();
+        }
+
+        UI::BeginDisabled(!Network::IsConnected() || Network::IsUISuspended());
+        UI::SetNextItemWidth(200);
+        NewPresetName = UI::InputText("##bingopresetname", NewPresetName);
+        UI::SameLine();
+        UI::BeginDisabled(NewPresetName == "");
+        if (UI::Button(Icons::FloppyO + " Save current settings")) {
+            UIRoomSettings::SaveConfiguredSettings();
+            NetParams::PresetName = NewPresetName;
+            NewPresetName = "";
+            startnew(Network::CreatePreset);
+        }
+        UI::EndDisabled();
+
+        UI::SetNextItemWidth(200);
+        ImportCodeInput = UI::InputText(
+            "##bingopresetcode", ImportCodeInput, UI::InputTextFlags::CharsUppercase);
+        UI::SameLine();
+        UI::BeginDisabled(ImportCodeInput.Length < 6);
+        if (UI::Button(Icons::Download + " Import shared preset")) {
+            NetParams::PresetShareCode = ImportCodeInput;
+            ImportCodeInput = "";
+            startnew(Network::ImportPreset);
+        }
+        UI::EndDisabled();
+        UI::EndDisabled();
+
+        UITools::ErrorMessage("CreatePreset");
+        UITools::ErrorMessage("UpdatePreset");
+        UITools::ErrorMessage("DeletePreset");
+        UITools::ErrorMessage("ImportPreset");
+        UI::Separator();
+    }
+
+    void PresetList() {
+        if (!UI::BeginTable("bingopresets", 2, UI::TableFlags::SizingFixedFit))
+            return;
+        UI::TableSetupColumn("name", UI::TableColumnFlags::WidthStretch);
+        for (uint i = 0; i < Presets.Length; i++) {
+            RoomPreset preset = Presets[i];
+            UI::TableNextColumn();
+            UI::AlignTextToFramePadding();
+            UI::Text((preset.official ? "\\$fd8" + Icons::Star + " \\$z" : "") + preset.name);
+
+            UI::TableNextColumn();
+            if (UI::Button(Icons::Upload + " Load##bingopresetload" + i))
+                LoadPreset(preset);
+            UI::SetItemTooltip("Use the settings of this preset in the room settings below.");
+            if (preset.official)
+                continue;
+
+            UI::SameLine();
+            if (UI::Button(Icons::Share + "##bingopresetshare" + i))
+                IO::SetClipboard(preset.shareCode);
+            UI::SetItemTooltip("Copy the share code \\$ff8" + preset.shareCode +
+                               "\\$z to the clipboard.");
+            UI::SameLine();
+            if (UI::Button(Icons::FloppyO + "##bingopresetupdate" + i)) {
+                UIRoomSettings::SaveConfiguredSettings();
+                NetParams::PresetId = preset.id;
+                NetParams::PresetName = preset.name;
+                startnew(Network::UpdatePreset);
+            }
+            UI::SetItemTooltip("Replace this preset with the current room settings.");
+            UI::SameLine();
+            UIColor::DarkRed();
+            if (UI::Button(Icons::TrashO + "##bingopresetdelete" + i)) {
+                NetParams::PresetId = preset.id;
+                startnew(Network::DeletePreset);
+            }
+            UIColor::Reset();
+            UI::SetItemTooltip("Delete this preset.");
+        }
+        UI::EndTable();
+    }
+}
@@ -315,6 +315,9 @@ namespace UIGameRoom {
                 break;
             }
         }
+        for (uint i = 0; i < Match.maploadWarnings.Length; i++) {
+            tooltipText += "\n" + Match.maploadWarnings[i];
+        }
 
         Layout::AlignText(bodyText, 0.99);
         UI::Text(bodyText);
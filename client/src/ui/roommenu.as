@@ -84,6 +84,7 @@ namespace UIRoomMenu {
             if (PublicRooms.Length == 0) {
                 UI::Text("\\$888There are no open public rooms at the moment.\nGo ahead and create "
                          "one!");
+                UISchedule::ScheduledRoomList();
                 UI::EndChild();
                 return;
             }
@@ -150,6 +151,7 @@ namespace UIRoomMenu {
             UI::TableNextColumn();
             UI::EndTable();
             UI::PopStyleColor();
+            UISchedule::ScheduledRoomList();
         }
         UI::EndChild();
     }
@@ -199,6 +201,8 @@ namespace UIRoomMenu {
         Matchmaking();
         */
 
+        UIDaily::Render();
+
         UITools::SectionHeader("Public Rooms");
         if (Network::GetState() == ConnectionState::Connected) {
             PublicRoomList();
@@ -108,6 +108,16 @@ namespace UIRoomSettings {
                 @SelectedPack = null;
             }
 
+            if (UI::Selectable(stringof(MapMode::Local), MatchConfig.selection == MapMode::Local)) {
+                MatchConfig.selection = MapMode::Local;
+                @SelectedPack = null;
+            }
+
+            if (UI::Selectable(stringof(MapMode::Custom), MatchConfig.selection == MapMode::Custom)) {
+                MatchConfig.selection = MapMode::Custom;
+                @SelectedPack = null;
+            }
+
             for (uint i = 0; i < Config::FeaturedMappacks.Length; i++) {
                 FeaturedMappack pack = Config::FeaturedMappacks[i];
                 if (UI::Selectable("\\$ff8Featured Mappack: \\$z" + pack.name,
@@ -197,6 +207,39 @@ namespace UIRoomSettings {
         MatchConfig.mappackId = UI::InputInt("##bingomappack", MatchConfig.mappackId, 0);
     }
 
+    string CustomMapsText = "";
+
+    void CustomMapsInput() {
+        UITools::AlignedLabel(Icons::ListOl + "  Picked Maps");
+        Layout::MoveTo(GAME_SETTINGS_ALIGN_X * UI::GetScale());
+        string text = UI::InputTextMultiline("##bingocustommaps", CustomMapsText, vec2(250, 120));
+        UI::SameLine();
+        UITools::HelpTooltip("One map per line, by TMX ID or map UID. Add @ and a cell number to place a map on the board, e.g. 123456@1 for the top left cell.");
+        if (text == CustomMapsText) return;
+
+        CustomMapsText = text;
+        MatchConfig.customMaps.RemoveRange(0, MatchConfig.customMaps.Length);
+        array<string>@ lines = text.Split("\n");
+        for (uint i = 0; i < lines.Length; i++) {
+            array<string>@ parts = lines[i].Trim().Split("@");
+            string id = parts[0].Trim();
+            if (id == "") continue;
+
+            CustomMap map();
+            uint tmxid;
+            if (Text::TryParseUInt(id, tmxid)) {
+                map.tmxid = tmxid;
+            } else {
+                map.uid = id;
+            }
+            uint cell;
+            if (parts.Length > 1 && Text::TryParseUInt(parts[1].Trim(), cell)) {
+                map.position = cell;
+            }
+            MatchConfig.customMaps.InsertLast(map);
+        }
+    }
+
     void MapTagSelector() {
         // FIX: mapTag should not be less than 1, as it is invalid. Correct it if it was loaded from an
         // invalid LastConfig
@@ -253,6 +296,29 @@ namespace UIRoomSettings {
         }
     }
 
+    void PracticeGoalSelector() {
+        UITools::AlignedLabel(Icons::User + "  Solo Practice");
+        Layout::MoveTo(GAME_SETTINGS_ALIGN_X * UI::GetScale());
+        UI::SetNextItemWidth(250);
+        if (UI::BeginCombo("##bingopractice", stringof(MatchConfig.practiceGoal))) {
+            array<PracticeGoal> goals = {PracticeGoal::None,
+                                         PracticeGoal::Bingo,
+                                         PracticeGoal::Blackout,
+                                         PracticeGoal::TimeAttack};
+            for (uint i = 0; i < goals.Length; i++) {
+                if (UI::Selectable(stringof(goals[i]), MatchConfig.practiceGoal == goals[i])) {
+                    MatchConfig.practiceGoal = goals[i];
+                }
+            }
+            UI::EndCombo();
+        }
+        UI::SameLine();
+        UITools::HelpTooltip("Play the board alone to beat your personal best.\nSolo Bingo: "
+                             "complete a line as fast as possible.\nBlackout: claim every map "
+                             "as fast as possible.\nTime Attack: claim as many maps as possible "
+                             "before the time limit.");
+    }
+
     void OvertimeToggle() {
         LabelAdvancedSettings(Icons::PlusSquare + " Enable Overtime");
         MatchConfig.overtime = UI::Checkbox("##bingoovertime", MatchConfig.overtime);
@@ -389,12 +455,16 @@ namespace UIRoomSettings {
         UI::SameLine();
         AccessToggle();
         TargetMedalSelector();
+        PracticeGoalSelector();
         if (MatchConfig.selection == MapMode::Tags) {
             MapTagSelector();
         }
         if (MatchConfig.selection == MapMode::Mappack) {
             MappackIdInput();
         }
+        if (MatchConfig.selection == MapMode::Custom) {
+            CustomMapsInput();
+        }
 
         GridSizeSelector();
         TimeLimitControl();
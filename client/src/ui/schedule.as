@@ -0,0 +1,140 @@
+
+namespace UISchedule {
+    // Public scheduled rooms, loaded with the public room list
+    array<ScheduledRoom> ListedRooms;
+    // Rooms scheduled by the local player during this session
+    array<ScheduledRoom> OwnRooms;
+    int StartDelayHours = 1;
+    int StartDelayMinutes = 0;
+    int MinPlayers = 0;
+
+    void UpdateRoom(ScheduledRoom room) {
+        bool found = false;
+        for (uint i = 0; i < OwnRooms.Length; i++) {
+            if (OwnRooms[i].joinCode == room.joinCode) {
+                OwnRooms[i] = room;
+                found = true;
+            }
+        }
+        if (!found && @Profile !is null && room.hostUid == Profile.uid)
+            OwnRooms.InsertLast(room);
+
+        for (uint i = 0; i < ListedRooms.Length; i++) {
+            if (ListedRooms[i].joinCode == room.joinCode)
+                ListedRooms[i] = room;
+        }
+    }
+
+    void RemoveRoom(const string& in joinCode) {
+        for (int i = int(OwnRooms.Length) - 1; i >= 0; i--) {
+            if (OwnRooms[i].joinCode == joinCode)
+                OwnRooms.RemoveAt(i);
+        }
+        for (int i = int(ListedRooms.Length) - 1; i >= 0; i--) {
+            if (ListedRooms[i].joinCode == joinCode)
+                ListedRooms.RemoveAt(i);
+        }
+    }
+
+    string StartTimeText(ScheduledRoom room) {
+        return Icons::ClockO + " " + Time::FormatString("%Y-%m-%d %H:%M", room.startTimestamp);
+    }
+
+    /* Section of the Create tab to schedule a room with the current settings. */
+    void Render() {
+        if (!UI::CollapsingHeader(Icons::CalendarO + " Schedule for later"))
+            return;
+
+        UITools::AlignedLabel("Starts in");
+        UI::SetNextItemWidth(100);
+        StartDelayHours = Math::Clamp(UI::InputInt("hours##bingoschedulehours", StartDelayHours), 0, 720);
+        UI::SameLine();
+        UI::SetNextItemWidth(100);
+        StartDelayMinutes =
+            Math::Clamp(UI::InputInt("minutes##bingoscheduleminutes", StartDelayMinutes, 5), 0, 59);
+
+        UITools::AlignedLabel("Players required to start");
+        UI::SetNextItemWidth(100);
+        MinPlayers = Math::Max(UI::InputInt("##bingoscheduleplayers", MinPlayers), 0);
+        UI::SameLine();
+        UITools::HelpTooltip("The match starts on time if this many players are in the room. "
+                             "When set to 0, one player in each team is required.");
+
+        UI::BeginDisabled(!Config::CanPlay || !Network::IsConnected() || Network::IsUISuspended() ||
+                          StartDelayHours * 60 + StartDelayMinutes == 0);
+        UIColor::Cyan();
+        if (UI::Button(Icons::CalendarPlusO + " Schedule Room")) {
+            UIRoomSettings::SaveConfiguredSettings();
+            NetParams::ScheduleStartTimestamp =
+                Time::Stamp + (StartDelayHours * 60 + StartDelayMinutes) * 60;
+            NetParams::ScheduleMinPlayers = MinPlayers;
+            startnew(Network::ScheduleRoom);
+        }
+        UIColor::Reset();
+        UI::EndDisabled();
+        UITools::ErrorMessage("ScheduleRoom");
+
+        for (uint i = 0; i < OwnRooms.Length; i++) {
+            ScheduledRoom room = OwnRooms[i];
+            UI::AlignTextToFramePadding();
+            UI::Text(room.config.name + "\t\\$888" + StartTimeText(room));
+            UI::SameLine();
+            if (UI::Button(Icons::Clipboard + " " + room.joinCode + "##bingoschedulecode" + i))
+                IO::SetClipboard(room.joinCode);
+            UI::SetItemTooltip("Copy the room code to share it with players.");
+            UI::SameLine();
+            CancelButton(room, i);
+        }
+        UITools::ErrorMessage("CancelScheduledRoom");
+        UI::Separator();
+    }
+
+    void CancelButton(ScheduledRoom room, uint index) {
+        UIColor::DarkRed();
+        if (UI::Button(Icons::Times + " Cancel##bingoschedulecancel" + index)) {
+            NetParams::JoinCode = room.joinCode;
+            startnew(Network::CancelScheduledRoom);
+        }
+        UIColor::Reset();
+    }
+
+    /* Upcoming public rooms in the Play tab, with buttons to register to their teams. */
+    void ScheduledRoomList() {
+        if (ListedRooms.Length == 0)
+            return;
+
+        UITools::SectionHeader("Upcoming Rooms");
+        UI::BeginDisabled(!Network::IsConnected() || Network::IsUISuspended());
+        for (uint i = 0; i < ListedRooms.Length; i++) {
+            ScheduledRoom room = ListedRooms[i];
+            UI::Text(room.config.name + "\t\\$888" + StartTimeText(room));
+            UI::Text(string::Join(UIGameRoom::MatchConfigInfo(room.matchConfig), "\t"));
+
+            int registered = room.LocalPlayerTeam();
+            for (uint j = 0; j < room.teams.Length; j++) {
+                Team team = room.teams[j];
+                if (j > 0)
+                    UI::SameLine();
+                UI::PushStyleColor(UI::Col::Button, UIColor::GetAlphaColor(team.color, .6));
+                string label = (int(j) == registered ? Icons::Check + " " : "") + team.name +
+                               " (" + room.RegisteredCount(j) + ")";
+                if (UI::Button(label + "##bingoscheduleteam" + i + "_" + j)) {
+                    NetParams::JoinCode = room.joinCode;
+                    NetParams::ScheduleTeamIndex = int(j) == registered ? -1 : int(j);
+                    startnew(Network::RegisterScheduledRoom);
+                }
+                UI::PopStyleColor();
+                UI::SetItemTooltip(int(j) == registered ? "Cancel your registration."
+                                                        : "Register to this team, you are put in it when you join.");
+            }
+
+            if (@Profile !is null && room.hostUid == Profile.uid) {
+                UI::SameLine();
+                CancelButton(room, i);
+            }
+            UI::Separator();
+        }
+        UI::EndDisabled();
+        UITools::ErrorMessage("RegisterScheduledRoom");
+    }
+}
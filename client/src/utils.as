@@ -8,9 +8,31 @@ string stringof(MapMode mode) {
     if (mode == MapMode::Campaign) {
         return "Campaign";
     }
+    if (mode == MapMode::Mixed) {
+        return "Mixed Sources";
+    }
+    if (mode == MapMode::Local) {
+        return "Local Maps";
+    }
+    if (mode == MapMode::Custom) {
+        return "Hand-picked Maps";
+    }
     return "Custom Mappack";
 }
 
+string stringof(PracticeGoal goal) {
+    if (goal == PracticeGoal::Bingo) {
+        return "Solo Bingo";
+    }
+    if (goal == PracticeGoal::Blackout) {
+        return "Blackout";
+    }
+    if (goal == PracticeGoal::TimeAttack) {
+        return "Time Attack";
+    }
+    return "Off";
+}
+
 bool hasPlayerLimit(RoomConfiguration config) { return config.size != 0; }
 
 bool canPlayersChooseTheirOwnTeam(RoomConfiguration @roomConfig) {
@@ -12,6 +12,7 @@ class TypeDef:
 types = {
     "int": TypeDef("int", "i32", "int"),
     "uint": TypeDef("uint", "u32", "int"),
+    "uint64": TypeDef("uint64", "u64", "int"),
     "string": TypeDef("string", "String", "str"),
     "bool": TypeDef("bool", "bool", "bool"),
     "rgbColor": TypeDef("vec3", "Color", "color"),
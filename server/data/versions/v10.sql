@@ -0,0 +1,16 @@
+-- Database version: 10
+-- Created on: 2026-10-19
+-- 
+-- Room configurations saved by players to create rooms from
+CREATE TABLE room_presets (
+    id INTEGER NOT NULL,
+    owner_uid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    share_code CHAR(6) NOT NULL UNIQUE,
+    config TEXT NOT NULL,
+    match_config TEXT NOT NULL,
+    teams TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    FOREIGN KEY(owner_uid) REFERENCES players(uid)
+    PRIMARY KEY(id)
+);
@@ -0,0 +1,25 @@
+-- Database version: 11
+-- Created on: 2026-10-19
+-- 
+-- Rooms scheduled by hosts to open at a later time, and the players registered to them
+CREATE TABLE scheduled_rooms (
+    id INTEGER NOT NULL,
+    host_uid INTEGER NOT NULL,
+    join_code CHAR(6) NOT NULL UNIQUE,
+    config TEXT NOT NULL,
+    match_config TEXT NOT NULL,
+    teams TEXT NOT NULL,
+    start_at TIMESTAMP NOT NULL,
+    min_players INTEGER NOT NULL,
+    FOREIGN KEY(host_uid) REFERENCES players(uid)
+    PRIMARY KEY(id)
+);
+
+CREATE TABLE scheduled_room_players (
+    room_id INTEGER NOT NULL,
+    player_uid INTEGER NOT NULL,
+    team_index INTEGER NOT NULL,
+    FOREIGN KEY(room_id) REFERENCES scheduled_rooms(id)
+    FOREIGN KEY(player_uid) REFERENCES players(uid)
+    PRIMARY KEY(room_id, player_uid)
+);
@@ -0,0 +1,17 @@
+-- Database version: 12
+-- Created on: 2026-10-19
+-- 
+-- State of the live rooms and matches, restored when the server restarts
+CREATE TABLE room_snapshots (
+    join_code CHAR(6) NOT NULL,
+    data TEXT NOT NULL,
+    saved_at TIMESTAMP NOT NULL,
+    PRIMARY KEY(join_code)
+);
+
+CREATE TABLE match_snapshots (
+    uid TEXT NOT NULL,
+    data TEXT NOT NULL,
+    saved_at TIMESTAMP NOT NULL,
+    PRIMARY KEY(uid)
+);
@@ -0,0 +1,6 @@
+-- Database version: 5
+-- Created on: 2026-10-19
+-- 
+-- Store the random seed of matches
+ALTER TABLE matches
+ADD COLUMN seed INTEGER;
@@ -0,0 +1,22 @@
+-- Database version: 6
+-- Created on: 2026-10-19
+-- 
+-- Daily challenge boards and results
+CREATE TABLE daily_challenges (
+    timedate TEXT NOT NULL,
+    maps TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(timedate)
+);
+
+CREATE TABLE daily_results (
+    player_uid INTEGER NOT NULL,
+    match_uid CHAR(12) NOT NULL,
+    timedate TEXT NOT NULL,
+    tiles_claimed INTEGER NOT NULL,
+    first_bingo_millis INTEGER,
+    FOREIGN KEY(player_uid) REFERENCES players(uid)
+    FOREIGN KEY(match_uid) REFERENCES matches(uid)
+    FOREIGN KEY(timedate) REFERENCES daily_challenges(timedate)
+    PRIMARY KEY(player_uid, match_uid)
+);
@@ -0,0 +1,25 @@
+-- Database version: 7
+-- Created on: 2026-10-19
+-- 
+-- Solo practice results and personal bests
+CREATE TABLE practice_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    player_uid INTEGER NOT NULL,
+    match_uid CHAR(12) NOT NULL,
+    config_key TEXT NOT NULL,
+    goal INTEGER NOT NULL,
+    completed BOOLEAN NOT NULL,
+    tiles_claimed INTEGER NOT NULL,
+    duration_millis INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(player_uid) REFERENCES players(uid)
+);
+
+CREATE TABLE practice_bests (
+    player_uid INTEGER NOT NULL,
+    config_key TEXT NOT NULL,
+    result_id INTEGER NOT NULL,
+    FOREIGN KEY(player_uid) REFERENCES players(uid)
+    FOREIGN KEY(result_id) REFERENCES practice_results(id)
+    PRIMARY KEY(player_uid, config_key)
+);
@@ -0,0 +1,12 @@
+-- Database version: 8
+-- Created on: 2026-10-19
+-- 
+-- Server-wide blacklist of maps excluded from map selection
+CREATE TABLE map_blacklist (
+    tmxid INTEGER NOT NULL,
+    reason TEXT NOT NULL DEFAULT "",
+    added_by INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(added_by) REFERENCES players(uid)
+    PRIMARY KEY(tmxid)
+);
@@ -0,0 +1,10 @@
+-- Database version: 9
+-- Created on: 2026-10-19
+-- 
+-- Maps played on the board of each match
+CREATE TABLE matches_maps (
+    match_uid CHAR(12) NOT NULL,
+    tmxid INTEGER NOT NULL,
+    FOREIGN KEY(match_uid) REFERENCES matches(uid)
+    PRIMARY KEY(match_uid, tmxid)
+);
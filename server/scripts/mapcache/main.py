@@ -37,6 +37,12 @@ def err(e):
 def include_track(map):
     return map["MapType"] == "TM_Race" and map["Vehicle"] == 1
 
+MAP_COLUMNS = (
+    "tmxid", "uid", "webservices_id", "userid", "username", "track_name", "gbx_name",
+    "author_time", "gold_time", "silver_time", "bronze_time", "uploaded_at", "updated_at",
+    "tags", "style",
+)
+
 def save_result(page, result):
     values = []
     for res in result:
@@ -82,7 +88,11 @@ def save_result(page, result):
             )
         )
 
-    cur.executemany("INSERT OR REPLACE INTO maps VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", values)
+    # world records are fetched by the server, keep them when updating a map
+    cur.executemany(f"""
+    INSERT INTO maps ({", ".join(MAP_COLUMNS)}) VALUES ({", ".join("?" * len(MAP_COLUMNS))})
+    ON CONFLICT(tmxid) DO UPDATE SET {", ".join(f"{c}=excluded.{c}" for c in MAP_COLUMNS[1:])}
+    """, values)
     conn.commit()
 
 def create_db(cur):
@@ -103,6 +113,8 @@ def create_db(cur):
         "updated_at"	TIMESTAMP NOT NULL,
         "tags"	TEXT,
         "style" TEXT,
+        "wr_time" INTEGER,
+        "wr_fetched_at" TIMESTAMP,
         PRIMARY KEY("tmxid")
     );
     """)
@@ -1,18 +1,40 @@
 use parking_lot::Mutex;
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt::Display,
     fs::{self},
     io,
+    mem::discriminant,
     sync::OnceLock,
+    time::{Duration, SystemTime},
 };
 use toml::{map::Map, Value};
 use tracing::{debug, error, info, warn};
 
 static CONFIGURATION_KEYS: OnceLock<Mutex<HashMap<String, ConfigValue>>> = OnceLock::new();
 
+const CONFIGURATION_FILE: &str = "config.toml";
+
+/// Keys only read when the server starts, or whole sections when ending with a dot.
+const STARTUP_KEYS: [&str; 13] = [
+    "environment",
+    "network.tcp_port",
+    "network.timeout",
+    "network.tmx_ingestion",
+    "network.tmx_page_size",
+    "network.tmx_request_interval",
+    "behaviour.snapshot_interval",
+    "behaviour.config_watch_interval",
+    "maps.wr_refresh_interval",
+    "maps.health_interval",
+    "daily.enabled",
+    "webservices.",
+    "keys.",
+];
+
 /// Types of configuration values.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ConfigValue {
     String(String),
     Integer(i64),
@@ -56,6 +78,15 @@ impl ConfigValue {
 
         None
     }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::Integer(_) => "integer",
+            Self::Float(_) => "float",
+            Self::Boolean(_) => "boolean",
+        }
+    }
 }
 
 impl Display for ConfigValue {
@@ -108,37 +139,90 @@ fn populate_configuration_keys(
     }
 }
 
-/// Initialize configuration. Only call this once.
-pub fn initialize() -> bool {
+fn default_configuration() -> (&'static str, HashMap<String, ConfigValue>) {
     let default_data = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/data/config.default.toml"
     ));
 
-    info!("intializing configuration");
     let mut configuration = HashMap::new();
-
     let default_config: Map<String, Value> =
         toml::from_str(default_data).expect("default configuration has invalid syntax");
     populate_configuration_keys(&mut configuration, &default_config, String::new());
+    (default_data, configuration)
+}
 
-    match fs::read_to_string("config.toml") {
+/// Read the default configuration, overridden by the values of `config.toml`.
+/// Values of the wrong type are reported and replaced by their default. A missing file is
+/// created from the default configuration if `create_missing` is set, and is an error otherwise.
+fn read_configuration(
+    create_missing: bool,
+) -> Result<(HashMap<String, ConfigValue>, Vec<String>), String> {
+    let (default_data, defaults) = default_configuration();
+    let mut overrides = HashMap::new();
+
+    match fs::read_to_string(CONFIGURATION_FILE) {
         Ok(text) => {
-            let config_overrides: Map<String, Value> = match toml::from_str(&text) {
-                Ok(map) => map,
-                Err(e) => {
-                    error!("error loading configuration file: {e}");
-                    return false;
-                }
-            };
-            populate_configuration_keys(&mut configuration, &config_overrides, String::new());
+            let config_overrides: Map<String, Value> = toml::from_str(&text)
+                .map_err(|e| format!("error loading configuration file: {e}"))?;
+            populate_configuration_keys(&mut overrides, &config_overrides, String::new());
         }
-        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound && create_missing => {
             info!("configuration file not found, created a new copy of the default configuration.");
-            fs::write("config.toml", default_data).unwrap();
+            fs::write(CONFIGURATION_FILE, default_data).unwrap();
+        }
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err("configuration file not found".to_owned())
+        }
+        Err(e) => return Err(format!("IO error reading configuration file: {e}")),
+    };
+
+    let errors = validate(&defaults, &mut overrides);
+    let mut configuration = defaults;
+    configuration.extend(overrides);
+    Ok((configuration, errors))
+}
+
+/// Check configuration values against the types of the default configuration, which acts as
+/// the schema. Invalid values are removed and returned as errors.
+fn validate(
+    defaults: &HashMap<String, ConfigValue>,
+    values: &mut HashMap<String, ConfigValue>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    values.retain(|key, value| match defaults.get(key) {
+        Some(default) if discriminant(default) != discriminant(value) => {
+            errors.push(format!(
+                "configuration key {} should be a {}, found {} {}",
+                key,
+                default.type_name(),
+                value.type_name(),
+                value
+            ));
+            false
+        }
+        Some(_) => true,
+        None => {
+            warn!("unknown configuration key: {}", key);
+            true
+        }
+    });
+    errors.sort();
+    errors
+}
+
+/// Initialize configuration. Only call this once.
+pub fn initialize() -> bool {
+    info!("intializing configuration");
+    let configuration = match read_configuration(true) {
+        Ok((configuration, errors)) => {
+            for e in errors {
+                error!("{e}, using the default value");
+            }
+            configuration
         }
         Err(e) => {
-            error!("IO error reading configuration file: {e}");
+            error!("{e}");
             return false;
         }
     };
@@ -149,6 +233,116 @@ pub fn initialize() -> bool {
     return true;
 }
 
+/// Use the default configuration without reading `config.toml`, for tests.
+#[cfg(test)]
+pub fn initialize_defaults() {
+    CONFIGURATION_KEYS.get_or_init(|| Mutex::new(default_configuration().1));
+}
+
+/// Keys changed by a configuration reload.
+#[derive(Serialize, Debug, Default)]
+pub struct ReloadSummary {
+    pub changed: Vec<String>,
+    /// Changed keys that are only read when the server starts.
+    pub restart_required: Vec<String>,
+}
+
+/// Read the configuration file again and replace all current values at once.
+/// The previous values are kept if the file is missing or any of its values is invalid.
+pub fn reload() -> Result<ReloadSummary, String> {
+    let (configuration, errors) = read_configuration(false)?;
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    let mut current = CONFIGURATION_KEYS.get().unwrap().lock();
+    let changed = changed_keys(&current, &configuration);
+    for key in &changed {
+        info!(
+            "configuration key {} changed: {} -> {}",
+            key,
+            display_value(key, current.get(key)),
+            display_value(key, configuration.get(key))
+        );
+    }
+    *current = configuration;
+    drop(current);
+
+    let restart_required: Vec<String> = changed
+        .iter()
+        .filter(|key| is_startup_key(key))
+        .cloned()
+        .collect();
+    for key in &restart_required {
+        warn!(
+            "configuration key {} is only read at startup, restart the server to apply it",
+            key
+        );
+    }
+    info!("configuration reloaded, {} keys changed", changed.len());
+    Ok(ReloadSummary {
+        changed,
+        restart_required,
+    })
+}
+
+/// Keys added, removed or modified between two configurations, in alphabetical order.
+fn changed_keys(
+    old: &HashMap<String, ConfigValue>,
+    new: &HashMap<String, ConfigValue>,
+) -> Vec<String> {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn is_startup_key(key: &str) -> bool {
+    STARTUP_KEYS
+        .iter()
+        .any(|startup| key == *startup || (startup.ends_with('.') && key.starts_with(startup)))
+}
+
+/// Display a value for the logs, hiding secrets.
+fn display_value(key: &str, value: Option<&ConfigValue>) -> String {
+    match value {
+        None => "(unset)".to_owned(),
+        Some(_) if key.starts_with("keys.") => "(hidden)".to_owned(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Start the background task reloading the configuration when its file is modified.
+pub fn start_watch_task() {
+    let interval = get_integer("behaviour.config_watch_interval").unwrap_or(0);
+    if interval <= 0 {
+        info!("configuration file watching is disabled");
+        return;
+    }
+
+    let modified = || {
+        fs::metadata(CONFIGURATION_FILE)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    tokio::spawn(async move {
+        let mut last_modified: Option<SystemTime> = modified();
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval as u64)).await;
+            let current = modified();
+            if current == last_modified {
+                continue;
+            }
+            last_modified = current;
+            info!("configuration file was modified, reloading it");
+            if let Err(e) = reload() {
+                error!("configuration not reloaded: {e}");
+            }
+        }
+    });
+}
+
 /// Get the value of a configuration setting.
 pub fn get(key: &str) -> Option<ConfigValue> {
     let configuration = CONFIGURATION_KEYS.get().unwrap().lock();
@@ -194,3 +388,66 @@ pub fn enumerate_keys() {
 pub fn is_development() -> bool {
     get_string("environment").is_some_and(|v| v == "dev")
 }
+
+/// Return whether the account is listed as a server administrator.
+pub fn is_admin(account_id: &str) -> bool {
+    get_string("admins").is_some_and(|v| v.split(',').any(|admin| admin.trim() == account_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, ConfigValue)]) -> HashMap<String, ConfigValue> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_are_rejected() {
+        let defaults = values(&[
+            ("behaviour.powerup_spawn", ConfigValue::Float(0.1)),
+            ("behaviour.max_teams", ConfigValue::Integer(10)),
+        ]);
+        let mut overrides = values(&[
+            ("behaviour.powerup_spawn", ConfigValue::Integer(1)),
+            ("behaviour.max_teams", ConfigValue::Integer(8)),
+            ("behaviour.unknown", ConfigValue::Boolean(true)),
+        ]);
+        let errors = validate(&defaults, &mut overrides);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("configuration key behaviour.powerup_spawn"));
+        assert!(!overrides.contains_key("behaviour.powerup_spawn"));
+        assert!(overrides.contains_key("behaviour.unknown"));
+    }
+
+    #[test]
+    fn changed_keys_are_listed() {
+        let old = values(&[
+            ("network.tcp_port", ConfigValue::Integer(5000)),
+            (
+                "client.required_version",
+                ConfigValue::String("5.0".to_owned()),
+            ),
+            ("daily.enabled", ConfigValue::Boolean(true)),
+        ]);
+        let new = values(&[
+            ("network.tcp_port", ConfigValue::Integer(5001)),
+            (
+                "client.required_version",
+                ConfigValue::String("5.0".to_owned()),
+            ),
+            ("behaviour.never_close", ConfigValue::Boolean(true)),
+        ]);
+        let changed = changed_keys(&old, &new);
+        assert_eq!(
+            changed,
+            vec!["behaviour.never_close", "daily.enabled", "network.tcp_port"]
+        );
+        assert!(is_startup_key("network.tcp_port"));
+        assert!(is_startup_key("webservices.max_retries"));
+        assert!(!is_startup_key("behaviour.never_close"));
+    }
+}
@@ -0,0 +1,219 @@
+use std::{collections::HashMap, time::Duration as StdDuration};
+
+use anyhow::anyhow;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+use crate::{
+    config,
+    datatypes::MatchConfiguration,
+    server::{context::ClientContext, mapload, shutdown},
+    store::{
+        self,
+        daily::{DailyChallenge, DailyLeaderboardEntry, DailyResult},
+    },
+};
+
+use super::{
+    directory::{Owned, MATCHES},
+    livegame::LiveMatch,
+    models::map::GameMap,
+    teams::TeamsManager,
+    util::rng,
+};
+
+const ROTATION_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(3600);
+const LEADERBOARD_SIZE: u32 = 100;
+
+static DAILY_BOARDS: Mutex<Lazy<HashMap<NaiveDate, Vec<GameMap>>>> =
+    Mutex::new(Lazy::new(HashMap::new));
+static DAILY_LEADERBOARDS: Mutex<Lazy<HashMap<NaiveDate, Vec<DailyLeaderboardEntry>>>> =
+    Mutex::new(Lazy::new(HashMap::new));
+
+/// The current UTC day.
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// The representation of a day used in the `daily_timedate` columns of the store.
+pub fn timedate(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn daily_seed(date: NaiveDate) -> u64 {
+    date.num_days_from_ce() as u64
+}
+
+fn daily_grid_size() -> u32 {
+    config::get_integer("daily.grid_size").unwrap_or(5) as u32
+}
+
+fn daily_config(grid_size: u32, seed: u64) -> MatchConfiguration {
+    MatchConfiguration {
+        grid_size,
+        time_limit: Duration::minutes(config::get_integer("daily.time_limit").unwrap_or(30)),
+        overtime: false,
+        late_join: false,
+        rerolls: false,
+        seed: Some(seed),
+        ..Default::default()
+    }
+}
+
+/// Load the board of a day from the store, drawing a new one from the map cache if it doesn't exist yet.
+async fn prepare_board(date: NaiveDate) -> Result<Vec<GameMap>, anyhow::Error> {
+    let timedate = timedate(date);
+    let map_ids = match store::daily::get_daily_challenge(&timedate).await {
+        Ok(challenge) => challenge.map_ids,
+        Err(sqlx::Error::RowNotFound) => {
+            let count = daily_grid_size() * daily_grid_size();
+            let maps = mapload::cache_load_seeded(count, daily_seed(date)).await?;
+            if maps.len() < count as usize {
+                return Err(anyhow!(
+                    "not enough maps in the cache for a daily challenge: {} needed, found {}",
+                    count,
+                    maps.len()
+                ));
+            }
+
+            let map_ids: Vec<i32> = maps
+                .iter()
+                .filter_map(|map| match map {
+                    GameMap::TMX(record) => Some(record.tmxid),
+                    _ => None,
+                })
+                .collect();
+            store::daily::create_daily_challenge(&DailyChallenge {
+                timedate: timedate.clone(),
+                map_ids: map_ids.clone(),
+            })
+            .await?;
+            info!("created daily challenge for {}", timedate);
+            map_ids
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let maps = mapload::cache_load_by_ids(map_ids.clone()).await?;
+    if maps.len() != map_ids.len() {
+        return Err(anyhow!(
+            "{} maps of the daily challenge of {} are no longer in the cache",
+            map_ids.len() - maps.len(),
+            timedate
+        ));
+    }
+    Ok(maps)
+}
+
+/// Refresh the cached leaderboard of a daily challenge.
+pub async fn refresh_leaderboard(date: NaiveDate) {
+    match store::daily::get_daily_leaderboard(&timedate(date), LEADERBOARD_SIZE).await {
+        Ok(entries) => {
+            DAILY_LEADERBOARDS.lock().insert(date, entries);
+        }
+        Err(e) => error!("failed to load daily leaderboard for {}: {}", date, e),
+    }
+}
+
+/// Get the cached leaderboard of a daily challenge.
+pub fn get_leaderboard(date: NaiveDate) -> Option<Vec<DailyLeaderboardEntry>> {
+    DAILY_LEADERBOARDS.lock().get(&date).cloned()
+}
+
+/// Save a player's result, then update the leaderboard of that day.
+pub async fn save_result(date: NaiveDate, result: DailyResult) {
+    if let Err(e) = store::daily::create_daily_result(&result).await {
+        error!("failed to save daily result: {}", e);
+        return;
+    }
+    refresh_leaderboard(date).await;
+}
+
+/// Start the background task making sure the boards of today and tomorrow are ready.
+pub fn start_rotation_task() {
+    if !config::get_boolean("daily.enabled").unwrap_or(false) {
+        info!("daily challenges are disabled");
+        return;
+    }
+
+    tokio::spawn(async {
+        loop {
+            let today = today();
+            for date in [today, today + Duration::days(1)] {
+                if DAILY_BOARDS.lock().contains_key(&date) {
+                    continue;
+                }
+                match prepare_board(date).await {
+                    Ok(maps) => {
+                        DAILY_BOARDS.lock().insert(date, maps);
+                    }
+                    Err(e) => error!("failed to prepare daily challenge for {}: {}", date, e),
+                }
+            }
+            refresh_leaderboard(today).await;
+
+            // keep yesterday around for the leaderboard of matches still running past midnight
+            let oldest = today - Duration::days(1);
+            DAILY_BOARDS.lock().retain(|date, _| *date >= oldest);
+            DAILY_LEADERBOARDS.lock().retain(|date, _| *date >= oldest);
+            sleep(ROTATION_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Start a solo match on today's daily challenge board.
+pub fn start_daily_match(ctx: &ClientContext) -> Result<Owned<LiveMatch>, anyhow::Error> {
+    if shutdown::is_draining() {
+        return Err(anyhow!(
+            "The server is about to restart, the daily challenge cannot be started."
+        ));
+    }
+    let date = today();
+    let maps = DAILY_BOARDS.lock().get(&date).cloned().ok_or(anyhow!(
+        "The daily challenge is not available yet, please try again later."
+    ))?;
+    let grid_size = (maps.len() as f64).sqrt() as u32;
+    if (grid_size * grid_size) as usize != maps.len() {
+        return Err(anyhow!(
+            "The daily challenge board is incomplete, please try again later."
+        ));
+    }
+    let seed = daily_seed(date);
+
+    let game = LiveMatch::new(
+        daily_config(grid_size, seed),
+        seed,
+        rng::from_seed(seed),
+        maps,
+        TeamsManager::new(),
+    );
+    let mut lock = game.lock();
+    lock.set_daily(date);
+    lock.add_player(ctx, None)?;
+    lock.setup_match_start(Utc::now());
+    MATCHES.insert(lock.uid().to_owned(), game.clone());
+    drop(lock);
+
+    Ok(game)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn consecutive_days_different_boards() {
+        let candidates: Vec<i32> = (1..=1000).collect();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+        let board = |date| mapload::seeded_selection(candidates.clone(), 25, daily_seed(date));
+
+        let today = board(date);
+        assert_eq!(today, board(date));
+        let tomorrow = board(date + Duration::days(1));
+        let shared = today.iter().filter(|id| tomorrow.contains(id)).count();
+        assert!(shared < 5, "{} maps shared by consecutive boards", shared);
+    }
+}
@@ -10,7 +10,7 @@ use parking_lot::{Mutex, MutexGuard};
 use crate::transport::Channel;
 
 use super::{
-    events::roomlist::RoomlistEvent, livegame::LiveMatch, room::GameRoom,
+    events::roomlist::RoomlistEvent, livegame::LiveMatch, room::GameRoom, schedule,
     util::roomcode::generate_roomcode,
 };
 
@@ -19,6 +19,8 @@ pub static MATCHES: Directory<String, LiveMatch> = Directory::new();
 
 pub static PUB_ROOMS_CHANNEL: Mutex<Lazy<Channel>> =
     Mutex::new(Lazy::new(|| Channel::new()));
+/// All connected players, for server-wide messages. Closed connections are dropped on broadcast.
+pub static CLIENTS_CHANNEL: Mutex<Lazy<Channel>> = Mutex::new(Lazy::new(Channel::new));
 
 pub struct Directory<K, T> {
     inner: Mutex<Lazy<HashMap<K, Owned<T>>>>,
@@ -79,7 +81,7 @@ pub fn get_new_roomcode() -> String {
     let lock = ROOMS.lock();
     let mut join_code = generate_roomcode();
 
-    while lock.get(&join_code).is_some() {
+    while lock.get(&join_code).is_some() || schedule::is_reserved(&join_code) {
         join_code = generate_roomcode();
     }
 
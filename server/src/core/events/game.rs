@@ -6,12 +6,14 @@ use crate::{
     core::{
         livegame::BingoLine,
         models::{
-            livegame::{MapClaim, MatchEndInfo, MatchPhase, MatchState},
+            livegame::{MapClaim, MatchEndInfo, MatchPhase, MatchState, TeamScore},
             map::GameMap,
+            player::InventoryItem,
             team::{BaseTeam, TeamIdentifier},
         },
     },
     datatypes::{ChatMessage, PlayerProfile, PlayerRef, Poll, Powerup},
+    store::practice::PracticeResult,
 };
 
 #[serde_with::serde_as]
@@ -41,6 +43,19 @@ pub enum GameEvent {
     AnnounceDraw {
         end_state: MatchEndInfo,
     },
+    AnnounceWinByPoints {
+        team: TeamIdentifier,
+        scores: Vec<TeamScore>,
+        end_state: MatchEndInfo,
+    },
+    ScoresUpdate {
+        scores: Vec<TeamScore>,
+    },
+    PracticeEnd {
+        result: PracticeResult,
+        personal_best: PracticeResult,
+        new_best: bool,
+    },
     PhaseChange {
         phase: MatchPhase,
     },
@@ -86,9 +101,12 @@ pub enum GameEvent {
         cell_id: usize,
         is_special: bool,
     },
-    ItemSlotEquip {
-        uid: u32,
-        powerup: Powerup,
+    InventoryUpdate {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        uid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        team: Option<TeamIdentifier>,
+        items: Vec<InventoryItem>,
     },
     PowerupActivated {
         powerup: Powerup,
@@ -1,3 +1,4 @@
 pub mod game;
 pub mod room;
 pub mod roomlist;
+pub mod server;
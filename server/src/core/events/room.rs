@@ -41,5 +41,7 @@ pub enum RoomEvent {
     RoomExtrasUpdate {
         locked: bool,
         load_status: LoadState,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        load_warnings: Vec<String>,
     },
 }
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::{serde_as, TimestampSeconds};
+
+#[serde_as]
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "event")]
+pub enum ServerEvent {
+    /// The server stopped accepting new rooms and matches, and may restart at `deadline`.
+    MaintenanceNotice {
+        message: String,
+        #[serde_as(as = "Option<TimestampSeconds>")]
+        deadline: Option<DateTime<Utc>>,
+    },
+    /// A message sent to all players by a server administrator.
+    Announcement { message: String },
+}
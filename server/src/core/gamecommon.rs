@@ -8,7 +8,7 @@ pub type PlayerId = u32;
 
 use super::{
     directory::Owned,
-    models::team::{BaseTeam, TeamIdentifier},
+    models::team::{BaseTeam, Handicap, TeamIdentifier},
     room::GameRoom,
 };
 
@@ -16,10 +16,11 @@ pub fn setup_room(room_arc: &Owned<GameRoom>, teams: &Vec<BaseTeam>) {
     let mut room = room_arc.lock();
     let team_presets = teams.iter().map(|t| (t.name.clone(), t.color)).collect();
 
-    room.create_team_from_preset(&team_presets)
-        .expect("creating initial 1st team");
-    room.create_team_from_preset(&team_presets)
-        .expect("creating initial 2nd team");
+    // practice rooms only get the single team they were created with
+    for _ in 0..teams.len().min(2) {
+        room.create_team_from_preset(&team_presets)
+            .expect("creating initial team");
+    }
 
     server::mapload::load_maps(
         Arc::downgrade(&room_arc),
@@ -36,6 +37,7 @@ pub struct PlayerData {
     pub team: TeamIdentifier,
     pub operator: bool,
     pub disconnected: bool,
+    pub handicap: Handicap,
     #[serde(skip)]
     pub writer: NetMessager,
 }
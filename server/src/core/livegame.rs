@@ -1,5 +1,5 @@
 use anyhow::anyhow;
-use rand::{distributions::Standard, seq::IteratorRandom, thread_rng, Rng};
+use rand::{distributions::Standard, seq::IteratorRandom, Rng};
 use std::{
     collections::HashMap,
     sync::{Arc, Weak},
@@ -8,43 +8,54 @@ use std::{
 use crate::{
     config,
     core::models::{
-        livegame::{MatchEndInfo, MvpData, TileItemState, TileSelector},
+        livegame::{MatchEndInfo, MvpData, PowerupTarget, TileItemState, TileSelector},
         team::NetworkGameTeam,
     },
     datatypes::{
         CampaignMap, Gamemode, MatchConfiguration, PlayerRef, Poll, PollChoice, Powerup,
-        RoomConfiguration,
+        PracticeGoal, RoomConfiguration,
     },
     integrations::{self, hooks::MatchEndEffect},
     server::{
         context::ClientContext,
+        shutdown,
         tasks::{execute_delayed_task, execute_repeating_task},
     },
     store::{
         self,
+        daily::DailyResult,
         matches::{Match, MatchOutcome, MatchResult},
+        practice::PracticeResult,
     },
-    transport::Channel,
+    transport::{messager::closed_messager, Channel},
 };
-use chrono::{DateTime, Duration, TimeDelta, Utc};
+use chrono::{DateTime, Duration, NaiveDate, TimeDelta, Utc};
 use parking_lot::Mutex;
 use serde::Serialize;
 use serde_repr::Serialize_repr;
 use tracing::{error, warn};
 
 use super::{
+    daily,
     directory::{Owned, Shared, MATCHES},
     events::game::GameEvent,
     gamecommon::PlayerId,
     models::{
-        livegame::{GameCell, MapClaim, MatchPhase, MatchState},
+        livegame::{GameCell, MapClaim, MatchPhase, MatchState, TeamScore},
         map::GameMap,
-        player::IngamePlayer,
-        team::{GameTeam, TeamIdentifier},
+        player::{IngamePlayer, InventoryItem},
+        snapshot::{MatchSnapshot, PlayerSnapshot, TeamSnapshot},
+        team::{GameTeam, Handicap, TeamIdentifier},
     },
+    practice,
     room::GameRoom,
+    scoring,
     teams::TeamsManager,
-    util::{base64, Color},
+    util::{
+        base64,
+        rng::{self, MatchRng},
+        Color,
+    },
 };
 
 pub struct LiveMatch {
@@ -61,6 +72,11 @@ pub struct LiveMatch {
     polls: HashMap<u32, Owned<PollData>>,
     last_claim: Option<MapClaim>,
     idents: u32,
+    seed: u64,
+    rng: MatchRng,
+    daily: Option<NaiveDate>,
+    first_bingo: Option<DateTime<Utc>>,
+    last_scores: Vec<TeamScore>,
 }
 
 struct MatchOptions {
@@ -74,6 +90,12 @@ struct PollData {
     pub held_tiles: Vec<GameCell>,
 }
 
+#[derive(Clone, Copy, Debug)]
+enum InventoryOwner {
+    Player(i32),
+    Team(TeamIdentifier),
+}
+
 pub enum PollResultCallback {
     None,
     Reroll(u32),
@@ -83,6 +105,8 @@ pub enum PollResultCallback {
 impl LiveMatch {
     pub fn new(
         config: MatchConfiguration,
+        seed: u64,
+        rng: MatchRng,
         maps: Vec<GameMap>,
         teams: TeamsManager<GameTeam>,
     ) -> Owned<Self> {
@@ -114,12 +138,175 @@ impl LiveMatch {
             polls: HashMap::new(),
             last_claim: None,
             idents: 0,
+            seed,
+            rng,
+            daily: None,
+            first_bingo: None,
+            last_scores: Vec::new(),
         };
         let arc = Arc::new(Mutex::new(_self));
         arc.lock().ptr = Arc::downgrade(&arc);
         arc
     }
 
+    /// Create a match again from a snapshot, resuming its timers. Its players are disconnected
+    /// until they join again.
+    /// The random generator resumes from where it was, so later rerolls and powerups are the
+    /// same as in an uninterrupted match.
+    pub fn restore(snapshot: MatchSnapshot, room: Shared<GameRoom>) -> Owned<Self> {
+        let teams = snapshot
+            .teams
+            .into_iter()
+            .map(|team| GameTeam {
+                base: team.base,
+                members: team
+                    .members
+                    .into_iter()
+                    .map(|player| IngamePlayer {
+                        profile: player.profile,
+                        operator: player.operator,
+                        disconnected: true,
+                        inventory: player.inventory,
+                        handicap: player.handicap,
+                        writer: closed_messager(),
+                    })
+                    .collect(),
+                channel: Channel::new(),
+                winner: team.winner,
+                inventory: team.inventory,
+            })
+            .collect();
+        let arc = Self::new(
+            snapshot.config,
+            snapshot.seed,
+            rng::resume(snapshot.seed, snapshot.rng_position),
+            Vec::new(),
+            TeamsManager::from_teams(teams, snapshot.teams_id),
+        );
+        let mut game = arc.lock();
+        game.uid = snapshot.uid;
+        game.room = room;
+        game.options.start_countdown = snapshot.start_countdown;
+        game.cells = snapshot.cells;
+        game.started = snapshot.started;
+        game.phase = snapshot.phase;
+        game.last_claim = snapshot.last_claim;
+        game.idents = snapshot.idents;
+        game.daily = snapshot.daily;
+        game.first_bingo = snapshot.first_bingo;
+        game.last_scores = game.get_scores();
+        game.resume();
+        drop(game);
+        arc
+    }
+
+    pub fn snapshot(&self, room_code: Option<String>) -> MatchSnapshot {
+        MatchSnapshot {
+            uid: self.uid.clone(),
+            room_code,
+            config: self.config.clone(),
+            start_countdown: self.options.start_countdown,
+            teams: self
+                .teams
+                .get_teams()
+                .iter()
+                .map(|team| TeamSnapshot {
+                    base: team.base.clone(),
+                    members: team
+                        .members
+                        .iter()
+                        .map(|player| PlayerSnapshot {
+                            profile: player.profile.clone(),
+                            operator: player.operator,
+                            inventory: player.inventory.clone(),
+                            handicap: player.handicap.clone(),
+                        })
+                        .collect(),
+                    winner: team.winner,
+                    inventory: team.inventory.clone(),
+                })
+                .collect(),
+            teams_id: self.teams.teams_id(),
+            cells: self.cells.clone(),
+            started: self.started,
+            phase: self.phase,
+            last_claim: self.last_claim.clone(),
+            idents: self.idents,
+            seed: self.seed,
+            rng_position: rng::position(&self.rng),
+            daily: self.daily,
+            first_bingo: self.first_bingo,
+        }
+    }
+
+    /// Schedule again the timers of a restored match: phase changes, powerup spawns, tile effects
+    /// and item expirations.
+    fn resume(&mut self) {
+        self.setup_timers();
+        if self.config.mode == Gamemode::Frenzy {
+            self.setup_powerups();
+        }
+
+        for i in 0..self.cells.len() {
+            let state = self.cells[i].state;
+            if state != TileItemState::Rally && state != TileItemState::Jail {
+                continue;
+            }
+            let state_ident = self.new_ident();
+            let cell = &mut self.cells[i];
+            cell.state_ident = Some(state_ident);
+            let deadline = cell.state_deadline;
+            let selector = TileSelector::StateIdent(state_ident);
+            if state == TileItemState::Rally {
+                self.execute_at(deadline, move |game| game.rally_resolve(selector));
+            } else {
+                self.execute_at(deadline, move |game| game.jail_resolve(selector));
+            }
+        }
+
+        let mut expiring = Vec::new();
+        for team in self.teams.get_teams() {
+            let owned = team
+                .inventory
+                .iter()
+                .map(|item| (InventoryOwner::Team(team.base.id), item));
+            let members = team.members.iter().flat_map(|player| {
+                player
+                    .inventory
+                    .iter()
+                    .map(|item| (InventoryOwner::Player(player.profile.uid), item))
+            });
+            expiring.extend(
+                owned
+                    .chain(members)
+                    .filter_map(|(owner, item)| item.expires.map(|time| (owner, item.id, time))),
+            );
+        }
+        for (owner, item_ident, expires) in expiring {
+            self.execute_at(expires, move |game| game.item_expire(owner, item_ident));
+        }
+    }
+
+    /// Run a task on this match at the given time, or right away if it has passed.
+    fn execute_at<F>(&self, time: DateTime<Utc>, callback: F)
+    where
+        F: FnOnce(&mut Self) + Send + 'static,
+    {
+        execute_delayed_task(
+            self.ptr.clone(),
+            callback,
+            (time - Utc::now()).to_std().unwrap_or_default(),
+        );
+    }
+
+    pub fn parent_room(&self) -> &Shared<GameRoom> {
+        &self.room
+    }
+
+    pub fn phase(&self) -> MatchPhase {
+        self.phase
+    }
+
     pub fn set_parent_room(&mut self, room: Shared<GameRoom>) {
         self.room = room;
     }
@@ -128,6 +315,14 @@ impl LiveMatch {
         self.channel = channel;
     }
 
+    pub fn daily(&self) -> Option<NaiveDate> {
+        self.daily
+    }
+
+    pub fn set_daily(&mut self, date: NaiveDate) {
+        self.daily = Some(date);
+    }
+
     pub fn set_start_countdown(&mut self, countdown: Duration) {
         if self.started.is_some() {
             panic!("attempted to change match options after starting");
@@ -143,46 +338,114 @@ impl LiveMatch {
             self.setup_powerups();
         }
 
+        let has_head_starts = self.apply_head_starts();
         self.broadcast_start();
+        if has_head_starts {
+            let state = self.get_state();
+            self.channel.broadcast(&GameEvent::MatchSync(state));
+        }
+    }
+
+    /// Give teams with a head start handicap their pre-claimed tiles. Returns whether any tile was claimed.
+    /// Like the tiles filled in at the end of overtime, a head start tile has a claimant but no run:
+    /// it counts for its team until the first run is submitted on it, which then owns the tile.
+    fn apply_head_starts(&mut self) -> bool {
+        let head_starts: Vec<(TeamIdentifier, usize)> = self
+            .teams
+            .get_teams()
+            .iter()
+            .map(|team| {
+                let players_head_start: u32 =
+                    team.members.iter().map(|p| p.handicap.head_start).sum();
+                (
+                    team.base.id,
+                    (team.base.handicap.head_start + players_head_start) as usize,
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+
+        for (team, count) in &head_starts {
+            let free_cells: Vec<usize> = (0..self.cell_count())
+                .filter(|i| self.cells[*i].claimant.is_none())
+                .collect();
+            let claimed = free_cells
+                .into_iter()
+                .choose_multiple(&mut self.rng, *count);
+            for i in claimed {
+                self.cells[i].claimant = Some(*team);
+            }
+        }
+        !head_starts.is_empty()
+    }
+
+    /// Check a run against the handicaps of its player and team, and add their time allowance.
+    pub fn apply_handicap(&self, claim: &mut MapClaim) -> Result<(), anyhow::Error> {
+        let team = self
+            .teams
+            .get(claim.team_id)
+            .ok_or(anyhow!("team id {} not found", claim.team_id))?;
+        let player_handicap = team
+            .members
+            .iter()
+            .find(|p| p.profile.uid == claim.player.uid as i32)
+            .map(|p| &p.handicap);
+
+        let required_medal = player_handicap
+            .and_then(|h| h.target_medal)
+            .or(team.base.handicap.target_medal);
+        if let Some(medal) = required_medal {
+            if (claim.medal as u8) > (medal as u8) {
+                return Err(anyhow!(
+                    "This run does not reach the {:?} medal required by your handicap.",
+                    medal
+                ));
+            }
+        }
+
+        let allowance =
+            team.base.handicap.time_allowance + player_handicap.map_or(0, |h| h.time_allowance);
+        if allowance != 0 {
+            claim.time = (claim.time as i64 + allowance).max(0) as u64;
+            claim.time_allowance = allowance;
+        }
+        Ok(())
     }
 
+    /// Schedule the phase changes of the match that are still ahead of its current phase.
     fn setup_timers(&mut self) {
+        let Some(playstart) = self.playstart_date() else {
+            return;
+        };
         let max_duration =
             Duration::minutes(config::get_integer("behaviour.max_match_duration").unwrap_or(0));
         let mut first_phase = MatchPhase::Running;
-        let countdown_duration = self.options.start_countdown;
         let nobingo_duration = self.config.no_bingo_duration;
         let main_phase_duration = self.config.time_limit;
+        let phase = self.phase;
         if !nobingo_duration.is_zero() {
             first_phase = MatchPhase::NoBingo;
-            execute_delayed_task(
-                self.ptr.clone(),
-                |game| game.nobingo_phase_change(),
-                (countdown_duration + nobingo_duration).to_std().unwrap(),
-            );
+            if phase == MatchPhase::Starting || phase == MatchPhase::NoBingo {
+                self.execute_at(playstart + nobingo_duration, |game| {
+                    game.nobingo_phase_change()
+                });
+            }
         }
-        if !main_phase_duration.is_zero() {
-            execute_delayed_task(
-                self.ptr.clone(),
-                |game| game.endmain_phase_change(),
-                (countdown_duration + nobingo_duration + main_phase_duration)
-                    .to_std()
-                    .unwrap(),
-            );
+        if !main_phase_duration.is_zero()
+            && phase != MatchPhase::Overtime
+            && phase != MatchPhase::Ended
+        {
+            self.execute_at(playstart + nobingo_duration + main_phase_duration, |game| {
+                game.endmain_phase_change()
+            });
         }
         if !max_duration.is_zero() {
-            execute_delayed_task(
-                self.ptr.clone(),
-                |game| game.draw_end_game(),
-                (countdown_duration + max_duration).to_std().unwrap(),
-            );
+            self.execute_at(playstart + max_duration, |game| game.draw_end_game());
         }
 
-        execute_delayed_task(
-            self.ptr.clone(),
-            move |game| game.set_phase(first_phase),
-            countdown_duration.to_std().unwrap(),
-        );
+        if phase == MatchPhase::Starting {
+            self.execute_at(playstart, move |game| game.set_phase(first_phase));
+        }
     }
 
     fn setup_powerups(&mut self) {
@@ -229,6 +492,10 @@ impl LiveMatch {
         &self.config
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     pub fn channel(&mut self) -> &mut Channel {
         &mut self.channel
     }
@@ -268,6 +535,10 @@ impl LiveMatch {
         let already_joined_team = self.get_player_team(ctx.profile.uid);
 
         if let Some(team) = already_joined_team {
+            if let Some(player) = self.get_player_mut(ctx.profile.uid) {
+                player.writer = ctx.writer.clone();
+                player.disconnected = false;
+            }
             self.channel.subscribe(ctx.profile.uid, ctx.writer.clone());
             self.teams
                 .get_mut(team)
@@ -289,7 +560,7 @@ impl LiveMatch {
         Ok(team)
     }
 
-    fn add_player(
+    pub fn add_player(
         &mut self,
         ctx: &ClientContext,
         team: Option<TeamIdentifier>,
@@ -320,8 +591,8 @@ impl LiveMatch {
             profile: ctx.profile.clone(),
             operator: false,
             disconnected: false,
-            holding_powerup: Powerup::Empty,
-            item_ident: None,
+            inventory: Vec::new(),
+            handicap: Handicap::default(),
             writer: ctx.writer.clone(),
         });
         team.channel.subscribe(ctx.profile.uid, ctx.writer.clone());
@@ -400,20 +671,22 @@ impl LiveMatch {
                 .collect(),
             started: self.started.unwrap_or_default(),
             can_reroll: self.can_reroll(),
+            scores: self.get_scores(),
         }
     }
 
     pub fn add_submitted_run(&mut self, id: usize, claim: MapClaim) {
+        let medal_ladder = self.config.medal_ladder;
         let ranking = &mut self.cells[id].claims;
         let running_player = claim.player.clone();
 
-        // Bubble up in the ranking until we find a time that was not beaten
+        // Bubble up in the ranking until we find a claim that was not beaten
         let mut i = ranking.len();
         while i > 0 {
             let current = &ranking[i - 1];
             if current.player == running_player {
                 ranking.remove(i - 1);
-            } else if claim.time >= current.time {
+            } else if !claim.beats(current, medal_ladder) {
                 break;
             }
             i -= 1;
@@ -434,8 +707,16 @@ impl LiveMatch {
             self.last_claim = Some(claim);
 
             if self.cells[id].state == TileItemState::HasPowerup {
-                self.cells[id].state = TileItemState::Empty;
-                self.give_new_powerup(running_player.clone());
+                if self.has_inventory_space(running_player.uid as i32) {
+                    self.cells[id].state = TileItemState::Empty;
+                    self.give_new_powerup(running_player.clone());
+                } else {
+                    // the powerup stays on the tile, resync clients who cleared it locally
+                    self.channel.broadcast(&GameEvent::PowerupSpawn {
+                        cell_id: id,
+                        is_special: false,
+                    });
+                }
             }
 
             if self.cells[id].state == TileItemState::Jail
@@ -450,6 +731,7 @@ impl LiveMatch {
     }
 
     fn announce_bingo_and_game_end(&mut self, lines: Vec<BingoLine>) {
+        self.first_bingo.get_or_insert_with(Utc::now);
         for line in &lines {
             let winning_team = self.get_team_mut(line.team).expect("winning team exists");
             winning_team.winner = true;
@@ -474,15 +756,17 @@ impl LiveMatch {
 
         self.cells
             .iter()
-            .filter(|c| !(c.claimant.is_some() || c.state == TileItemState::Rainbow))
-            .for_each(|c| {
+            .enumerate()
+            .filter(|(_, c)| !(c.claimant.is_some() || c.state == TileItemState::Rainbow))
+            .for_each(|(i, c)| {
                 if let Some(claim) = c.leading_claim() {
                     if self
                         .teams
                         .get(claim.team_id)
                         .is_some_and(|t| t.winner && t.members.len() > 1)
                     {
-                        *claimed_maps_count.entry(claim.player.clone()).or_insert(0) += 1;
+                        *claimed_maps_count.entry(claim.player.clone()).or_insert(0) +=
+                            self.cell_points(i);
                     }
                 }
             });
@@ -522,6 +806,9 @@ impl LiveMatch {
         if self.should_match_be_saved() {
             self.save_match_end(draw, end_state.mvp.map(|mvp| mvp.player));
         }
+        if self.is_practice() {
+            self.save_practice_results();
+        }
 
         if let Some(hook) = integrations::HOOK.get() {
             // Hook event: match ended
@@ -534,6 +821,7 @@ impl LiveMatch {
             let started = self.started.unwrap_or_default();
             let ended = Utc::now();
             let uid = self.uid().to_string();
+            let seed = self.seed;
             let match_config = self.config.clone();
             let room_config = if let Some(room) = self.room.upgrade() {
                 room.lock().config().clone()
@@ -548,6 +836,7 @@ impl LiveMatch {
                     teams,
                     started,
                     ended,
+                    seed,
                 })
                 .await
             });
@@ -557,15 +846,20 @@ impl LiveMatch {
     }
 
     fn should_match_be_saved(&self) -> bool {
-        // Save match results if there are at least 2 players
-        self.player_count() >= 2
+        // Save match results if there are at least 2 players, or for any daily challenge
+        // Practice results are kept separately from ranked matches
+        !self.is_practice() && (self.daily.is_some() || self.player_count() >= 2)
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.config.practice_goal != PracticeGoal::None
     }
 
     fn cell_count(&self) -> usize {
         self.config.grid_size as usize * self.config.grid_size as usize
     }
 
-    fn player_count(&self) -> usize {
+    pub fn player_count(&self) -> usize {
         self.teams.get_teams().iter().map(|t| t.members.len()).sum()
     }
 
@@ -575,6 +869,8 @@ impl LiveMatch {
             started_at: self.started.unwrap_or_default(),
             ended_at: Utc::now(),
             mvp_player_uid: mvp.map(|player| player.uid as i32),
+            seed: self.seed,
+            daily_timedate: self.daily.map(daily::timedate),
         };
         let mut player_results = Vec::new();
         for team in self.teams.get_teams() {
@@ -591,10 +887,98 @@ impl LiveMatch {
                 ));
             }
         }
-        tokio::spawn(store::matches::write_match_end(
-            match_model,
-            MatchResult(player_results),
-        ));
+        // reroll spares after the board were never played
+        let maps = self
+            .cells
+            .iter()
+            .take(self.cell_count())
+            .filter_map(|cell| match &cell.map {
+                GameMap::TMX(record) => Some(record.tmxid),
+                _ => None,
+            })
+            .collect();
+        let daily_results = self.get_daily_results();
+        let write = shutdown::track_write();
+        tokio::spawn(async move {
+            let _write = write;
+            if let Err(e) =
+                store::matches::write_match_end(match_model, MatchResult(player_results), maps)
+                    .await
+            {
+                error!("failed to save match end: {}", e);
+                return;
+            }
+            if let Some((date, results)) = daily_results {
+                for result in results {
+                    daily::save_result(date, result).await;
+                }
+            }
+        });
+    }
+
+    fn save_practice_results(&self) {
+        let config_key = practice::config_key(&self.config);
+        let duration_millis = self
+            .playstart_date()
+            .map(|start| (Utc::now() - start).num_milliseconds())
+            .unwrap_or_default();
+        for team in self.teams.get_teams() {
+            let tiles_claimed = self.count_team_cells(team.base.id) as i32;
+            for player in &team.members {
+                let result = PracticeResult {
+                    player_uid: player.profile.uid,
+                    match_uid: self.uid.clone(),
+                    config_key: config_key.clone(),
+                    goal: self.config.practice_goal,
+                    completed: team.winner,
+                    tiles_claimed,
+                    duration_millis,
+                    created_at: Utc::now(),
+                };
+                let write = shutdown::track_write();
+                let channel = self.channel.clone();
+                tokio::spawn(async move {
+                    let _write = write;
+                    practice::save_result(result, channel).await;
+                });
+            }
+        }
+    }
+
+    fn get_daily_results(&self) -> Option<(NaiveDate, Vec<DailyResult>)> {
+        let date = self.daily?;
+        let first_bingo_millis = self
+            .first_bingo
+            .zip(self.playstart_date())
+            .map(|(bingo, start)| (bingo - start).num_milliseconds());
+        let results = self
+            .teams
+            .get_teams()
+            .iter()
+            .flat_map(|team| {
+                let tiles_claimed = self.count_team_cells(team.base.id) as i32;
+                team.members.iter().map(move |player| DailyResult {
+                    player_uid: player.profile.uid,
+                    match_uid: self.uid.clone(),
+                    timedate: daily::timedate(date),
+                    tiles_claimed,
+                    first_bingo_millis,
+                })
+            })
+            .collect();
+        Some((date, results))
+    }
+
+    fn count_team_cells(&self, team: TeamIdentifier) -> usize {
+        self.cells
+            .iter()
+            .take(self.cell_count())
+            .filter(|cell| {
+                cell.claimant
+                    .or(cell.leading_claim().map(|claim| claim.team_id))
+                    .is_some_and(|claimant| claimant == team)
+            })
+            .count()
     }
 
     fn broadcast_submitted_run(&mut self, cell_id: usize, claim: MapClaim, position: usize) {
@@ -694,6 +1078,9 @@ impl LiveMatch {
             map: self.cells[cell_id].map.clone(),
             can_reroll: self.can_reroll(),
         });
+        if self.config.mode == Gamemode::Points {
+            self.broadcast_scores();
+        }
     }
 
     pub fn get_player_mut(&mut self, uid: i32) -> Option<&mut IngamePlayer> {
@@ -710,25 +1097,49 @@ impl LiveMatch {
         &mut self,
         uid: i32,
         powerup: Powerup,
-        board_index: usize,
-        forwards: bool,
-        choice: i32,
-        player_id: i32,
+        item_id: Option<u32>,
+        target: PowerupTarget,
     ) -> Result<(), String> {
+        let PowerupTarget {
+            board_index,
+            forwards,
+            choice,
+            player_uid: player_id,
+        } = target;
         let Some(player) = self.get_player_mut(uid) else {
             return Err(format!("player with uid '{}' not found", uid));
         };
+        let player_ref = player.as_player_ref();
+        let Some(owner) = self.get_inventory_owner(uid) else {
+            return Err(format!("player with uid '{}' has no inventory", uid));
+        };
 
+        let item = self.get_inventory(owner).and_then(|inventory| {
+            inventory
+                .iter()
+                .find(|item| match item_id {
+                    Some(id) => item.id == id,
+                    None => item.powerup == powerup,
+                })
+                .cloned()
+        });
         if !config::get_boolean("behaviour.skip_checks").unwrap_or(false) {
-            if player.holding_powerup != powerup {
-                return Err(format!(
-                    "not holding powerup {:?}, your held powerup is {:?}",
-                    powerup, player.holding_powerup
-                ));
+            match &item {
+                None => {
+                    return Err(format!(
+                        "not holding powerup {:?} in your inventory",
+                        powerup
+                    ));
+                }
+                Some(item) if item.powerup != powerup => {
+                    return Err(format!(
+                        "item {} is a {:?} powerup, not {:?}",
+                        item.id, item.powerup, powerup
+                    ));
+                }
+                _ => (),
             }
         }
-
-        let player_ref = player.as_player_ref();
         let target = if powerup == Powerup::Jail {
             self.get_player_mut(player_id).map(|p| p.as_player_ref())
         } else {
@@ -770,7 +1181,9 @@ impl LiveMatch {
         }
 
         // Powerup activation
-        self.give_powerup(player_ref.clone(), Powerup::Empty);
+        if let Some(item) = item {
+            self.take_item(owner, item.id);
+        }
         match powerup {
             Powerup::RowShift | Powerup::ColumnShift => {
                 self.powerup_effect_board_shift(powerup == Powerup::RowShift, board_index, forwards)
@@ -805,6 +1218,9 @@ impl LiveMatch {
     }
 
     fn try_do_bingo_checks(&mut self) -> bool {
+        if self.config.mode == Gamemode::Points {
+            self.broadcast_scores();
+        }
         if self.phase != MatchPhase::NoBingo {
             return self.run_bingo_checks();
         }
@@ -812,6 +1228,16 @@ impl LiveMatch {
     }
 
     fn run_bingo_checks(&mut self) -> bool {
+        if self.config.mode == Gamemode::Points {
+            // lines only give bonus points, the highest score wins at the time limit
+            return false;
+        }
+        match self.config.practice_goal {
+            PracticeGoal::Blackout => return self.run_blackout_checks(),
+            PracticeGoal::TimeAttack => return false,
+            _ => (),
+        }
+
         let bingos = self.check_for_bingos();
         let len = bingos.len();
         if len >= 1 && bingos.iter().all(|line| line.team == bingos[0].team) {
@@ -821,6 +1247,31 @@ impl LiveMatch {
         false
     }
 
+    fn run_blackout_checks(&mut self) -> bool {
+        let Some(team) = self.teams.get_teams().first().map(|team| team.base.id) else {
+            return false;
+        };
+        if self.count_team_cells(team) < self.cell_count() {
+            return false;
+        }
+        self.announce_practice_completed(team);
+        true
+    }
+
+    fn announce_practice_completed(&mut self, team: TeamIdentifier) {
+        self.teams
+            .get_mut(team)
+            .expect("practice team exists")
+            .winner = true;
+
+        let end_state = self.get_end_state();
+        self.channel.broadcast(&GameEvent::AnnounceWinByCellCount {
+            team,
+            end_state: end_state.clone(),
+        });
+        self.set_game_ended(false, end_state);
+    }
+
     pub fn check_for_bingos(&self) -> Vec<BingoLine> {
         let grid_size = self.config.grid_size as usize;
         let mut bingos = Vec::new();
@@ -894,6 +1345,10 @@ impl LiveMatch {
     }
 
     pub fn do_cell_winner_checks(&mut self) -> bool {
+        if self.config.mode == Gamemode::Points {
+            return self.do_points_winner_checks();
+        }
+
         if let Some(winning_team) = self.get_winning_team_by_cell_count() {
             self.teams
                 .get_mut(winning_team)
@@ -911,6 +1366,93 @@ impl LiveMatch {
         false
     }
 
+    fn do_points_winner_checks(&mut self) -> bool {
+        let scores = self.get_scores();
+        let Some(best) = scores.iter().max_by_key(|score| score.score) else {
+            return false;
+        };
+        let best_count = scores
+            .iter()
+            .filter(|score| score.score == best.score)
+            .count();
+        if best_count > 1 {
+            return false;
+        }
+
+        let winning_team = best.team;
+        self.teams
+            .get_mut(winning_team)
+            .expect("winning team exists")
+            .winner = true;
+
+        let end_state = self.get_end_state();
+        self.channel.broadcast(&GameEvent::AnnounceWinByPoints {
+            team: winning_team,
+            scores,
+            end_state: end_state.clone(),
+        });
+        self.set_game_ended(false, end_state);
+        true
+    }
+
+    fn cell_owner(cell: &GameCell) -> Option<TeamIdentifier> {
+        if cell.state == TileItemState::Rainbow {
+            return None;
+        }
+        cell.claimant
+            .or(cell.leading_claim().map(|claim| claim.team_id))
+    }
+
+    /// The points a tile is worth, which is always one outside of the Points gamemode.
+    fn cell_points(&self, board_index: usize) -> i32 {
+        if self.config.mode != Gamemode::Points {
+            return 1;
+        }
+        scoring::tile_value(
+            &self.cells[board_index],
+            board_index,
+            self.config.grid_size as usize,
+        )
+    }
+
+    /// Scores of all teams in the Points gamemode: the value of each held tile and a bonus for each line.
+    pub fn get_scores(&self) -> Vec<TeamScore> {
+        if self.config.mode != Gamemode::Points {
+            return Vec::new();
+        }
+
+        let lines = self.check_for_bingos();
+        self.teams
+            .get_teams()
+            .iter()
+            .map(|team| {
+                let tiles: i32 = (0..self.cell_count())
+                    .filter(|i| Self::cell_owner(&self.cells[*i]) == Some(team.base.id))
+                    .map(|i| self.cell_points(i))
+                    .sum();
+                let team_lines = lines
+                    .iter()
+                    .filter(|line| line.team == team.base.id)
+                    .count();
+                let bonus = team_lines as i32 * scoring::line_bonus();
+                TeamScore {
+                    team: team.base.id,
+                    score: tiles + bonus,
+                }
+            })
+            .collect()
+    }
+
+    fn broadcast_scores(&mut self) {
+        let scores = self.get_scores();
+        if scores != self.last_scores {
+            self.channel.broadcast(&GameEvent::ScoresUpdate {
+                scores: scores.clone(),
+            });
+            self.last_scores = scores;
+        }
+    }
+
     fn get_winning_team_by_cell_count(&self) -> Option<TeamIdentifier> {
         let mut winner = None;
         let mut max_score = 0;
@@ -1054,6 +1596,18 @@ impl LiveMatch {
             return;
         }
 
+        if self.is_practice() {
+            // a time attack is complete once the time runs out, other goals have failed
+            let team = self.teams.get_teams().first().map(|team| team.base.id);
+            match team {
+                Some(team) if self.config.practice_goal == PracticeGoal::TimeAttack => {
+                    self.announce_practice_completed(team)
+                }
+                _ => self.draw_end_game(),
+            }
+            return;
+        }
+
         if self.do_cell_winner_checks() {
             return;
         }
@@ -1065,7 +1619,7 @@ impl LiveMatch {
         }
     }
 
-    fn draw_end_game(&mut self) {
+    pub fn draw_end_game(&mut self) {
         let end_state = self.get_end_state();
 
         self.channel.broadcast(&GameEvent::AnnounceDraw {
@@ -1075,10 +1629,9 @@ impl LiveMatch {
     }
 
     fn tick_powerups_spawn(&mut self) {
-        let mut rng = thread_rng();
         let num_cells = self.cell_count();
         let powerup_spawn_threshold = config::get_float("behaviour.powerup_spawn").unwrap_or(0.);
-        let powerup_spawn_sample = rng.sample::<f64, Standard>(Standard);
+        let powerup_spawn_sample = self.rng.sample::<f64, Standard>(Standard);
         let inactivity_threshold =
             Duration::seconds(config::get_integer("behaviour.claim_inactivity_secs").unwrap_or(0));
         let now = Utc::now();
@@ -1091,7 +1644,7 @@ impl LiveMatch {
                         .leading_claim()
                         .is_some_and(|claim| now - claim.timestamp < inactivity_threshold)
             });
-            if let Some(chosen_tile) = candidates.choose(&mut rng) {
+            if let Some(chosen_tile) = candidates.choose(&mut self.rng) {
                 chosen_tile.state = TileItemState::HasPowerup;
                 self.channel.broadcast(&GameEvent::PowerupSpawn {
                     cell_id: chosen_tile.cell_id,
@@ -1108,32 +1661,105 @@ impl LiveMatch {
     }
 
     fn give_powerup(&mut self, player_ref: PlayerRef, powerup: Powerup) {
+        let Some(owner) = self.get_inventory_owner(player_ref.uid as i32) else {
+            return;
+        };
         let item_ident = self.new_ident();
-        if let Some(player) = self.get_player_mut(player_ref.uid as i32) {
-            player.holding_powerup = powerup;
-            player.item_ident = Some(item_ident);
-
-            if powerup != Powerup::Empty && self.config.items_expire != 0 {
-                let pref = player_ref.clone();
-                execute_delayed_task(
-                    self.ptr.clone(),
-                    move |_self| _self.item_expire(pref, item_ident),
-                    Duration::seconds(self.config.items_expire.into())
-                        .to_std()
-                        .unwrap(),
-                );
-            }
-        }
+        let capacity = self.inventory_capacity();
+        let expire_duration = Duration::seconds(self.config.items_expire.into());
+        let expires = (!expire_duration.is_zero()).then(|| Utc::now() + expire_duration);
 
-        self.channel.broadcast(&GameEvent::ItemSlotEquip {
-            uid: player_ref.uid,
+        let Some(inventory) = self.get_inventory_mut(owner) else {
+            return;
+        };
+        if inventory.len() >= capacity {
+            warn!(
+                "inventory of {:?} is full, {:?} was not given",
+                owner, powerup
+            );
+            return;
+        }
+        inventory.push(InventoryItem {
+            id: item_ident,
             powerup,
+            expires,
         });
+
+        if expires.is_some() {
+            execute_delayed_task(
+                self.ptr.clone(),
+                move |_self| _self.item_expire(owner, item_ident),
+                expire_duration.to_std().unwrap(),
+            );
+        }
+        self.broadcast_inventory(owner);
+    }
+
+    fn take_item(&mut self, owner: InventoryOwner, item_ident: u32) -> Option<InventoryItem> {
+        let inventory = self.get_inventory_mut(owner)?;
+        let index = inventory.iter().position(|item| item.id == item_ident)?;
+        let item = inventory.remove(index);
+        self.broadcast_inventory(owner);
+        Some(item)
+    }
+
+    fn broadcast_inventory(&mut self, owner: InventoryOwner) {
+        let items = self.get_inventory(owner).cloned().unwrap_or_default();
+        let event = match owner {
+            InventoryOwner::Player(uid) => GameEvent::InventoryUpdate {
+                uid: Some(uid as u32),
+                team: None,
+                items,
+            },
+            InventoryOwner::Team(team) => GameEvent::InventoryUpdate {
+                uid: None,
+                team: Some(team),
+                items,
+            },
+        };
+        self.channel.broadcast(&event);
+    }
+
+    fn inventory_capacity(&self) -> usize {
+        self.config.inventory_size.max(1) as usize
+    }
+
+    fn has_inventory_space(&self, uid: i32) -> bool {
+        self.get_inventory_owner(uid)
+            .and_then(|owner| self.get_inventory(owner))
+            .is_some_and(|inventory| inventory.len() < self.inventory_capacity())
+    }
+
+    fn get_inventory_owner(&self, uid: i32) -> Option<InventoryOwner> {
+        if self.config.shared_inventory {
+            self.get_player_team(uid).map(InventoryOwner::Team)
+        } else {
+            Some(InventoryOwner::Player(uid))
+        }
+    }
+
+    fn get_inventory(&self, owner: InventoryOwner) -> Option<&Vec<InventoryItem>> {
+        match owner {
+            InventoryOwner::Player(uid) => self
+                .teams
+                .get_teams()
+                .iter()
+                .flat_map(|t| &t.members)
+                .find(|p| p.profile.uid == uid)
+                .map(|p| &p.inventory),
+            InventoryOwner::Team(id) => self.teams.get(id).map(|t| &t.inventory),
+        }
+    }
+
+    fn get_inventory_mut(&mut self, owner: InventoryOwner) -> Option<&mut Vec<InventoryItem>> {
+        match owner {
+            InventoryOwner::Player(uid) => self.get_player_mut(uid).map(|p| &mut p.inventory),
+            InventoryOwner::Team(id) => self.teams.get_mut(id).map(|t| &mut t.inventory),
+        }
     }
 
     fn draft_powerup(&mut self) -> Option<Powerup> {
-        let item_settings = &self.config().items;
-        let mut rng = thread_rng();
+        let item_settings = &self.config.items;
         let drafting_probabilities = vec![
             (Powerup::RowShift, item_settings.row_shift),
             (Powerup::ColumnShift, item_settings.column_shift),
@@ -1147,7 +1773,7 @@ impl LiveMatch {
         for (powerup, occurences) in drafting_probabilities {
             drafting_pool.extend_from_slice(&[powerup].repeat(occurences as usize));
         }
-        drafting_pool.into_iter().choose(&mut rng)
+        drafting_pool.into_iter().choose(&mut self.rng)
     }
 
     fn fix_cell_ids(&mut self) {
@@ -1319,23 +1945,21 @@ impl LiveMatch {
                 if tile_right < self.cell_count() as i32 {
                     self.cells[tile_right as usize].claimant = Some(winning_team);
                 }
-                if tile_down < self.cell_count() as i32{
+                if tile_down < self.cell_count() as i32 {
                     self.cells[tile_down as usize].claimant = Some(winning_team);
                 }
             }
 
-            self.channel
-                .broadcast(&GameEvent::RallyResolved { cell_id: cell_id as usize, team });
+            self.channel.broadcast(&GameEvent::RallyResolved {
+                cell_id: cell_id as usize,
+                team,
+            });
             self.try_do_bingo_checks();
         }
     }
 
-    fn item_expire(&mut self, player_ref: PlayerRef, item_ident: u32) {
-        if let Some(player) = self.get_player_mut(player_ref.uid as i32) {
-            if player.item_ident.is_some_and(|ident| ident == item_ident) {
-                self.give_powerup(player_ref, Powerup::Empty);
-            }
-        }
+    fn item_expire(&mut self, owner: InventoryOwner, item_ident: u32) {
+        self.take_item(owner, item_ident);
     }
 
     fn new_ident(&mut self) -> u32 {
@@ -1407,3 +2031,123 @@ fn iter_check_unique_team<'a>(iter: impl Iterator<Item = &'a GameCell>) -> Optio
         })
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::util::Color, datatypes::Medal, orm::mapcache::record::MapRecord};
+
+    fn map(tmxid: i32) -> GameMap {
+        GameMap::TMX(MapRecord::test(tmxid))
+    }
+
+    fn test_match(head_start: u32) -> Owned<LiveMatch> {
+        config::initialize_defaults();
+        let mut teams = TeamsManager::<GameTeam>::new();
+        teams.create_team("Red".to_string(), Color::new(255, 0, 0));
+        teams.create_team("Blue".to_string(), Color::new(0, 0, 255));
+        teams.get_teams_mut()[0].base.handicap.head_start = head_start;
+        let config = MatchConfiguration {
+            grid_size: 5,
+            ..Default::default()
+        };
+        LiveMatch::new(
+            config,
+            1,
+            rng::from_seed(1),
+            (0..25).map(map).collect(),
+            teams,
+        )
+    }
+
+    fn run(team_id: TeamIdentifier, time: u64) -> MapClaim {
+        MapClaim {
+            player: PlayerRef {
+                uid: 2,
+                name: "Runner".to_string(),
+            },
+            team_id,
+            time,
+            medal: Medal::Author,
+            splits: Vec::new(),
+            timestamp: Utc::now(),
+            time_allowance: 0,
+        }
+    }
+
+    #[test]
+    fn medal_ladder_faster_run_steals_same_medal() {
+        let game = test_match(0);
+        let mut game = game.lock();
+        game.config.medal_ladder = true;
+        let red = game.teams.get_teams()[0].base.id;
+        let blue = game.teams.get_teams()[1].base.id;
+        let claim = |team_id, uid, time, medal| MapClaim {
+            player: PlayerRef {
+                uid,
+                name: format!("Runner {}", uid),
+            },
+            medal,
+            ..run(team_id, time)
+        };
+
+        game.add_submitted_run(0, claim(red, 1, 42500, Medal::Gold));
+        game.add_submitted_run(0, claim(blue, 2, 41000, Medal::Gold));
+        assert_eq!(game.cells[0].leading_claim().unwrap().team_id, blue);
+
+        // a slower run with the same medal ranks after
+        game.add_submitted_run(0, claim(red, 3, 42000, Medal::Gold));
+        assert_eq!(game.cells[0].leading_claim().unwrap().team_id, blue);
+
+        game.add_submitted_run(0, claim(red, 1, 39900, Medal::Author));
+        assert_eq!(game.cells[0].leading_claim().unwrap().team_id, red);
+    }
+
+    #[test]
+    fn head_start_tiles_taken_by_first_run() {
+        let game = test_match(3);
+        let mut game = game.lock();
+        let red = game.teams.get_teams()[0].base.id;
+        let blue = game.teams.get_teams()[1].base.id;
+        assert!(game.apply_head_starts());
+
+        let held: Vec<usize> = (0..game.cell_count())
+            .filter(|i| LiveMatch::cell_owner(&game.cells[*i]) == Some(red))
+            .collect();
+        assert_eq!(held.len(), 3);
+        assert!(held.iter().all(|i| game.cells[*i].claims.is_empty()));
+
+        game.add_submitted_run(held[0], run(blue, 50000));
+        assert_eq!(LiveMatch::cell_owner(&game.cells[held[0]]), Some(blue));
+        assert_eq!(LiveMatch::cell_owner(&game.cells[held[1]]), Some(red));
+    }
+
+    #[tokio::test]
+    async fn snapshot_restores_match() {
+        let game = test_match(0);
+        let mut lock = game.lock();
+        let blue = lock.teams.get_teams()[1].base.id;
+        lock.add_submitted_run(7, run(blue, 45000));
+        lock.rng.gen::<u64>();
+        let snapshot = lock.snapshot(Some("ROOM01".to_string()));
+        drop(lock);
+
+        // snapshots are saved as JSON in the store
+        let snapshot: MatchSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        let restored = LiveMatch::restore(snapshot, Weak::new());
+        let mut restored = restored.lock();
+        let lock = game.lock();
+        assert_eq!(restored.uid, lock.uid);
+        assert_eq!(restored.seed, lock.seed);
+        assert_eq!(restored.teams.get_teams().len(), 2);
+        assert_eq!(restored.cell_count(), 25);
+        assert_eq!(LiveMatch::cell_owner(&restored.cells[7]), Some(blue));
+        assert_eq!(restored.cells[7].claims.len(), 1);
+        assert_eq!(restored.cells[7].map.name(), "Map 7");
+
+        // draws continue where the match left off
+        let mut original = lock.rng.clone();
+        assert_eq!(restored.rng.gen::<u64>(), original.gen::<u64>());
+    }
+}
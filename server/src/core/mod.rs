@@ -1,8 +1,15 @@
+pub mod daily;
 pub mod directory;
 pub mod events;
 pub mod gamecommon;
 pub mod livegame;
 pub mod models;
+pub mod persistence;
+pub mod practice;
+pub mod presets;
 pub mod room;
+pub mod schedule;
+pub mod scoring;
 pub mod teams;
 pub mod util;
+pub mod validation;
@@ -25,10 +25,18 @@ pub struct MatchState {
     pub can_reroll: bool,
     #[serde_as(as = "TimestampSeconds")]
     pub started: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<TeamScore>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TeamScore {
+    pub team: TeamIdentifier,
+    pub score: i32,
 }
 
 #[serde_with::serde_as]
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GameCell {
     pub cell_id: usize,
     pub map: GameMap,
@@ -60,6 +68,38 @@ pub struct MapClaim {
     pub splits: Vec<u64>,
     #[serde_as(as = "TimestampSeconds")]
     pub timestamp: DateTime<Utc>,
+    /// Handicap time already included in `time`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub time_allowance: i64,
+}
+
+fn is_zero(value: &i64) -> bool {
+    *value == 0
+}
+
+impl MapClaim {
+    /// Whether this claim ranks before another one on the same map.
+    /// With the medal ladder, a better medal always wins, and the time decides between runs
+    /// of the same medal.
+    pub fn beats(&self, other: &MapClaim, medal_ladder: bool) -> bool {
+        if medal_ladder && self.medal != other.medal {
+            return (self.medal as u8) < (other.medal as u8);
+        }
+        self.time < other.time
+    }
+}
+
+/// Where a powerup is used, as chosen by the player activating it. Each powerup only reads the
+/// fields it needs.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct PowerupTarget {
+    pub board_index: usize,
+    /// Direction of a row or column shift.
+    pub forwards: bool,
+    /// Map chosen from a golden dice roll.
+    pub choice: i32,
+    /// Player sent to jail.
+    pub player_uid: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
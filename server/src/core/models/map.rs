@@ -1,8 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{datatypes::CampaignMap, orm::mapcache::record::MapRecord};
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type")]
 pub enum GameMap {
     TMX(MapRecord),
@@ -16,4 +16,12 @@ impl GameMap {
             GameMap::Campaign(map) => format!("#{}", map.map.to_string()),
         }
     }
+
+    /// A stable key identifying this map, used to order maps independently of how they were loaded.
+    pub fn sort_key(&self) -> String {
+        match self {
+            GameMap::TMX(map) => map.uid.clone(),
+            GameMap::Campaign(map) => format!("campaign:{}:{}", map.campaign_id, map.map),
+        }
+    }
 }
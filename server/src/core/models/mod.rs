@@ -2,4 +2,5 @@ pub mod livegame;
 pub mod map;
 pub mod player;
 pub mod room;
+pub mod snapshot;
 pub mod team;
@@ -1,6 +1,9 @@
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::TimestampSeconds;
 use std::hash::Hash;
 
+use super::team::Handicap;
 use crate::{
     core::gamecommon::PlayerData,
     datatypes::{PlayerProfile, PlayerRef, Powerup},
@@ -13,10 +16,10 @@ pub struct IngamePlayer {
     pub profile: PlayerProfile,
     pub operator: bool,
     pub disconnected: bool,
-    pub holding_powerup: Powerup,
+    pub inventory: Vec<InventoryItem>,
+    #[serde(skip_serializing_if = "Handicap::is_none")]
+    pub handicap: Handicap,
 
-    #[serde(skip)]
-    pub item_ident: Option<u32>,
     #[serde(skip)]
     pub writer: NetMessager,
 }
@@ -27,8 +30,8 @@ impl From<PlayerData> for IngamePlayer {
             profile: value.profile,
             operator: value.operator,
             disconnected: value.disconnected,
-            holding_powerup: Powerup::Empty,
-            item_ident: None,
+            inventory: Vec::new(),
+            handicap: value.handicap,
             writer: value.writer,
         }
     }
@@ -48,3 +51,12 @@ impl Hash for PlayerRef {
         self.uid.hash(state);
     }
 }
+
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InventoryItem {
+    pub id: u32,
+    pub powerup: Powerup,
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub expires: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds, TimestampSeconds};
+
+use crate::datatypes::{MatchConfiguration, PlayerProfile, RoomConfiguration};
+
+use super::{
+    livegame::{GameCell, MapClaim, MatchPhase},
+    map::GameMap,
+    player::InventoryItem,
+    team::{BaseTeam, Handicap, TeamIdentifier},
+};
+
+/// State of a room saved to the store, to be restored after a restart.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoomSnapshot {
+    pub join_code: String,
+    pub config: RoomConfiguration,
+    pub match_config: MatchConfiguration,
+    pub teams: Vec<BaseTeam>,
+    pub teams_id: usize,
+    /// Teams of the members and registered players, given back to them when they join again.
+    pub registrations: HashMap<i32, TeamIdentifier>,
+    #[serde_as(as = "TimestampSeconds")]
+    pub created: DateTime<Utc>,
+    pub host_uid: Option<i32>,
+    pub loaded_maps: Vec<GameMap>,
+    pub load_warnings: Vec<String>,
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub scheduled_start: Option<DateTime<Utc>>,
+}
+
+/// State of a match saved to the store, to be restored after a restart.
+/// Polls are not kept, they are cancelled by the restart.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MatchSnapshot {
+    pub uid: String,
+    /// Join code of the room the match is played in, if any.
+    pub room_code: Option<String>,
+    pub config: MatchConfiguration,
+    #[serde_as(as = "DurationMilliSeconds<i64>")]
+    pub start_countdown: Duration,
+    pub teams: Vec<TeamSnapshot>,
+    pub teams_id: usize,
+    pub cells: Vec<GameCell>,
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub started: Option<DateTime<Utc>>,
+    pub phase: MatchPhase,
+    pub last_claim: Option<MapClaim>,
+    pub idents: u32,
+    pub seed: u64,
+    /// Position of the match RNG in the sequence of the seed.
+    #[serde(default)]
+    pub rng_position: u128,
+    pub daily: Option<NaiveDate>,
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub first_bingo: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TeamSnapshot {
+    pub base: BaseTeam,
+    pub members: Vec<PlayerSnapshot>,
+    pub winner: bool,
+    pub inventory: Vec<InventoryItem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlayerSnapshot {
+    pub profile: PlayerProfile,
+    pub operator: bool,
+    pub inventory: Vec<InventoryItem>,
+    pub handicap: Handicap,
+}
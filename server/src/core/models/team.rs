@@ -4,9 +4,10 @@ use palette::serde::as_array;
 use serde::{Deserialize, Serialize};
 
 use crate::core::{teams::Team, util::Color};
+use crate::datatypes::Medal;
 use crate::transport::Channel;
 
-use super::player::IngamePlayer;
+use super::player::{IngamePlayer, InventoryItem};
 use super::room::RoomTeam;
 
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Debug, Hash, Deserialize)]
@@ -18,12 +19,32 @@ impl Display for TeamIdentifier {
     }
 }
 
+/// Adjustments given to a team or a player to balance matches between different skill levels.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Handicap {
+    /// Medal required to claim a map, replacing the target medal of the match.
+    pub target_medal: Option<Medal>,
+    /// Milliseconds added to every submitted time, negative values reduce them.
+    pub time_allowance: i64,
+    /// Number of tiles claimed at the start of the match.
+    pub head_start: u32,
+}
+
+impl Handicap {
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, Eq)]
 pub struct BaseTeam {
     pub id: TeamIdentifier,
     pub name: String,
     #[serde(with = "as_array")]
     pub color: Color,
+    #[serde(default)]
+    pub handicap: Handicap,
 }
 
 impl BaseTeam {
@@ -32,6 +53,7 @@ impl BaseTeam {
             id: TeamIdentifier(id),
             name,
             color,
+            handicap: Handicap::default(),
         }
     }
 }
@@ -54,6 +76,7 @@ pub struct GameTeam {
     pub members: Vec<IngamePlayer>,
     pub channel: Channel,
     pub winner: bool,
+    pub inventory: Vec<InventoryItem>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -61,6 +84,7 @@ pub struct NetworkGameTeam {
     pub base: BaseTeam,
     pub members: Vec<IngamePlayer>,
     pub winner: bool,
+    pub inventory: Vec<InventoryItem>,
 }
 
 impl From<BaseTeam> for GameTeam {
@@ -70,6 +94,7 @@ impl From<BaseTeam> for GameTeam {
             members: Vec::new(),
             channel: Channel::new(),
             winner: false,
+            inventory: Vec::new(),
         }
     }
 }
@@ -86,6 +111,7 @@ impl From<RoomTeam> for GameTeam {
             members: value.members.into_iter().map(IngamePlayer::from).collect(),
             channel,
             winner: false,
+            inventory: Vec::new(),
         }
     }
 }
@@ -96,6 +122,7 @@ impl From<&GameTeam> for NetworkGameTeam {
             base: value.base.clone(),
             members: value.members.clone(),
             winner: value.winner,
+            inventory: value.inventory.clone(),
         }
     }
 }
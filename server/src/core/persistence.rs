@@ -0,0 +1,125 @@
+// Live rooms and matches saved to the store, to be restored after a restart
+
+use std::{
+    sync::{Arc, Weak},
+    time::Duration as StdDuration,
+};
+
+use chrono::{Duration, Utc};
+use tokio::time::sleep;
+use tracing::{debug, error, info};
+
+use crate::{config, server::tasks::execute_delayed_task, store};
+
+use super::{
+    directory::{Owned, MATCHES, ROOMS},
+    livegame::LiveMatch,
+    models::{
+        livegame::MatchPhase,
+        snapshot::{MatchSnapshot, RoomSnapshot},
+    },
+    room::GameRoom,
+};
+
+/// Time given to the players of a restored room to come back before it is closed.
+const RESTORED_ROOM_GRACE: StdDuration = StdDuration::from_secs(5 * 60);
+
+fn collect_snapshots() -> (Vec<RoomSnapshot>, Vec<MatchSnapshot>) {
+    let rooms: Vec<Owned<GameRoom>> = ROOMS.lock().values().cloned().collect();
+    let matches: Vec<Owned<LiveMatch>> = MATCHES.lock().values().cloned().collect();
+
+    let room_snapshots: Vec<RoomSnapshot> =
+        rooms.iter().map(|room| room.lock().snapshot()).collect();
+    let match_snapshots = matches
+        .iter()
+        .filter_map(|game| {
+            let game = game.lock();
+            if game.phase() == MatchPhase::Ended {
+                return None;
+            }
+            let parent = Weak::as_ptr(game.parent_room());
+            let room_code = rooms
+                .iter()
+                .zip(&room_snapshots)
+                .find(|(room, _)| Arc::as_ptr(room) == parent)
+                .map(|(_, snapshot)| snapshot.join_code.clone());
+            Some(game.snapshot(room_code))
+        })
+        .collect();
+    (room_snapshots, match_snapshots)
+}
+
+/// Save the state of all live rooms and matches, replacing the previous snapshots.
+pub async fn save_snapshots() {
+    let (rooms, matches) = collect_snapshots();
+    match store::snapshot::replace_snapshots(&rooms, &matches, Utc::now()).await {
+        Ok(()) => debug!("saved {} rooms and {} matches", rooms.len(), matches.len()),
+        Err(e) => error!("failed to save rooms and matches: {}", e),
+    }
+}
+
+/// Restore the rooms and matches saved recently enough before the server stopped.
+pub async fn restore_snapshots() {
+    let max_age =
+        Duration::minutes(config::get_integer("behaviour.snapshot_max_age").unwrap_or(10));
+    let since = Utc::now() - max_age;
+    let (rooms, matches) = match tokio::try_join!(
+        store::snapshot::get_room_snapshots(since),
+        store::snapshot::get_match_snapshots(since)
+    ) {
+        Ok(snapshots) => snapshots,
+        Err(e) => {
+            error!("failed to load saved rooms and matches: {}", e);
+            return;
+        }
+    };
+    let (room_count, match_count) = (rooms.len(), matches.len());
+
+    for snapshot in rooms {
+        let join_code = snapshot.join_code.clone();
+        let room = GameRoom::restore(snapshot);
+        ROOMS.insert(join_code, room.clone());
+
+        // close the room if no one came back to it
+        execute_delayed_task(
+            Arc::downgrade(&room),
+            |room| {
+                if room.players().is_empty() {
+                    room.check_close();
+                }
+            },
+            RESTORED_ROOM_GRACE,
+        );
+    }
+
+    for snapshot in matches {
+        let room = snapshot
+            .room_code
+            .clone()
+            .and_then(|join_code| ROOMS.find(join_code));
+        let parent = room.as_ref().map(Arc::downgrade).unwrap_or_default();
+        let game = LiveMatch::restore(snapshot, parent);
+        if let Some(room) = room {
+            room.lock().attach_match(&game);
+        }
+        let uid = game.lock().uid().to_owned();
+        MATCHES.insert(uid, game);
+    }
+    info!("restored {} rooms and {} matches", room_count, match_count);
+}
+
+/// Start the background task saving live rooms and matches at a regular interval.
+pub fn start_snapshot_task() {
+    let interval = config::get_integer("behaviour.snapshot_interval").unwrap_or(30);
+    if interval <= 0 {
+        info!("periodic saving of rooms and matches is disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            sleep(StdDuration::from_secs(interval as u64)).await;
+            save_snapshots().await;
+        }
+    });
+}
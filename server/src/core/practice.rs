@@ -0,0 +1,108 @@
+use tracing::error;
+
+use crate::{
+    datatypes::{MapMode, MatchConfiguration},
+    store::{self, practice::PracticeResult},
+    transport::Channel,
+};
+
+use super::events::game::GameEvent;
+
+/// Identify the settings a practice result can be compared against.
+/// Personal bests are only kept between matches with the same key.
+pub fn config_key(config: &MatchConfiguration) -> String {
+    let source = match config.selection {
+        MapMode::RandomTMX | MapMode::Local => String::new(),
+        MapMode::Tags => config.map_tag.unwrap_or_default().to_string(),
+        MapMode::Mappack => config.mappack_id.unwrap_or_default().to_string(),
+        MapMode::Campaign => config
+            .campaign_selection
+            .as_ref()
+            .map(|campaigns| {
+                campaigns
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<String>>()
+                    .join(",")
+            })
+            .unwrap_or_default(),
+        MapMode::Mixed => config
+            .map_sources
+            .iter()
+            .map(|source| {
+                format!(
+                    "{:?}{}x{}",
+                    source.selection,
+                    source
+                        .mappack_id
+                        .map(|id| id as i64)
+                        .or(source.map_tag.map(i64::from))
+                        .unwrap_or_default(),
+                    source.weight
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(","),
+        MapMode::Custom => config
+            .custom_maps
+            .iter()
+            .map(|map| {
+                map.uid
+                    .clone()
+                    .filter(|uid| !uid.is_empty())
+                    .unwrap_or_else(|| map.tmxid.unwrap_or_default().to_string())
+            })
+            .collect::<Vec<String>>()
+            .join(","),
+    };
+    format!(
+        "{:?}/{:?}/{}/{:?}:{}/{:?}/{}",
+        config.practice_goal,
+        config.mode,
+        config.grid_size,
+        config.selection,
+        source,
+        config.target_medal,
+        config.time_limit.num_milliseconds(),
+    )
+}
+
+/// Save a practice result, update the player's personal best and announce both to the match.
+pub async fn save_result(result: PracticeResult, mut channel: Channel) {
+    let id = match store::practice::create_practice_result(&result).await {
+        Ok(id) => id,
+        Err(e) => {
+            error!("failed to save practice result: {}", e);
+            return;
+        }
+    };
+
+    let previous_best =
+        match store::practice::get_personal_best(result.player_uid, &result.config_key).await {
+            Ok(best) => Some(best),
+            Err(sqlx::Error::RowNotFound) => None,
+            Err(e) => {
+                error!("failed to load personal best: {}", e);
+                return;
+            }
+        };
+    let new_best = previous_best
+        .as_ref()
+        .is_none_or(|previous| result.beats(previous));
+    if new_best {
+        if let Err(e) =
+            store::practice::set_personal_best(result.player_uid, &result.config_key, id).await
+        {
+            error!("failed to save personal best: {}", e);
+        }
+    }
+
+    let personal_best = previous_best
+        .filter(|_| !new_best)
+        .unwrap_or_else(|| result.clone());
+    channel.broadcast(&GameEvent::PracticeEnd {
+        result,
+        personal_best,
+        new_best,
+    });
+}
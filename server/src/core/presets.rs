@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use chrono::{Duration, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tracing::error;
+
+use crate::{
+    config,
+    datatypes::{Gamemode, MatchConfiguration, RoomConfiguration},
+    server::shutdown::{self, WriteGuard},
+    store::{self, preset::RoomPreset},
+};
+
+use super::{models::team::BaseTeam, util::roomcode::generate_roomcode};
+
+static PRESETS: Lazy<RwLock<PlayerPresets>> = Lazy::new(Default::default);
+
+/// Changes to the presets of players, saved to the store by a single task so that they are
+/// applied in the order they were made.
+enum PresetWrite {
+    Save(Box<RoomPreset>),
+    Delete(i64),
+}
+
+static WRITES: Lazy<UnboundedSender<(WriteGuard, PresetWrite)>> = Lazy::new(|| {
+    let (sender, mut receiver) = unbounded_channel();
+    tokio::spawn(async move {
+        // the guard keeps a write pending until it is applied
+        while let Some((_guard, write)) = receiver.recv().await {
+            match write {
+                PresetWrite::Save(preset) => {
+                    if let Err(e) = store::preset::save_preset(&preset).await {
+                        error!("failed to save room preset {}: {}", preset.id, e);
+                    }
+                }
+                PresetWrite::Delete(id) => {
+                    if let Err(e) = store::preset::delete_preset(id).await {
+                        error!("failed to delete room preset {}: {}", id, e);
+                    }
+                }
+            }
+        }
+    });
+    sender
+});
+
+/// Presets defined by the server, available to every player. Their ids are negative so they
+/// never collide with the presets of players.
+static OFFICIAL_PRESETS: Lazy<Vec<RoomPreset>> = Lazy::new(|| {
+    vec![
+        official_preset(
+            -1,
+            "Quick 3x3",
+            MatchConfiguration {
+                grid_size: 3,
+                time_limit: Duration::minutes(20),
+                ..Default::default()
+            },
+        ),
+        official_preset(
+            -2,
+            "Frenzy Chaos",
+            MatchConfiguration {
+                mode: Gamemode::Frenzy,
+                grid_size: 5,
+                items_tick_multiplier: 2000,
+                inventory_size: 3,
+                ..Default::default()
+            },
+        ),
+    ]
+});
+
+fn official_preset(id: i64, name: &str, match_config: MatchConfiguration) -> RoomPreset {
+    RoomPreset {
+        id,
+        owner_uid: None,
+        name: name.to_owned(),
+        share_code: None,
+        config: RoomConfiguration {
+            name: name.to_owned(),
+            ..Default::default()
+        },
+        match_config,
+        teams: Vec::new(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// The presets saved by players, indexed by id.
+struct PlayerPresets {
+    presets: HashMap<i64, RoomPreset>,
+    /// Ids are never given twice, so that a write for a deleted preset can't affect a new one.
+    next_id: i64,
+}
+
+impl Default for PlayerPresets {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl PlayerPresets {
+    fn new(presets: Vec<RoomPreset>) -> Self {
+        let next_id = presets.iter().map(|preset| preset.id).max().unwrap_or(0) + 1;
+        Self {
+            presets: presets
+                .into_iter()
+                .map(|preset| (preset.id, preset))
+                .collect(),
+            next_id,
+        }
+    }
+
+    fn owned(&self, player_uid: i32, id: i64) -> Option<&RoomPreset> {
+        self.presets
+            .get(&id)
+            .filter(|preset| preset.owner_uid == Some(player_uid))
+    }
+
+    fn new_share_code(&self) -> String {
+        loop {
+            let code = generate_roomcode();
+            if !self
+                .presets
+                .values()
+                .any(|preset| preset.share_code.as_ref() == Some(&code))
+            {
+                return code;
+            }
+        }
+    }
+
+    fn create(
+        &mut self,
+        player_uid: i32,
+        name: String,
+        config: RoomConfiguration,
+        match_config: MatchConfiguration,
+        teams: Vec<BaseTeam>,
+        max_presets: usize,
+    ) -> Result<RoomPreset, anyhow::Error> {
+        if self
+            .presets
+            .values()
+            .filter(|preset| preset.owner_uid == Some(player_uid))
+            .count()
+            >= max_presets
+        {
+            return Err(anyhow!(
+                "You can save up to {} presets, delete one to save a new preset.",
+                max_presets
+            ));
+        }
+
+        let preset = RoomPreset {
+            id: self.next_id,
+            owner_uid: Some(player_uid),
+            name,
+            share_code: Some(self.new_share_code()),
+            config,
+            match_config,
+            teams,
+            updated_at: Utc::now(),
+        };
+        self.next_id = preset.id + 1;
+        self.presets.insert(preset.id, preset.clone());
+        Ok(preset)
+    }
+
+    fn update(
+        &mut self,
+        player_uid: i32,
+        id: i64,
+        name: String,
+        config: RoomConfiguration,
+        match_config: MatchConfiguration,
+        teams: Vec<BaseTeam>,
+    ) -> Result<RoomPreset, anyhow::Error> {
+        let preset = self
+            .presets
+            .get_mut(&id)
+            .filter(|preset| preset.owner_uid == Some(player_uid))
+            .ok_or(anyhow!("This preset does not exist or is not yours."))?;
+        preset.name = name;
+        preset.config = config;
+        preset.match_config = match_config;
+        preset.teams = teams;
+        preset.updated_at = Utc::now();
+        Ok(preset.clone())
+    }
+
+    fn delete(&mut self, player_uid: i32, id: i64) -> Result<(), anyhow::Error> {
+        if self.owned(player_uid, id).is_none() {
+            return Err(anyhow!("This preset does not exist or is not yours."));
+        }
+        self.presets.remove(&id);
+        Ok(())
+    }
+
+    fn import(
+        &mut self,
+        player_uid: i32,
+        share_code: &str,
+        max_presets: usize,
+    ) -> Result<RoomPreset, anyhow::Error> {
+        let shared = self
+            .presets
+            .values()
+            .find(|preset| preset.share_code.as_deref() == Some(share_code))
+            .cloned()
+            .ok_or(anyhow!("No preset was shared with this code."))?;
+        self.create(
+            player_uid,
+            shared.name,
+            shared.config,
+            shared.match_config,
+            shared.teams,
+            max_presets,
+        )
+    }
+}
+
+/// Load the presets of all players from the store.
+pub async fn load_presets() {
+    match store::preset::get_presets().await {
+        Ok(presets) => *PRESETS.write() = PlayerPresets::new(presets),
+        Err(e) => error!("failed to load room presets: {}", e),
+    }
+}
+
+fn write(write: PresetWrite) {
+    if WRITES.send((shutdown::track_write(), write)).is_err() {
+        error!("room presets can no longer be saved");
+    }
+}
+
+fn max_presets() -> usize {
+    config::get_integer("behaviour.max_presets").unwrap_or(20) as usize
+}
+
+/// The presets a player can create a room from: their own and the official ones.
+pub fn get_presets(player_uid: i32) -> Vec<RoomPreset> {
+    let mut presets = OFFICIAL_PRESETS.clone();
+    let mut owned: Vec<RoomPreset> = PRESETS
+        .read()
+        .presets
+        .values()
+        .filter(|preset| preset.owner_uid == Some(player_uid))
+        .cloned()
+        .collect();
+    owned.sort_by(|a, b| a.name.cmp(&b.name));
+    presets.extend(owned);
+    presets
+}
+
+/// Get a preset by id, if it is official or owned by the player.
+pub fn get_preset(player_uid: i32, id: i64) -> Option<RoomPreset> {
+    OFFICIAL_PRESETS
+        .iter()
+        .find(|preset| preset.id == id)
+        .cloned()
+        .or_else(|| PRESETS.read().owned(player_uid, id).cloned())
+}
+
+pub fn create_preset(
+    player_uid: i32,
+    name: String,
+    config: RoomConfiguration,
+    match_config: MatchConfiguration,
+    teams: Vec<BaseTeam>,
+) -> Result<RoomPreset, anyhow::Error> {
+    let preset =
+        PRESETS
+            .write()
+            .create(player_uid, name, config, match_config, teams, max_presets())?;
+    write(PresetWrite::Save(Box::new(preset.clone())));
+    Ok(preset)
+}
+
+pub fn update_preset(
+    player_uid: i32,
+    id: i64,
+    name: String,
+    config: RoomConfiguration,
+    match_config: MatchConfiguration,
+    teams: Vec<BaseTeam>,
+) -> Result<RoomPreset, anyhow::Error> {
+    let preset = PRESETS
+        .write()
+        .update(player_uid, id, name, config, match_config, teams)?;
+    write(PresetWrite::Save(Box::new(preset.clone())));
+    Ok(preset)
+}
+
+pub fn delete_preset(player_uid: i32, id: i64) -> Result<(), anyhow::Error> {
+    PRESETS.write().delete(player_uid, id)?;
+    write(PresetWrite::Delete(id));
+    Ok(())
+}
+
+/// Save a copy of the preset shared with the given code to the presets of the player.
+pub fn import_preset(player_uid: i32, share_code: &str) -> Result<RoomPreset, anyhow::Error> {
+    let preset = PRESETS
+        .write()
+        .import(player_uid, share_code, max_presets())?;
+    write(PresetWrite::Save(Box::new(preset.clone())));
+    Ok(preset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(presets: &mut PlayerPresets, player_uid: i32, name: &str) -> RoomPreset {
+        presets
+            .create(
+                player_uid,
+                name.to_owned(),
+                RoomConfiguration::default(),
+                MatchConfiguration::default(),
+                Vec::new(),
+                2,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn presets_are_created_updated_and_deleted() {
+        let mut presets = PlayerPresets::default();
+        let first = create(&mut presets, 1, "First");
+        let second = create(&mut presets, 1, "Second");
+        assert_ne!(first.share_code, second.share_code);
+
+        let updated = presets
+            .update(
+                1,
+                first.id,
+                "Renamed".to_owned(),
+                RoomConfiguration::default(),
+                MatchConfiguration {
+                    grid_size: 3,
+                    ..Default::default()
+                },
+                Vec::new(),
+            )
+            .unwrap();
+        assert_eq!(updated.name, "Renamed");
+        assert_eq!(
+            presets.owned(1, first.id).unwrap().match_config.grid_size,
+            3
+        );
+
+        presets.delete(1, second.id).unwrap();
+        assert!(presets.owned(1, second.id).is_none());
+        // ids of deleted presets are not given again
+        assert!(create(&mut presets, 1, "Third").id > second.id);
+    }
+
+    #[test]
+    fn presets_can_only_be_changed_by_their_owner() {
+        let mut presets = PlayerPresets::default();
+        let preset = create(&mut presets, 1, "Mine");
+        assert!(presets.owned(2, preset.id).is_none());
+        let update = presets.update(
+            2,
+            preset.id,
+            "Stolen".to_owned(),
+            RoomConfiguration::default(),
+            MatchConfiguration::default(),
+            Vec::new(),
+        );
+        assert!(update.is_err());
+        assert!(presets.delete(2, preset.id).is_err());
+        assert_eq!(presets.owned(1, preset.id).unwrap().name, "Mine");
+    }
+
+    #[test]
+    fn shared_presets_are_imported_as_copies() {
+        let mut presets = PlayerPresets::default();
+        let shared = create(&mut presets, 1, "Shared");
+        let imported = presets
+            .import(2, shared.share_code.as_deref().unwrap(), 2)
+            .unwrap();
+        assert_ne!(imported.id, shared.id);
+        assert_ne!(imported.share_code, shared.share_code);
+        assert_eq!(imported.owner_uid, Some(2));
+        assert_eq!(imported.name, "Shared");
+        assert!(presets.import(2, "NOCODE", 2).is_err());
+    }
+
+    #[test]
+    fn presets_are_limited_per_player() {
+        let mut presets = PlayerPresets::default();
+        create(&mut presets, 1, "First");
+        let shared = create(&mut presets, 1, "Second");
+        let over_limit = presets.create(
+            1,
+            "Third".to_owned(),
+            RoomConfiguration::default(),
+            MatchConfiguration::default(),
+            Vec::new(),
+            2,
+        );
+        assert!(over_limit.is_err());
+        assert!(presets
+            .import(1, shared.share_code.as_deref().unwrap(), 2)
+            .is_err());
+        // the limit applies to each player separately
+        create(&mut presets, 2, "Other");
+    }
+}
@@ -1,15 +1,15 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Weak},
 };
 
 use anyhow::anyhow;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use parking_lot::Mutex;
 use rand::{distributions::Uniform, seq::SliceRandom, Rng};
 use serde::{Serialize, Serializer};
 use thiserror::Error;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 use super::{
     directory::{self, Owned, Shared, PUB_ROOMS_CHANNEL, ROOMS},
@@ -20,16 +20,24 @@ use super::{
         self,
         map::GameMap,
         room::{RoomState, RoomTeam},
-        team::{BaseTeam, GameTeam, TeamIdentifier},
+        snapshot::RoomSnapshot,
+        team::{BaseTeam, GameTeam, Handicap, TeamIdentifier},
     },
     teams::TeamsManager,
-    util::Color,
+    util::{
+        rng::{self, MatchRng},
+        Color,
+    },
 };
 use crate::{
     config,
     core::models::room::LoadState,
-    datatypes::{MatchConfiguration, Medal, PlayerProfile, PlayerRef, RoomConfiguration},
-    server::{context::ClientContext, mapload},
+    datatypes::{
+        Gamemode, MapMode, MatchConfiguration, Medal, PlayerProfile, PlayerRef, PracticeGoal,
+        RoomConfiguration,
+    },
+    server::{context::ClientContext, mapload, shutdown},
+    store,
     transport::Channel,
 };
 
@@ -48,6 +56,11 @@ pub struct GameRoom {
     host_uid: Option<i32>,
     verification_locked: bool,
     mapload_status: LoadState,
+    load_warnings: Vec<String>,
+    rng: MatchRng,
+    recent_maps: HashMap<i32, HashSet<i32>>,
+    scheduled_start: Option<DateTime<Utc>>,
+    registrations: HashMap<i32, TeamIdentifier>,
 }
 
 impl GameRoom {
@@ -56,6 +69,7 @@ impl GameRoom {
         matchconfig: MatchConfiguration,
         join_code: String,
     ) -> Owned<Self> {
+        let rng = rng::from_optional_seed(matchconfig.seed);
         let _self = Self {
             ptr: Weak::new(),
             join_code,
@@ -71,12 +85,52 @@ impl GameRoom {
             host_uid: None,
             verification_locked: false,
             mapload_status: LoadState::default(),
+            load_warnings: Vec::new(),
+            rng,
+            recent_maps: HashMap::new(),
+            scheduled_start: None,
+            registrations: HashMap::new(),
         };
         let arc = Arc::new(Mutex::new(_self));
         arc.lock().ptr = Arc::downgrade(&arc);
         arc
     }
 
+    /// Create a room again from a snapshot. Its members are expected to join again, and are put
+    /// back in their team when they do.
+    pub fn restore(snapshot: RoomSnapshot) -> Owned<Self> {
+        let arc = Self::create(snapshot.config, snapshot.match_config, snapshot.join_code);
+        let mut room = arc.lock();
+        room.teams = TeamsManager::from_teams(snapshot.teams, snapshot.teams_id);
+        room.registrations = snapshot.registrations;
+        room.created = snapshot.created;
+        room.host_uid = snapshot.host_uid;
+        room.loaded_maps = snapshot.loaded_maps;
+        room.load_warnings = snapshot.load_warnings;
+        room.scheduled_start = snapshot.scheduled_start;
+        room.update_maps_loaded_status();
+        drop(room);
+        arc
+    }
+
+    pub fn snapshot(&self) -> RoomSnapshot {
+        let mut registrations = self.registrations.clone();
+        registrations.extend(self.members.iter().map(|p| (p.uid, p.team)));
+        RoomSnapshot {
+            join_code: self.join_code.clone(),
+            config: self.config.clone(),
+            match_config: self.matchconfig.clone(),
+            teams: self.teams.get_teams().clone(),
+            teams_id: self.teams.teams_id(),
+            registrations,
+            created: self.created,
+            host_uid: self.host_uid,
+            loaded_maps: self.loaded_maps.clone(),
+            load_warnings: self.load_warnings.clone(),
+            scheduled_start: self.scheduled_start,
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.config.name
     }
@@ -130,6 +184,31 @@ impl GameRoom {
         self.members.iter().filter(|p| p.uid == uid).next()
     }
 
+    pub fn set_team_handicap(
+        &mut self,
+        team: TeamIdentifier,
+        handicap: Handicap,
+    ) -> Result<(), anyhow::Error> {
+        self.teams
+            .get_mut(team)
+            .ok_or(anyhow!("team id {} not found", team))?
+            .handicap = handicap;
+        self.broadcast_sync();
+        Ok(())
+    }
+
+    pub fn set_player_handicap(
+        &mut self,
+        uid: i32,
+        handicap: Handicap,
+    ) -> Result<(), anyhow::Error> {
+        self.get_player_mut(uid)
+            .ok_or(anyhow!("player {} is not in this room", uid))?
+            .handicap = handicap;
+        self.broadcast_sync();
+        Ok(())
+    }
+
     pub fn get_player_mut(&mut self, uid: i32) -> Option<&mut PlayerData> {
         self.members.iter_mut().filter(|p| p.uid == uid).next()
     }
@@ -142,6 +221,28 @@ impl GameRoom {
         self.host_uid = Some(uid)
     }
 
+    pub fn scheduled_start(&self) -> Option<DateTime<Utc>> {
+        self.scheduled_start
+    }
+
+    /// Open the room ahead of a scheduled match start, with the teams players registered to.
+    pub fn set_schedule(
+        &mut self,
+        start: DateTime<Utc>,
+        registrations: HashMap<i32, TeamIdentifier>,
+    ) {
+        self.scheduled_start = Some(start);
+        self.registrations = registrations;
+    }
+
+    /// Register a player to a team, they are put in it when they join. `None` removes the registration.
+    pub fn register_player(&mut self, uid: i32, team: Option<TeamIdentifier>) {
+        match team {
+            Some(team) => self.registrations.insert(uid, team),
+            None => self.registrations.remove(&uid),
+        };
+    }
+
     pub fn teams_as_model(&self) -> Vec<RoomTeam> {
         self.team_members()
             .into_iter()
@@ -180,6 +281,12 @@ impl GameRoom {
                         }
                     })
                     .collect(),
+                handicaps: players
+                    .iter()
+                    .filter_map(|id| self.get_player(*id))
+                    .filter(|data| !data.handicap.is_none())
+                    .map(|data| (data.uid, data.handicap.clone()))
+                    .collect(),
             })
             .collect()
     }
@@ -223,22 +330,49 @@ impl GameRoom {
         profile: &PlayerProfile,
         operator: bool,
     ) -> TeamIdentifier {
-        let team = self
-            .get_least_populated_team()
-            .expect("0 teams in self.teams")
-            .id;
+        let team = match self.registrations.get(&profile.uid) {
+            Some(team) if self.teams.exists(*team) => *team,
+            _ => {
+                self.get_least_populated_team()
+                    .expect("0 teams in self.teams")
+                    .id
+            }
+        };
         self.members.push(PlayerData {
             uid: profile.uid,
             profile: profile.clone(),
             team,
             operator,
             disconnected: false,
+            handicap: Handicap::default(),
             writer: ctx.writer.clone(),
         });
         self.channel.subscribe(profile.uid, ctx.writer.clone());
+        if self.matchconfig.avoid_recent_maps {
+            self.fetch_recent_maps(profile.uid);
+        }
         team
     }
 
+    /// Load the maps a member had on a board recently, so they can be avoided in the next match.
+    fn fetch_recent_maps(&self, player_uid: i32) {
+        let room = self.ptr.clone();
+        let days = config::get_integer("maps.recent_days").unwrap_or(14);
+        let since = Utc::now() - TimeDelta::days(days);
+        tokio::spawn(async move {
+            match store::matches::get_recent_maps(player_uid, since).await {
+                Ok(maps) => {
+                    if let Some(room) = room.upgrade() {
+                        room.lock()
+                            .recent_maps
+                            .insert(player_uid, maps.into_iter().collect());
+                    }
+                }
+                Err(e) => error!("failed to load recent maps of player {}: {}", player_uid, e),
+            }
+        });
+    }
+
     pub fn get_load_marker(&self) -> u32 {
         self.load_marker
     }
@@ -258,11 +392,19 @@ impl GameRoom {
         }
     }
 
+    /// Set the problems encountered while loading maps, shown to players along with the load status.
+    pub fn set_load_warnings(&mut self, warnings: Vec<String>, userdata: u32) {
+        if userdata == self.load_marker {
+            self.load_warnings = warnings;
+        }
+    }
+
     fn update_maps_loaded_status(&mut self) {
         self.mapload_status = if self.loaded_maps.is_empty() {
             LoadState::Unloaded
         } else if self.loaded_maps.len()
             < (self.matchconfig.grid_size * self.matchconfig.grid_size) as usize
+            || !self.load_warnings.is_empty()
         {
             LoadState::Warn
         } else {
@@ -286,7 +428,12 @@ impl GameRoom {
                 });
 
             if !maps_without_wr.is_empty() {
-                mapload::reload_maps(self.ptr.clone(), maps_without_wr, self.load_marker);
+                mapload::reload_maps(
+                    self.ptr.clone(),
+                    &self.matchconfig,
+                    maps_without_wr,
+                    self.load_marker,
+                );
                 self.set_maps_loaded_status(LoadState::Loading);
             }
             return maps_with_wr;
@@ -310,7 +457,11 @@ impl GameRoom {
         if self.at_size_capacity() {
             return Err(JoinRoomError::PlayerLimitReached);
         }
-        if self.has_started() && !self.matchconfig().late_join {
+        // registered players, such as the members of a restored room, can always come back
+        if self.has_started()
+            && !self.matchconfig().late_join
+            && !self.registrations.contains_key(&profile.uid)
+        {
             return Err(JoinRoomError::HasStarted);
         }
         if self.has_player(ctx.profile.uid) {
@@ -342,6 +493,7 @@ impl GameRoom {
 
     pub fn player_remove(&mut self, uid: i32) {
         self.members.retain(|m| m.uid != uid);
+        self.recent_maps.remove(&uid);
         self.channel.unsubscribe(uid);
         self.channel.broadcast(&RoomEvent::PlayerLeave { uid: uid });
 
@@ -370,14 +522,7 @@ impl GameRoom {
     }
 
     pub fn sort_teams(&mut self) {
-        let mut unproccessed: Vec<&mut PlayerData> = self.members.iter_mut().collect();
-        let mut teams = self.teams.get_teams().iter().cycle();
-        let mut rng = rand::thread_rng();
-        while unproccessed.len() > 0 {
-            let dist = Uniform::new(0, unproccessed.len());
-            let selected = unproccessed.remove(rng.sample(dist));
-            selected.team = teams.next().unwrap().id;
-        }
+        assign_random_teams(&mut self.members, &self.teams, &mut self.rng);
         self.broadcast_all_player_teams();
     }
 
@@ -417,7 +562,7 @@ impl GameRoom {
     pub fn create_team_from_preset(&mut self, teams: &Vec<(String, Color)>) -> Option<BaseTeam> {
         let team = self
             .teams
-            .create_team_from_preset(teams)
+            .create_team_from_preset(teams, &mut self.rng)
             .map(BaseTeam::clone);
         if let Some(new_team) = &team {
             self.team_created(new_team);
@@ -476,8 +621,20 @@ impl GameRoom {
             || self.matchconfig.map_tag != config.map_tag
             || self.matchconfig.campaign_selection != config.campaign_selection
             || self.matchconfig.discovery != config.discovery
+            || self.matchconfig.filters != config.filters
+            || self.matchconfig.map_sources != config.map_sources
+            || self.matchconfig.custom_maps != config.custom_maps
+            || (config.avoid_recent_maps && !self.matchconfig.avoid_recent_maps)
             || self.matchconfig.grid_size < config.grid_size;
 
+        if config.avoid_recent_maps && !self.matchconfig.avoid_recent_maps {
+            for uid in self.members.iter().map(|p| p.uid) {
+                self.fetch_recent_maps(uid);
+            }
+        }
+        if rng::configured_seed(config.seed).is_some() && config.seed != self.matchconfig.seed {
+            self.rng = rng::from_optional_seed(config.seed);
+        }
         self.matchconfig = config;
         if mapconfig_changed {
             // map selection changed, reload maps
@@ -487,6 +644,7 @@ impl GameRoom {
 
     pub fn reload_maps(&mut self) {
         self.loaded_maps = Vec::new();
+        self.load_warnings = Vec::new();
         mapload::load_maps(self.ptr.clone(), &self.matchconfig, self.get_load_marker());
         self.set_maps_loaded_status(LoadState::Loading);
     }
@@ -533,6 +691,11 @@ impl GameRoom {
             return;
         }
 
+        // scheduled rooms wait for their host until the match start
+        if self.scheduled_start.is_some_and(|start| start > Utc::now()) {
+            return;
+        }
+
         if !self.members.iter().any(|p| p.operator) {
             debug!("No room operator, closing.");
             self.close_room("The host has left the room.".to_owned());
@@ -557,15 +720,74 @@ impl GameRoom {
             .broadcast(&RoomEvent::RoomSync(self.get_state()));
     }
 
-    fn prepare_start_match(&mut self) {
+    fn prepare_start_match(&mut self) -> (u64, MatchRng) {
+        let seed = rng::configured_seed(self.matchconfig.seed).unwrap_or_else(rng::generate_seed);
+        let mut rng = rng::from_seed(seed);
         if self.config.randomize {
-            self.sort_teams();
+            assign_random_teams(&mut self.members, &self.teams, &mut rng);
+            self.broadcast_all_player_teams();
         }
 
-        self.loaded_maps.shuffle(&mut rand::thread_rng());
+        // maps can be loaded in any order, sort them so that only the seed decides the board
+        let cells = (self.matchconfig.grid_size * self.matchconfig.grid_size) as usize;
+        match self.matchconfig.selection {
+            MapMode::Custom => {
+                // hand-picked boards keep the order chosen by the host
+                self.loaded_maps.sort_by_key(GameMap::sort_key);
+                let maps = std::mem::take(&mut self.loaded_maps);
+                self.loaded_maps =
+                    mapload::arrange_board(maps, &self.matchconfig.custom_maps, cells);
+                return (seed, rng);
+            }
+            MapMode::Mixed => {
+                // mixed boards hold each source's share of the cells, keep them apart from the spares
+                let board_len = cells.min(self.loaded_maps.len());
+                let (board, spares) = self.loaded_maps.split_at_mut(board_len);
+                for part in [board, spares] {
+                    part.sort_by_key(GameMap::sort_key);
+                    part.shuffle(&mut rng);
+                }
+            }
+            _ => {
+                self.loaded_maps.sort_by_key(GameMap::sort_key);
+                self.loaded_maps.shuffle(&mut rng);
+            }
+        }
+        if self.matchconfig.avoid_recent_maps {
+            // keep the shuffled order, but put maps that members played recently last
+            let recent: HashSet<i32> = self.recent_maps.values().flatten().copied().collect();
+            self.loaded_maps.sort_by_key(|map| match map {
+                GameMap::TMX(record) => recent.contains(&record.tmxid),
+                _ => false,
+            });
+        }
+        (seed, rng)
     }
 
     pub fn check_start_match(&mut self) -> Result<(), anyhow::Error> {
+        if shutdown::is_draining() {
+            return Err(anyhow!(
+                "The server is about to restart, new matches cannot be started."
+            ));
+        }
+        if self.matchconfig.practice_goal != PracticeGoal::None {
+            if self.members.len() > 1 {
+                return Err(anyhow!(
+                    "Practice matches are played alone, other players must leave the room first."
+                ));
+            }
+            if self.matchconfig.practice_goal == PracticeGoal::TimeAttack
+                && self.matchconfig.time_limit.is_zero()
+            {
+                return Err(anyhow!("A time attack practice requires a time limit."));
+            }
+        }
+        if self.matchconfig.mode == Gamemode::Points && self.matchconfig.time_limit.is_zero() {
+            return Err(anyhow!(
+                "The Points gamemode requires a time limit, the highest score wins when it runs out."
+            ));
+        }
+
         let map_count_minimum = self.matchconfig.grid_size * self.matchconfig.grid_size;
         let count = self.loaded_maps.len();
         if count < map_count_minimum as usize {
@@ -591,6 +813,7 @@ impl GameRoom {
         self.set_verification_locked(true);
         mapload::verify_map_records(
             self.ptr.clone(),
+            &self.matchconfig,
             maps,
             self.players()
                 .iter()
@@ -609,14 +832,17 @@ impl GameRoom {
         self.channel.broadcast(&RoomEvent::RoomExtrasUpdate {
             locked: self.verification_locked,
             load_status: self.mapload_status,
+            load_warnings: self.load_warnings.clone(),
         });
     }
 
     fn start_match(&mut self) -> Owned<LiveMatch> {
-        self.prepare_start_match();
+        let (seed, rng) = self.prepare_start_match();
         let start_date = Utc::now();
         let match_arc = LiveMatch::new(
             self.matchconfig.clone(),
+            seed,
+            rng,
             self.loaded_maps.clone(),
             TeamsManager::from_teams(
                 self.teams_as_model()
@@ -650,6 +876,11 @@ impl GameRoom {
         })
     }
 
+    /// Set the match played in this room, when it is restored.
+    pub fn attach_match(&mut self, game: &Owned<LiveMatch>) {
+        self.active_match = Some(Arc::downgrade(game));
+    }
+
     pub fn reset_match(&mut self) {
         self.active_match = None;
         self.send_in_game_status_update();
@@ -677,6 +908,22 @@ impl Serialize for GameRoom {
     }
 }
 
+fn assign_random_teams<R: Rng>(
+    members: &mut [PlayerData],
+    teams: &TeamsManager<BaseTeam>,
+    rng: &mut R,
+) {
+    // sort by player id first so that a seed gives the same teams regardless of join order
+    let mut unproccessed: Vec<&mut PlayerData> = members.iter_mut().collect();
+    unproccessed.sort_by_key(|p| p.uid);
+    let mut teams = teams.get_teams().iter().cycle();
+    while !unproccessed.is_empty() {
+        let dist = Uniform::new(0, unproccessed.len());
+        let selected = unproccessed.remove(rng.sample(dist));
+        selected.team = teams.next().unwrap().id;
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum JoinRoomError {
     #[error("The room is already full.")]
@@ -700,6 +947,8 @@ pub struct PlayerUpdates {
 pub struct NetworkTeam {
     pub info: BaseTeam,
     pub members: Vec<PlayerRef>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub handicaps: HashMap<i32, Handicap>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -711,3 +960,30 @@ pub struct NetworkRoom {
     pub matchconfig: MatchConfiguration,
     pub player_count: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_default_seed_is_replaced() {
+        // clients send a seed of 0 when the host did not set one
+        let matchconfig = MatchConfiguration {
+            seed: Some(0),
+            ..Default::default()
+        };
+        let room = GameRoom::create(
+            RoomConfiguration::default(),
+            matchconfig,
+            "SEED00".to_owned(),
+        );
+        let mut room = room.lock();
+        let (first, _) = room.prepare_start_match();
+        let (second, _) = room.prepare_start_match();
+        assert_ne!(first, 0);
+        assert_ne!(first, second);
+
+        room.matchconfig.seed = Some(42);
+        assert_eq!(room.prepare_start_match().0, 42);
+    }
+}
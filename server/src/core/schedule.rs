@@ -0,0 +1,521 @@
+// Rooms scheduled by hosts to open and start at a later time
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration as StdDuration,
+};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    time::sleep,
+};
+use tracing::{error, info};
+
+use crate::{
+    config,
+    datatypes::{MatchConfiguration, PracticeGoal, RoomConfiguration},
+    server::shutdown::{self, WriteGuard},
+    store::{
+        self,
+        schedule::{Registration, ScheduledRoom},
+    },
+};
+
+use super::{
+    directory::{self, Owned, Shared, ROOMS},
+    models::team::{BaseTeam, TeamIdentifier},
+    room::GameRoom,
+};
+
+const SCHEDULE_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(10);
+const MAX_DAYS_AHEAD: i64 = 30;
+/// Time after its start during which a room missed while the server was down is still opened.
+const MISSED_START_GRACE_MINUTES: i64 = 30;
+
+struct Schedule {
+    room: ScheduledRoom,
+    /// The room once it is open, with the ids of its teams in the order of the scheduled teams.
+    opened: Option<(Shared<GameRoom>, Vec<TeamIdentifier>)>,
+}
+
+static SCHEDULES: Lazy<RwLock<HashMap<i64, Schedule>>> = Lazy::new(Default::default);
+/// Ids are never given twice, so that a write for a finished schedule can't affect a new one.
+static NEXT_SCHEDULE_ID: AtomicI64 = AtomicI64::new(1);
+
+/// Changes to scheduled rooms, saved to the store by a single task so that they are applied in
+/// the order they were made.
+enum ScheduleWrite {
+    Save(Box<ScheduledRoom>),
+    Delete(i64),
+    Register(i64, Registration),
+    Unregister(i64, i32),
+}
+
+static WRITES: Lazy<UnboundedSender<(WriteGuard, ScheduleWrite)>> = Lazy::new(|| {
+    let (sender, mut receiver) = unbounded_channel();
+    tokio::spawn(async move {
+        // the guard keeps a write pending until it is applied
+        while let Some((_guard, write)) = receiver.recv().await {
+            apply_write(write).await;
+        }
+    });
+    sender
+});
+
+async fn apply_write(write: ScheduleWrite) {
+    match write {
+        ScheduleWrite::Save(room) => {
+            if let Err(e) = store::schedule::save_scheduled_room(&room).await {
+                error!("failed to save scheduled room {}: {}", room.id, e);
+            }
+        }
+        ScheduleWrite::Delete(id) => {
+            if let Err(e) = store::schedule::delete_scheduled_room(id).await {
+                error!("failed to delete scheduled room {}: {}", id, e);
+            }
+        }
+        ScheduleWrite::Register(room_id, registration) => {
+            if let Err(e) = store::schedule::save_registration(room_id, &registration).await {
+                error!("failed to save registration to room {}: {}", room_id, e);
+            }
+        }
+        ScheduleWrite::Unregister(room_id, player_uid) => {
+            if let Err(e) = store::schedule::delete_registration(room_id, player_uid).await {
+                error!("failed to delete registration to room {}: {}", room_id, e);
+            }
+        }
+    }
+}
+
+fn write(write: ScheduleWrite) {
+    if WRITES.send((shutdown::track_write(), write)).is_err() {
+        error!("scheduled rooms can no longer be saved");
+    }
+}
+
+/// Load the scheduled rooms from the store, dropping the ones whose start was missed.
+pub async fn load_schedules() {
+    let rooms = match store::schedule::get_scheduled_rooms().await {
+        Ok(rooms) => rooms,
+        Err(e) => {
+            error!("failed to load scheduled rooms: {}", e);
+            return;
+        }
+    };
+    let next_id = rooms.iter().map(|room| room.id).max().unwrap_or(0) + 1;
+    NEXT_SCHEDULE_ID.fetch_max(next_id, Ordering::Relaxed);
+
+    let oldest = Utc::now() - Duration::minutes(MISSED_START_GRACE_MINUTES);
+    let mut schedules = SCHEDULES.write();
+    for room in rooms {
+        if room.start_at < oldest {
+            info!("scheduled room {} was missed, removing it", room.join_code);
+            write(ScheduleWrite::Delete(room.id));
+            continue;
+        }
+        schedules.insert(room.id, Schedule { room, opened: None });
+    }
+}
+
+fn open_at(room: &ScheduledRoom) -> DateTime<Utc> {
+    room.start_at
+        - Duration::minutes(config::get_integer("behaviour.schedule_open_minutes").unwrap_or(15))
+}
+
+/// Whether a join code is kept for a scheduled room.
+pub fn is_reserved(join_code: &str) -> bool {
+    SCHEDULES
+        .read()
+        .values()
+        .any(|schedule| schedule.room.join_code == join_code)
+}
+
+/// Get the scheduled room that will open with the given join code.
+pub fn find(join_code: &str) -> Option<ScheduledRoom> {
+    SCHEDULES
+        .read()
+        .values()
+        .find(|schedule| schedule.room.join_code == join_code)
+        .map(|schedule| schedule.room.clone())
+}
+
+/// Public rooms that have not opened yet, the earliest first.
+pub fn get_listed() -> Vec<ScheduledRoom> {
+    let mut rooms: Vec<ScheduledRoom> = SCHEDULES
+        .read()
+        .values()
+        .filter(|schedule| schedule.opened.is_none() && schedule.room.config.public)
+        .map(|schedule| schedule.room.clone())
+        .collect();
+    rooms.sort_by_key(|room| room.start_at);
+    rooms
+}
+
+pub fn schedule_room(
+    host_uid: i32,
+    config: RoomConfiguration,
+    match_config: MatchConfiguration,
+    teams: Vec<BaseTeam>,
+    start_at: DateTime<Utc>,
+    min_players: u32,
+) -> Result<ScheduledRoom, anyhow::Error> {
+    let now = Utc::now();
+    if start_at <= now {
+        return Err(anyhow!("The start time of the room has already passed."));
+    }
+    if start_at > now + Duration::days(MAX_DAYS_AHEAD) {
+        return Err(anyhow!(
+            "Rooms can be scheduled up to {} days ahead.",
+            MAX_DAYS_AHEAD
+        ));
+    }
+    if match_config.practice_goal != PracticeGoal::None {
+        return Err(anyhow!("Practice rooms cannot be scheduled."));
+    }
+    let max_teams = config::get_integer("behaviour.max_teams").unwrap_or(6) as usize;
+    if teams.len() < 2 || teams.len() > max_teams {
+        return Err(anyhow!(
+            "A scheduled room needs between 2 and {} teams.",
+            max_teams
+        ));
+    }
+
+    // by default, at least one player in each team
+    let min_players = if min_players == 0 {
+        teams.len() as u32
+    } else {
+        min_players
+    };
+
+    let max_scheduled = config::get_integer("behaviour.max_scheduled_rooms").unwrap_or(3) as usize;
+    let join_code = directory::get_new_roomcode();
+    let mut schedules = SCHEDULES.write();
+    if schedules
+        .values()
+        .filter(|schedule| schedule.room.host_uid == host_uid)
+        .count()
+        >= max_scheduled
+    {
+        return Err(anyhow!(
+            "You can schedule up to {} rooms at once.",
+            max_scheduled
+        ));
+    }
+
+    let room = ScheduledRoom {
+        id: NEXT_SCHEDULE_ID.fetch_add(1, Ordering::Relaxed),
+        host_uid,
+        join_code,
+        config,
+        match_config,
+        teams,
+        start_at,
+        min_players,
+        registrations: Vec::new(),
+    };
+    schedules.insert(
+        room.id,
+        Schedule {
+            room: room.clone(),
+            opened: None,
+        },
+    );
+    write(ScheduleWrite::Save(Box::new(room.clone())));
+    Ok(room)
+}
+
+/// Cancel a scheduled room, closing it if it has opened already.
+pub fn cancel(player_uid: i32, join_code: &str, is_admin: bool) -> Result<(), anyhow::Error> {
+    let mut schedules = SCHEDULES.write();
+    let id = schedules
+        .values()
+        .find(|schedule| schedule.room.join_code == join_code)
+        .filter(|schedule| is_admin || schedule.room.host_uid == player_uid)
+        .map(|schedule| schedule.room.id)
+        .ok_or(anyhow!(
+            "This scheduled room does not exist or is not yours."
+        ))?;
+    let schedule = schedules.remove(&id).expect("schedule should exist");
+    drop(schedules);
+
+    write(ScheduleWrite::Delete(id));
+    if let Some(room) = schedule.opened.and_then(|(room, _)| room.upgrade()) {
+        room.lock()
+            .close_room("The host has cancelled this room.".to_owned());
+    }
+    Ok(())
+}
+
+/// Register a player to a team of a scheduled room. `None` removes their registration.
+pub fn register(
+    player_uid: i32,
+    join_code: &str,
+    team_index: Option<usize>,
+) -> Result<ScheduledRoom, anyhow::Error> {
+    let mut schedules = SCHEDULES.write();
+    let schedule = schedules
+        .values_mut()
+        .find(|schedule| schedule.room.join_code == join_code)
+        .ok_or(anyhow!(
+            "No scheduled room was found with code {}.",
+            join_code
+        ))?;
+    if team_index.is_some_and(|index| index >= schedule.room.teams.len()) {
+        return Err(anyhow!("This team does not exist in the scheduled room."));
+    }
+    let room_id = schedule.room.id;
+    let registrations = &mut schedule.room.registrations;
+    registrations.retain(|registration| registration.player_uid != player_uid);
+
+    match team_index {
+        Some(index) => {
+            let registration = Registration {
+                player_uid,
+                team_index: index,
+            };
+            registrations.push(registration.clone());
+            write(ScheduleWrite::Register(room_id, registration));
+        }
+        None => write(ScheduleWrite::Unregister(room_id, player_uid)),
+    }
+
+    if let Some((room, team_ids)) = &schedule.opened {
+        if let Some(room) = room.upgrade() {
+            let team = team_index.and_then(|index| team_ids.get(index).copied());
+            room.lock().register_player(player_uid, team);
+        }
+    }
+    Ok(schedule.room.clone())
+}
+
+/// Create the room of a schedule, with its teams and registrations.
+fn open_room(schedule: &ScheduledRoom) -> (Owned<GameRoom>, Vec<TeamIdentifier>) {
+    let room = GameRoom::create(
+        schedule.config.clone(),
+        schedule.match_config.clone(),
+        schedule.join_code.clone(),
+    );
+    let mut lock = room.lock();
+    let mut team_ids = Vec::new();
+    for team in &schedule.teams {
+        match lock.create_team(team.name.clone(), team.color) {
+            Ok(created) => {
+                if !team.handicap.is_none() {
+                    let _ = lock.set_team_handicap(created.id, team.handicap.clone());
+                }
+                team_ids.push(created.id);
+            }
+            Err(e) => error!("failed to create team of scheduled room: {}", e),
+        }
+    }
+    let registrations = schedule
+        .registrations
+        .iter()
+        .filter_map(|registration| {
+            team_ids
+                .get(registration.team_index)
+                .map(|team| (registration.player_uid, *team))
+        })
+        .collect();
+    lock.set_host_uid(schedule.host_uid);
+    lock.set_schedule(schedule.start_at, registrations);
+    lock.reload_maps();
+    if lock.config().public {
+        directory::send_room_visibility(&lock, true);
+    }
+    drop(lock);
+
+    ROOMS.insert(schedule.join_code.clone(), room.clone());
+    info!("opened scheduled room {}", schedule.join_code);
+    (room, team_ids)
+}
+
+/// Start the match of a scheduled room if enough players are present. The schedule is done either
+/// way, and the room stays open for its host to start the match.
+fn start_scheduled_match(id: i64) {
+    let Some(schedule) = SCHEDULES.write().remove(&id) else {
+        return;
+    };
+    write(ScheduleWrite::Delete(id));
+
+    let Some(room) = schedule.opened.and_then(|(room, _)| room.upgrade()) else {
+        return;
+    };
+    let mut lock = room.lock();
+    if lock.has_started() {
+        return;
+    }
+    let present = lock.players().len();
+    if present < schedule.room.min_players as usize {
+        info!(
+            "scheduled room {} has {} of {} players required, not starting",
+            schedule.room.join_code, present, schedule.room.min_players
+        );
+        if present == 0 {
+            lock.check_close();
+        }
+        return;
+    }
+    if let Err(e) = lock.check_start_match() {
+        info!(
+            "scheduled room {} could not start: {}",
+            schedule.room.join_code, e
+        );
+    }
+}
+
+/// Start the background task opening scheduled rooms and starting their matches on time.
+pub fn start_schedule_task() {
+    tokio::spawn(async {
+        loop {
+            // schedules are kept while draining, to open and start after the restart
+            if shutdown::is_draining() {
+                sleep(SCHEDULE_CHECK_INTERVAL).await;
+                continue;
+            }
+            let now = Utc::now();
+            let due: Vec<ScheduledRoom> = SCHEDULES
+                .read()
+                .values()
+                .filter(|schedule| schedule.opened.is_none() && now >= open_at(&schedule.room))
+                .map(|schedule| schedule.room.clone())
+                .collect();
+            for room in due {
+                let (opened, team_ids) = match ROOMS.find(room.join_code.clone()) {
+                    // the room was restored after a restart
+                    Some(restored) => {
+                        let team_ids = restored.lock().teams().iter().map(|team| team.id).collect();
+                        (restored, team_ids)
+                    }
+                    None => open_room(&room),
+                };
+                let cancelled = match SCHEDULES.write().get_mut(&room.id) {
+                    Some(schedule) => {
+                        schedule.opened = Some((Arc::downgrade(&opened), team_ids));
+                        false
+                    }
+                    None => true,
+                };
+                if cancelled {
+                    opened
+                        .lock()
+                        .close_room("The host has cancelled this room.".to_owned());
+                }
+            }
+
+            let starting: Vec<i64> = SCHEDULES
+                .read()
+                .values()
+                .filter(|schedule| now >= schedule.room.start_at)
+                .map(|schedule| schedule.room.id)
+                .collect();
+            for id in starting {
+                start_scheduled_match(id);
+            }
+            sleep(SCHEDULE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::util::Color, store::player::NewPlayer};
+
+    fn scheduled_room(id: i64, start_at: DateTime<Utc>) -> ScheduledRoom {
+        ScheduledRoom {
+            id,
+            host_uid: 1,
+            join_code: format!("SCHED{}", id),
+            config: RoomConfiguration::default(),
+            match_config: MatchConfiguration::default(),
+            teams: vec![
+                BaseTeam::new(0, "Red".to_owned(), Color::new(255, 0, 0)),
+                BaseTeam::new(1, "Blue".to_owned(), Color::new(0, 0, 255)),
+            ],
+            start_at,
+            min_players: 2,
+            registrations: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn load_schedules_drops_missed_rooms() {
+        store::initialize_test_store().await;
+        let host_uid = store::player::create_or_update_player(NewPlayer {
+            account_id: "scheduled-host".to_owned(),
+            username: "Host".to_owned(),
+            country_code: None,
+        })
+        .await
+        .unwrap();
+        let upcoming = ScheduledRoom {
+            host_uid,
+            ..scheduled_room(101, Utc::now() + Duration::hours(2))
+        };
+        let missed = ScheduledRoom {
+            host_uid,
+            ..scheduled_room(102, Utc::now() - Duration::hours(2))
+        };
+        store::schedule::save_scheduled_room(&upcoming)
+            .await
+            .unwrap();
+        store::schedule::save_scheduled_room(&missed).await.unwrap();
+        let registration = Registration {
+            player_uid: host_uid,
+            team_index: 1,
+        };
+        store::schedule::save_registration(upcoming.id, &registration)
+            .await
+            .unwrap();
+
+        load_schedules().await;
+        {
+            let schedules = SCHEDULES.read();
+            let loaded = &schedules
+                .get(&upcoming.id)
+                .expect("room should be loaded")
+                .room;
+            assert_eq!(loaded.join_code, upcoming.join_code);
+            assert_eq!(loaded.registrations, vec![registration]);
+            assert!(!schedules.contains_key(&missed.id));
+        }
+        // ids of loaded rooms are never given again
+        assert!(NEXT_SCHEDULE_ID.load(Ordering::Relaxed) > missed.id);
+        assert!(!is_reserved(&missed.join_code));
+    }
+
+    #[tokio::test]
+    async fn empty_scheduled_room_closes_at_start() {
+        store::initialize_test_store().await;
+        config::initialize_defaults();
+        let schedule = scheduled_room(201, Utc::now());
+        let room = GameRoom::create(
+            schedule.config.clone(),
+            schedule.match_config.clone(),
+            schedule.join_code.clone(),
+        );
+        room.lock().set_schedule(schedule.start_at, HashMap::new());
+        ROOMS.insert(schedule.join_code.clone(), room.clone());
+        SCHEDULES.write().insert(
+            schedule.id,
+            Schedule {
+                room: schedule.clone(),
+                opened: Some((Arc::downgrade(&room), Vec::new())),
+            },
+        );
+
+        start_scheduled_match(schedule.id);
+        assert!(!SCHEDULES.read().contains_key(&schedule.id));
+        assert!(!room.lock().has_started());
+        assert!(ROOMS.find(schedule.join_code.clone()).is_none());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{config, datatypes::Medal};
+
+use super::models::{livegame::GameCell, map::GameMap};
+
+/// Points of a claimed tile in the Points gamemode, before any bonus.
+fn base_points() -> i32 {
+    config::get_integer("scoring.tile_points").unwrap_or(1) as i32
+}
+
+/// Bonus points given to a team for each completed line in the Points gamemode.
+pub fn line_bonus() -> i32 {
+    config::get_integer("scoring.line_bonus").unwrap_or(5) as i32
+}
+
+/// Extra points for harder maps, based on the author time and the map's tags.
+fn difficulty_points(map: &GameMap) -> i32 {
+    let GameMap::TMX(record) = map else {
+        return 0;
+    };
+    let step = config::get_integer("scoring.author_time_step").unwrap_or(30000);
+    let max = config::get_integer("scoring.max_difficulty_points").unwrap_or(3);
+    let hard_tags = config::get_string("scoring.hard_tags").unwrap_or_default();
+
+    let time_points = if step > 0 {
+        (record.author_time as i64 / step).min(max)
+    } else {
+        0
+    };
+    let is_hard = record
+        .tags
+        .split(',')
+        .any(|tag| hard_tags.split(',').any(|hard| hard.trim() == tag));
+    (time_points + is_hard as i64).min(max) as i32
+}
+
+/// Extra points for the medal reached by the claim holding the tile.
+fn medal_points(medal: Medal) -> i32 {
+    match medal {
+        Medal::WR => 3,
+        Medal::Author => 2,
+        Medal::Gold => 1,
+        _ => 0,
+    }
+}
+
+/// Extra points for tiles that are part of more lines: one per diagonal crossing the tile.
+fn position_points(cell_id: usize, grid_size: usize) -> i32 {
+    let (row, column) = (cell_id / grid_size, cell_id % grid_size);
+    (row == column) as i32 + (row + column == grid_size - 1) as i32
+}
+
+/// The value of the tile at a board position for the team holding it.
+pub fn tile_value(cell: &GameCell, board_index: usize, grid_size: usize) -> i32 {
+    base_points()
+        + difficulty_points(&cell.map)
+        + cell
+            .leading_claim()
+            .map(|claim| medal_points(claim.medal))
+            .unwrap_or(0)
+        + position_points(board_index, grid_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::position_points;
+
+    #[test]
+    fn center_tile_is_on_both_diagonals() {
+        assert_eq!(position_points(12, 5), 2);
+        assert_eq!(position_points(0, 5), 1);
+        assert_eq!(position_points(4, 5), 1);
+        assert_eq!(position_points(7, 5), 0);
+        assert_eq!(position_points(5, 4), 1);
+    }
+}
@@ -18,14 +18,17 @@ pub struct TeamsManager<T: Team> {
 }
 
 impl<T: Team + From<BaseTeam>> TeamsManager<T> {
-    pub fn create_team_from_preset(&mut self, teams: &Vec<(String, Color)>) -> Option<&T> {
+    pub fn create_team_from_preset<R: Rng>(
+        &mut self,
+        teams: &[(String, Color)],
+        rng: &mut R,
+    ) -> Option<&T> {
         let team_count = self.teams.len();
         if team_count >= teams.len() {
             warn!("attempted to create more than {} teams", teams.len());
             return None;
         }
 
-        let mut rng = rand::thread_rng();
         let mut idx = rng.gen_range(0..teams.len());
         let mut retries = 100;
         while self.exists_with_name(&teams[idx].0) && retries > 0 {
@@ -1,6 +1,7 @@
 use palette::Srgb;
 
 pub mod base64;
+pub mod rng;
 pub mod roomcode;
 pub mod serialize;
 
@@ -0,0 +1,62 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// Random number generator driving all random decisions of a match. This is the generator behind
+/// `StdRng`, used directly so that its position in the sequence can be saved.
+pub type MatchRng = ChaCha12Rng;
+
+/// Generate a new random match seed.
+pub fn generate_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+/// Create a match RNG from a seed, so that the same seed always produces the same sequence.
+pub fn from_seed(seed: u64) -> MatchRng {
+    MatchRng::seed_from_u64(seed)
+}
+
+/// How far a match RNG went in its sequence, to resume it after a restart.
+pub fn position(rng: &MatchRng) -> u128 {
+    rng.get_word_pos()
+}
+
+/// Create a match RNG from a seed, continuing the sequence from a saved position.
+pub fn resume(seed: u64, position: u128) -> MatchRng {
+    let mut rng = from_seed(seed);
+    rng.set_word_pos(position);
+    rng
+}
+
+/// The seed set by the host, if any. Clients send 0 when no seed is set.
+pub fn configured_seed(seed: Option<u64>) -> Option<u64> {
+    seed.filter(|seed| *seed != 0)
+}
+
+/// Create a match RNG from an optional seed, falling back to a random one.
+pub fn from_optional_seed(seed: Option<u64>) -> MatchRng {
+    from_seed(configured_seed(seed).unwrap_or_else(generate_seed))
+}
+
+#[cfg(test)]
+mod test {
+    use rand::seq::SliceRandom;
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_shuffle() {
+        let mut a: Vec<u32> = (0..50).collect();
+        let mut b = a.clone();
+        a.shuffle(&mut from_seed(1234));
+        b.shuffle(&mut from_seed(1234));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn resumed_rng_continues_sequence() {
+        let mut rng = from_seed(1234);
+        rng.gen::<u64>();
+        let mut resumed = resume(1234, position(&rng));
+        assert_eq!(rng.gen::<u64>(), resumed.gen::<u64>());
+    }
+}
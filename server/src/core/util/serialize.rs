@@ -1,5 +1,5 @@
-use chrono::NaiveDateTime;
-use serde::{Serialize, Serializer};
+use chrono::{DateTime, NaiveDateTime};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 pub fn serialize_time<S: Serializer>(
     time: &NaiveDateTime,
@@ -8,6 +8,15 @@ pub fn serialize_time<S: Serializer>(
     serializer.serialize_i64(time.and_utc().timestamp())
 }
 
+pub fn deserialize_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<NaiveDateTime, D::Error> {
+    let timestamp = i64::deserialize(deserializer)?;
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.naive_utc())
+        .ok_or(D::Error::custom("timestamp out of range"))
+}
+
 pub fn serialize_or_default<S: Serializer, T: Default + Serialize>(
     opt: &Option<T>,
     serializer: S,
@@ -0,0 +1,468 @@
+// Bounds and rules that room and match configurations must follow
+
+use std::collections::HashSet;
+
+use chrono::Duration;
+use serde::Serialize;
+
+use crate::{
+    config,
+    core::models::team::Handicap,
+    datatypes::{MapMode, MatchConfiguration, RoomConfiguration},
+    server::mapload::{map_count, picked_cell, picked_tmxid, picked_uid},
+};
+
+/// A setting that was rejected, named by its path in the request.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Limits on configurations set by the server operator.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub min_grid_size: u32,
+    pub max_grid_size: u32,
+    /// Maximum number of players in a room, 0 for no limit.
+    pub max_room_size: u32,
+    pub max_inventory_size: u32,
+    pub max_map_sources: usize,
+    /// Maximum duration of a match, 0 for no limit.
+    pub max_match_duration: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            min_grid_size: 3,
+            max_grid_size: 8,
+            max_room_size: 0,
+            max_inventory_size: 5,
+            max_map_sources: 8,
+            max_match_duration: Duration::zero(),
+        }
+    }
+}
+
+impl Limits {
+    pub fn from_config() -> Self {
+        let default = Self::default();
+        let integer = |key: &str, default: u32| {
+            config::get_integer(key).map_or(default, |value| value.max(0) as u32)
+        };
+        Self {
+            min_grid_size: integer("limits.min_grid_size", default.min_grid_size),
+            max_grid_size: integer("limits.max_grid_size", default.max_grid_size),
+            max_room_size: integer("limits.max_room_size", default.max_room_size),
+            max_inventory_size: integer("limits.max_inventory_size", default.max_inventory_size),
+            max_map_sources: integer("limits.max_map_sources", default.max_map_sources as u32)
+                as usize,
+            max_match_duration: Duration::minutes(
+                config::get_integer("behaviour.max_match_duration").unwrap_or(0),
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Errors(Vec<FieldError>);
+
+impl Errors {
+    fn check(&mut self, valid: bool, field: &str, message: impl Into<String>) {
+        if !valid {
+            self.0.push(FieldError {
+                field: field.to_owned(),
+                message: message.into(),
+            });
+        }
+    }
+}
+
+/// Check room and match configurations against the limits of the server.
+pub fn validate(
+    config: &RoomConfiguration,
+    match_config: &MatchConfiguration,
+    limits: &Limits,
+) -> Result<(), Vec<FieldError>> {
+    let mut errors = Errors::default();
+    validate_room(&mut errors, config, limits);
+    validate_match(&mut errors, match_config, limits);
+    if errors.0.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.0)
+    }
+}
+
+/// Check a handicap against the board of the match it is given for.
+pub fn validate_handicap(
+    handicap: &Handicap,
+    match_config: &MatchConfiguration,
+) -> Result<(), Vec<FieldError>> {
+    let mut errors = Errors::default();
+    let cells = match_config.grid_size * match_config.grid_size;
+    errors.check(
+        handicap.head_start < cells / 2,
+        "handicap.head_start",
+        format!(
+            "A head start must be less than half of the {} cells of the board.",
+            cells
+        ),
+    );
+    if errors.0.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.0)
+    }
+}
+
+fn validate_room(errors: &mut Errors, config: &RoomConfiguration, limits: &Limits) {
+    errors.check(
+        config.name.chars().count() <= 64,
+        "config.name",
+        "The room name must be at most 64 characters long.",
+    );
+    if limits.max_room_size != 0 {
+        errors.check(
+            (1..=limits.max_room_size).contains(&config.size),
+            "config.size",
+            format!(
+                "Rooms are limited to {} players on this server.",
+                limits.max_room_size
+            ),
+        );
+    }
+}
+
+fn validate_match(errors: &mut Errors, config: &MatchConfiguration, limits: &Limits) {
+    errors.check(
+        (limits.min_grid_size..=limits.max_grid_size).contains(&config.grid_size),
+        "match_config.grid_size",
+        format!(
+            "The grid size must be between {} and {}.",
+            limits.min_grid_size, limits.max_grid_size
+        ),
+    );
+
+    errors.check(
+        config.time_limit >= Duration::zero(),
+        "match_config.time_limit",
+        "The time limit cannot be negative.",
+    );
+    errors.check(
+        config.no_bingo_duration >= Duration::zero(),
+        "match_config.no_bingo_duration",
+        "The no bingo duration cannot be negative.",
+    );
+    if !config.time_limit.is_zero() {
+        errors.check(
+            config.no_bingo_duration < config.time_limit,
+            "match_config.no_bingo_duration",
+            "The no bingo duration must be shorter than the time limit.",
+        );
+        errors.check(
+            limits.max_match_duration.is_zero() || config.time_limit <= limits.max_match_duration,
+            "match_config.time_limit",
+            format!(
+                "Matches are limited to {} minutes on this server.",
+                limits.max_match_duration.num_minutes()
+            ),
+        );
+    }
+
+    errors.check(
+        config.items_tick_multiplier > 0,
+        "match_config.items_tick_multiplier",
+        "The item spawn rate must be above zero.",
+    );
+    errors.check(
+        (1..=limits.max_inventory_size).contains(&config.inventory_size),
+        "match_config.inventory_size",
+        format!(
+            "The inventory size must be between 1 and {}.",
+            limits.max_inventory_size
+        ),
+    );
+    errors.check(
+        config.rally_length > 0,
+        "match_config.rally_length",
+        "The rally length must be above zero.",
+    );
+    errors.check(
+        config.jail_length > 0,
+        "match_config.jail_length",
+        "The jail length must be above zero.",
+    );
+
+    let filters = &config.filters;
+    if let (Some(min), Some(max)) = (filters.min_author_time, filters.max_author_time) {
+        errors.check(
+            min == 0 || max == 0 || min <= max,
+            "match_config.filters.max_author_time",
+            "The maximum author time must be above the minimum author time.",
+        );
+    }
+    if let (Some(after), Some(before)) = (filters.uploaded_after, filters.uploaded_before) {
+        errors.check(
+            after == 0 || before == 0 || after < before,
+            "match_config.filters.uploaded_before",
+            "Maps must be uploaded before the end of the upload period.",
+        );
+    }
+
+    match config.selection {
+        MapMode::Tags => errors.check(
+            config.map_tag.is_some_and(|tag| tag > 0),
+            "match_config.map_tag",
+            "A map tag must be selected.",
+        ),
+        MapMode::Mappack => errors.check(
+            config.mappack_id.is_some_and(|id| id > 0),
+            "match_config.mappack_id",
+            "A mappack must be selected.",
+        ),
+        MapMode::Campaign => validate_campaigns(errors, config),
+        MapMode::Mixed => validate_sources(errors, config, limits),
+        MapMode::Custom => validate_picks(errors, config),
+        _ => (),
+    }
+}
+
+fn validate_campaigns(errors: &mut Errors, config: &MatchConfiguration) {
+    errors.check(
+        config
+            .campaign_selection
+            .as_ref()
+            .is_some_and(|campaigns| !campaigns.is_empty()),
+        "match_config.campaign_selection",
+        "At least one campaign must be selected.",
+    );
+}
+
+/// Check the maps picked by the host against the size of the board. Maps beyond the board are
+/// kept as spares, up to as many maps as are loaded for other selection modes.
+fn validate_picks(errors: &mut Errors, config: &MatchConfiguration) {
+    let picks = &config.custom_maps;
+    let cells = (config.grid_size * config.grid_size) as usize;
+    let max_picks = map_count(config) as usize;
+    errors.check(
+        picks.len() >= cells,
+        "match_config.custom_maps",
+        format!(
+            "{} maps must be picked to fill a {}x{} board.",
+            cells, config.grid_size, config.grid_size
+        ),
+    );
+    errors.check(
+        picks.len() <= max_picks,
+        "match_config.custom_maps",
+        format!("At most {} maps can be picked for this match.", max_picks),
+    );
+
+    let mut used = HashSet::new();
+    for (i, pick) in picks.iter().enumerate() {
+        errors.check(
+            picked_uid(pick).is_some() || picked_tmxid(pick).is_some(),
+            &format!("match_config.custom_maps[{}]", i),
+            "A picked map must have a UID or a TMX id.",
+        );
+        if let Some(cell) = picked_cell(pick) {
+            let field = format!("match_config.custom_maps[{}].position", i);
+            errors.check(
+                cell < cells,
+                &field,
+                format!("There is no cell {} on the board.", cell + 1),
+            );
+            errors.check(
+                cell >= cells || used.insert(cell),
+                &field,
+                format!("Another map is already placed on cell {}.", cell + 1),
+            );
+        }
+    }
+}
+
+fn validate_sources(errors: &mut Errors, config: &MatchConfiguration, limits: &Limits) {
+    let sources = &config.map_sources;
+    errors.check(
+        !sources.is_empty(),
+        "match_config.map_sources",
+        "At least one map source must be selected.",
+    );
+    errors.check(
+        sources.len() <= limits.max_map_sources,
+        "match_config.map_sources",
+        format!(
+            "At most {} map sources can be mixed.",
+            limits.max_map_sources
+        ),
+    );
+    errors.check(
+        sources.is_empty() || sources.iter().any(|source| source.weight > 0),
+        "match_config.map_sources",
+        "At least one map source must have a share of the maps.",
+    );
+    for (i, source) in sources.iter().enumerate() {
+        let field = |name: &str| format!("match_config.map_sources[{}].{}", i, name);
+        match source.selection {
+            MapMode::Tags => errors.check(
+                source.map_tag.is_some_and(|tag| tag > 0),
+                &field("map_tag"),
+                "A map tag must be selected.",
+            ),
+            MapMode::Mappack => errors.check(
+                source.mappack_id.is_some_and(|id| id > 0),
+                &field("mappack_id"),
+                "A mappack must be selected.",
+            ),
+            MapMode::Campaign => validate_campaigns(errors, config),
+            MapMode::Mixed | MapMode::Custom => errors.check(
+                false,
+                &field("selection"),
+                "This map selection cannot be mixed with others.",
+            ),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datatypes::{CustomMap, MapSource};
+
+    fn fields(result: Result<(), Vec<FieldError>>) -> Vec<String> {
+        result
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn default_configuration_is_valid() {
+        let limits = Limits::default();
+        let config = RoomConfiguration::default();
+        assert!(validate(&config, &MatchConfiguration::default(), &limits).is_ok());
+    }
+
+    #[test]
+    fn invalid_fields_are_reported() {
+        let limits = Limits {
+            max_room_size: 16,
+            ..Default::default()
+        };
+        let config = RoomConfiguration {
+            size: 50,
+            ..Default::default()
+        };
+        let match_config = MatchConfiguration {
+            grid_size: 50,
+            items_tick_multiplier: 0,
+            selection: MapMode::Tags,
+            map_tag: None,
+            ..Default::default()
+        };
+        assert_eq!(
+            fields(validate(&config, &match_config, &limits)),
+            vec![
+                "config.size",
+                "match_config.grid_size",
+                "match_config.items_tick_multiplier",
+                "match_config.map_tag",
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_sources_are_checked() {
+        let match_config = MatchConfiguration {
+            selection: MapMode::Mixed,
+            map_sources: vec![
+                MapSource {
+                    selection: MapMode::Mappack,
+                    mappack_id: Some(0),
+                    ..Default::default()
+                },
+                MapSource {
+                    selection: MapMode::Mixed,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            fields(validate(
+                &RoomConfiguration::default(),
+                &match_config,
+                &Limits::default()
+            )),
+            vec![
+                "match_config.map_sources[0].mappack_id",
+                "match_config.map_sources[1].selection",
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_picks_are_checked_against_grid() {
+        let pick = |tmxid: u32, position: Option<u32>| CustomMap {
+            uid: Some(String::new()),
+            tmxid: Some(tmxid),
+            position,
+        };
+        let mut match_config = MatchConfiguration {
+            selection: MapMode::Custom,
+            grid_size: 3,
+            custom_maps: vec![pick(1, Some(1)), pick(2, Some(1)), pick(0, Some(10))],
+            rerolls: false,
+            ..Default::default()
+        };
+        let validate_picks = |match_config: &MatchConfiguration| {
+            validate(
+                &RoomConfiguration::default(),
+                match_config,
+                &Limits::default(),
+            )
+        };
+        assert_eq!(
+            fields(validate_picks(&match_config)),
+            vec![
+                "match_config.custom_maps",
+                "match_config.custom_maps[1].position",
+                "match_config.custom_maps[2]",
+                "match_config.custom_maps[2].position",
+            ]
+        );
+
+        match_config.custom_maps = (1..=9).map(|id| pick(id, None)).collect();
+        assert!(validate_picks(&match_config).is_ok());
+
+        match_config.custom_maps = (1..=10).map(|id| pick(id, None)).collect();
+        assert_eq!(
+            fields(validate_picks(&match_config)),
+            vec!["match_config.custom_maps"]
+        );
+
+        match_config.rerolls = true;
+        assert!(validate_picks(&match_config).is_ok());
+    }
+
+    #[test]
+    fn head_start_is_bounded_by_board() {
+        let match_config = MatchConfiguration {
+            grid_size: 5,
+            ..Default::default()
+        };
+        let head_start = |head_start| Handicap {
+            head_start,
+            ..Default::default()
+        };
+        assert!(validate_handicap(&head_start(11), &match_config).is_ok());
+        assert_eq!(
+            fields(validate_handicap(&head_start(12), &match_config)),
+            vec!["handicap.head_start"]
+        );
+    }
+}
@@ -93,6 +93,20 @@ pub struct MatchConfiguration {
 	pub rally_length: u32,
     #[derivative(Default(value = "600"))]
 	pub jail_length: u32,
+    #[derivative(Default(value = "1"))]
+	pub inventory_size: u32,
+    #[derivative(Default(value = "false"))]
+	pub shared_inventory: bool,
+    pub seed: Option<u64>,
+    #[derivative(Default(value = "PracticeGoal::None"))]
+	pub practice_goal: PracticeGoal,
+    #[derivative(Default(value = "false"))]
+	pub medal_ladder: bool,
+    pub filters: MapFilters,
+    #[derivative(Default(value = "false"))]
+	pub avoid_recent_maps: bool,
+    pub map_sources: Vec<MapSource>,
+    pub custom_maps: Vec<CustomMap>,
 }
 
 /* Item drawing probabilities for configuring the Frenzy gamemode. */
@@ -114,6 +128,44 @@ pub struct FrenzyItemSettings {
 	pub golden_dice: u32,
 }
 
+/* One of the map selections mixed together on a board, with its share of the maps. */
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, Derivative, PartialEq, Eq)]
+#[derivative(Default)]
+pub struct MapSource {
+    #[derivative(Default(value = "MapMode::RandomTMX"))]
+	pub selection: MapMode,
+    #[derivative(Default(value = "1"))]
+	pub weight: u32,
+    pub mappack_id: Option<u32>,
+    pub map_tag: Option<i32>,
+}
+
+/* A map picked by the host for a custom board, by UID or TMX id, optionally placed on a cell numbered from 1. */
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, Derivative, PartialEq, Eq)]
+#[derivative(Default)]
+pub struct CustomMap {
+    pub uid: Option<String>,
+    pub tmxid: Option<u32>,
+    pub position: Option<u32>,
+}
+
+/* Restrictions on the maps drawn from the map cache. */
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, Derivative, PartialEq, Eq)]
+#[derivative(Default)]
+pub struct MapFilters {
+    pub min_author_time: Option<u32>,
+    pub max_author_time: Option<u32>,
+    pub include_tags: Vec<i32>,
+    pub exclude_tags: Vec<i32>,
+    pub style: Option<String>,
+    pub uploaded_after: Option<u64>,
+    pub uploaded_before: Option<u64>,
+    pub excluded_authors: Vec<String>,
+}
+
 /* Request to open a connection by the client using an exisiting token. */
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone, Derivative, PartialEq, Eq)]
@@ -199,6 +251,9 @@ pub enum MapMode {
     Tags,
     Mappack,
     Campaign,
+    Mixed,
+    Local,
+    Custom,
 }
 
 /* A Trackmania medal ranking. */
@@ -221,6 +276,7 @@ pub enum Gamemode {
     #[default]
     Standard,
     Frenzy,
+    Points,
 }
 
 /* When a connection to the server fails, give the client a hint of what it should do. */
@@ -246,3 +302,14 @@ pub enum Powerup {
     RainbowTile,
     GoldenDice,
 }
+
+/* The objective of a solo practice match. */
+#[derive(Serialize_repr, Deserialize_repr, Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[repr(u8)]
+pub enum PracticeGoal {
+    #[default]
+    None,
+    Bingo,
+    Blackout,
+    TimeAttack,
+}
@@ -21,6 +21,7 @@ pub struct MatchEndEffect {
     pub teams: Vec<NetworkGameTeam>,
     pub started: DateTime<Utc>,
     pub ended: DateTime<Utc>,
+    pub seed: u64,
 }
 
 impl HooksClient {
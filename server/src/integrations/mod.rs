@@ -8,6 +8,7 @@ use once_cell::sync::Lazy;
 
 pub mod hooks;
 pub mod openplanet;
+pub mod ratelimit;
 pub mod tmexchange;
 pub mod tmio;
 pub mod webservices;
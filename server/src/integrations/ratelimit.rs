@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+/// Spaces out the requests made to an external service, shared between all of its callers.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until the next request is allowed to be sent.
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock();
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_are_spaced_by_interval() {
+        let limiter = RateLimiter::new(Duration::from_millis(40));
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(120));
+    }
+}
@@ -0,0 +1,341 @@
+// Fill the mapcache with maps from TrackmaniaExchange
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Client, Url,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config,
+    integrations::{ratelimit::RateLimiter, USER_AGENT},
+    orm::mapcache::{ingestion, record::MapRecord},
+};
+
+use super::{models::MapsResponse, BASE};
+
+const ROUTE_MAPS: &str = "/api/maps";
+const FIELDS: &str = "MapId,MapUid,OnlineMapId,Authors[],Name,GbxMapName,Medals.Author,Medals.Gold,Medals.Silver,Medals.Bronze,Vehicle,UploadedAt,UpdatedAt,MapType,Tags[]";
+
+/// Progress of the ingestion task since the server started.
+#[derive(Serialize, Clone, Default)]
+pub struct IngestionMetrics {
+    pub pages: u64,
+    pub maps_saved: u64,
+    pub maps_skipped: u64,
+    pub errors: u64,
+    pub passes: u64,
+    pub cursor: Option<i32>,
+    pub high_water_mark: Option<i32>,
+    pub last_page_at: Option<DateTime<Utc>>,
+}
+
+static METRICS: Lazy<Mutex<IngestionMetrics>> = Lazy::new(Default::default);
+
+/// Spaces out every request sent to TMX, from the ingestion task as well as from map loading and
+/// health checks.
+static LIMITER: Lazy<RateLimiter> = Lazy::new(|| {
+    RateLimiter::new(Duration::from_secs(
+        config::get_integer("network.tmx_request_interval").unwrap_or(10) as u64,
+    ))
+});
+
+pub fn metrics() -> IngestionMetrics {
+    METRICS.lock().clone()
+}
+
+/// Maps of one page of results, with the id to request the next page from.
+pub struct MapsPage {
+    pub maps: Vec<MapRecord>,
+    pub skipped: usize,
+    pub newest_id: Option<i32>,
+    pub next_id: Option<i32>,
+}
+
+impl MapsPage {
+    /// Keep the maps newer than the given TMX id. Returns whether the page reached that id,
+    /// in which case there are no newer maps left on the next pages.
+    pub fn retain_newer_than(&mut self, tmxid: i32) -> bool {
+        self.maps.retain(|map| map.tmxid > tmxid);
+        self.next_id.is_none_or(|next_id| next_id <= tmxid)
+    }
+}
+
+impl From<MapsResponse> for MapsPage {
+    fn from(response: MapsResponse) -> Self {
+        // paginate on the raw results so that a page of skipped maps still advances the cursor
+        let next_id = if response.more {
+            response.results.last().map(|map| map.map_id)
+        } else {
+            None
+        };
+        let newest_id = response.results.first().map(|map| map.map_id);
+        let total = response.results.len();
+        let maps: Vec<MapRecord> = response
+            .results
+            .into_iter()
+            .filter(|map| {
+                map.map_type.as_deref() == Some("TM_Race") && map.vehicle.unwrap_or(1) == 1
+            })
+            .filter_map(|map| {
+                MapRecord::try_from(map)
+                    .map_err(|e| warn!("invalid map ingested: {}", e))
+                    .ok()
+            })
+            .collect();
+        Self {
+            skipped: total - maps.len(),
+            maps,
+            newest_id,
+            next_id,
+        }
+    }
+}
+
+pub struct MapIngestion {
+    client: Client,
+    base: String,
+    page_size: u32,
+}
+
+impl MapIngestion {
+    pub fn new(base: &str, page_size: u32) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert("user-agent", HeaderValue::from_static(&USER_AGENT));
+        Self {
+            client: Client::builder()
+                .default_headers(headers)
+                .build()
+                .expect("client should be built"),
+            base: base.to_owned(),
+            page_size,
+        }
+    }
+
+    /// Request the page of maps uploaded before the given map id, or the latest maps if none.
+    pub async fn fetch_page(&self, after_id: Option<i32>) -> Result<MapsPage, anyhow::Error> {
+        let query_extra = after_id
+            .map(|id| format!("&after={}", id))
+            .unwrap_or_default();
+        self.request_maps(&format!("count={}{}", self.page_size, query_extra))
+            .await
+    }
+
+    /// Get maps by TMX id and by UID. Maps that can't be played in bingo are left out.
+    pub async fn get_maps(
+        &self,
+        ids: &[i32],
+        uids: &[String],
+    ) -> Result<Vec<MapRecord>, anyhow::Error> {
+        let mut maps = Vec::new();
+        if !ids.is_empty() {
+            let id_list: Vec<String> = ids.iter().map(i32::to_string).collect();
+            let query = format!("count={}&id={}", ids.len(), id_list.join(","));
+            maps.extend(self.request_maps(&query).await?.maps);
+        }
+        for uid in uids {
+            maps.extend(
+                self.request_maps(&format!("count=1&uid={}", uid))
+                    .await?
+                    .maps,
+            );
+        }
+        Ok(maps)
+    }
+
+    async fn request_maps(&self, query: &str) -> Result<MapsPage, anyhow::Error> {
+        LIMITER.acquire().await;
+        let url = Url::from_str(&format!(
+            "{}{}?fields={}&{}",
+            self.base, ROUTE_MAPS, FIELDS, query
+        ))?;
+        debug!("requesting TMX maps: {}", url);
+
+        let response: MapsResponse = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.into())
+    }
+
+    /// Get which of the given maps still exist on TMX.
+    pub async fn existing_maps(&self, ids: &[i32]) -> Result<Vec<i32>, anyhow::Error> {
+        let id_list: Vec<String> = ids.iter().map(i32::to_string).collect();
+        LIMITER.acquire().await;
+        let url = Url::from_str(&format!(
+            "{}{}?fields=MapId&count={}&id={}",
+            self.base,
+            ROUTE_MAPS,
+            ids.len(),
+            id_list.join(",")
+        ))?;
+        debug!("requesting TMX maps: {}", url);
+
+        let response: Value = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let results = response
+            .get("Results")
+            .and_then(Value::as_array)
+            .ok_or(anyhow!("missing results in TMX maps response"))?;
+        Ok(results
+            .iter()
+            .filter_map(|map| map.get("MapId").and_then(Value::as_i64))
+            .map(|id| id as i32)
+            .collect())
+    }
+
+    /// Fetch the maps uploaded since the newest map ingested, page by page until reaching it,
+    /// then move the high-water mark to the newest map.
+    async fn ingest_new_maps(&self) -> Result<(), anyhow::Error> {
+        let high_water_mark = ingestion::get_high_water_mark().await?;
+        let mut newest_id = None;
+        let mut after_id = None;
+        loop {
+            let mut page = self.fetch_page(after_id).await?;
+            newest_id = newest_id.or(page.newest_id);
+            // on the first run, the backfill takes care of the maps older than the first page
+            let complete = high_water_mark.is_none_or(|mark| page.retain_newer_than(mark));
+            self.save_page(&page).await?;
+            if complete {
+                break;
+            }
+            after_id = page.next_id;
+        }
+
+        if let Some(newest_id) = newest_id.filter(|id| high_water_mark < Some(*id)) {
+            ingestion::set_high_water_mark(newest_id).await?;
+            debug!("map ingestion caught up to #{}", newest_id);
+            METRICS.lock().high_water_mark = Some(newest_id);
+        }
+        Ok(())
+    }
+
+    /// Fetch the next page of older maps, save it to the mapcache and move the cursor forward.
+    async fn ingest_backfill_page(&self) -> Result<(), anyhow::Error> {
+        let cursor = ingestion::get_cursor().await?;
+        let page = self.fetch_page(cursor).await?;
+        self.save_page(&page).await?;
+        ingestion::set_cursor(page.next_id).await?;
+
+        let mut metrics = METRICS.lock();
+        metrics.cursor = page.next_id;
+        if page.next_id.is_none() {
+            metrics.passes += 1;
+            info!(
+                "map ingestion pass complete: {} maps saved over {} pages",
+                metrics.maps_saved, metrics.pages
+            );
+        } else if metrics.pages.is_multiple_of(100) {
+            info!(
+                "map ingestion progress: {} maps saved, {} skipped, resuming after #{}",
+                metrics.maps_saved,
+                metrics.maps_skipped,
+                page.next_id.unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
+    async fn save_page(&self, page: &MapsPage) -> Result<(), anyhow::Error> {
+        ingestion::save_maps(page.maps.clone()).await?;
+
+        let mut metrics = METRICS.lock();
+        metrics.pages += 1;
+        metrics.maps_saved += page.maps.len() as u64;
+        metrics.maps_skipped += page.skipped as u64;
+        metrics.last_page_at = Some(Utc::now());
+        Ok(())
+    }
+}
+
+/// Continuously page through the maps on TMX and keep the mapcache up to date.
+/// Every cycle first ingests the maps uploaded since the newest one in the mapcache, then
+/// continues the backfill of older maps, which starts over once all maps have been visited.
+pub fn start_ingestion_task() {
+    if !config::get_boolean("network.tmx_ingestion").unwrap_or(false) {
+        return;
+    }
+    let ingestion = MapIngestion::new(
+        BASE,
+        config::get_integer("network.tmx_page_size").unwrap_or(100) as u32,
+    );
+    info!("starting TMX map ingestion");
+
+    tokio::spawn(async move {
+        loop {
+            let result = match ingestion.ingest_new_maps().await {
+                Ok(()) => ingestion.ingest_backfill_page().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error!("map ingestion failed: {}", e);
+                METRICS.lock().errors += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = include_str!("../../../tests/fixtures/tmx_maps_page.json");
+
+    #[test]
+    fn page_keeps_stadium_race_maps() {
+        let response: MapsResponse = serde_json::from_str(PAGE).unwrap();
+        let page = MapsPage::from(response);
+
+        let ids: Vec<i32> = page.maps.iter().map(|map| map.tmxid).collect();
+        assert_eq!(ids, vec![184512, 184501]);
+        assert_eq!(page.skipped, 2);
+        assert_eq!(page.newest_id, Some(184512));
+        assert_eq!(page.next_id, Some(184501));
+
+        let map = &page.maps[0];
+        assert_eq!(map.username, "Larstm");
+        assert_eq!(map.tags, "3,37");
+        assert_eq!(map.style.as_deref(), Some("Tech"));
+        assert_eq!(map.author_time, 41237);
+        assert_eq!(map.wr_time, None);
+    }
+
+    #[test]
+    fn last_page_resets_cursor() {
+        let mut response: MapsResponse = serde_json::from_str(PAGE).unwrap();
+        response.more = false;
+        assert_eq!(MapsPage::from(response).next_id, None);
+    }
+
+    #[test]
+    fn catch_up_stops_at_high_water_mark() {
+        let response: MapsResponse = serde_json::from_str(PAGE).unwrap();
+        let mut page = MapsPage::from(response);
+        assert!(!page.retain_newer_than(184400));
+        assert_eq!(page.maps.len(), 2);
+
+        let response: MapsResponse = serde_json::from_str(PAGE).unwrap();
+        let mut page = MapsPage::from(response);
+        assert!(page.retain_newer_than(184501));
+        let ids: Vec<i32> = page.maps.iter().map(|map| map.tmxid).collect();
+        assert_eq!(ids, vec![184512]);
+    }
+}
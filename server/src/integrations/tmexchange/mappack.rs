@@ -4,8 +4,8 @@ use std::str::FromStr;
 
 use anyhow::anyhow;
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Client, Url,
+    header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Client, StatusCode, Url,
 };
 use tracing::{debug, error};
 
@@ -48,6 +48,7 @@ impl TryFrom<MapResponse> for MapRecord {
             track_name: value.name,
             gbx_name: value.gbx_map_name,
             wr_time,
+            wr_fetched_at: None,
             author_time: value.medals.author,
             gold_time: value.medals.gold,
             silver_time: value.medals.silver,
@@ -56,10 +57,28 @@ impl TryFrom<MapResponse> for MapRecord {
             updated_at: value.updated_at,
             tags,
             style: style_name,
+            download_url: None,
         })
     }
 }
 
+/// HTTP validators of a mappack response, sent back so that TMX only gives the maps of a
+/// mappack again when it changed.
+#[derive(Debug, Clone, Default)]
+pub struct MappackValidator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// The result of a conditional mappack request.
+pub enum MappackFetch {
+    NotModified,
+    Updated {
+        maps: Vec<GameMap>,
+        validator: MappackValidator,
+    },
+}
+
 pub struct MappackLoader {
     client: Client,
 }
@@ -76,24 +95,55 @@ impl MappackLoader {
         }
     }
 
+    /// Get the maps of a mappack unless they did not change since the response the validator
+    /// was taken from.
+    /// The validators of a response only cover its own page, so they are only kept for mappacks
+    /// that fit on a single page. Larger mappacks are fetched again in full every time.
     pub async fn get_mappack_tracks(
         &self,
         mappack_id: &str,
-    ) -> Result<Vec<GameMap>, anyhow::Error> {
-        let mut mappack_tracks = Vec::new();
-
-        let (mut page_results, mut pagination_next_id) =
-            self.paged_mappack_request_tracks(mappack_id, None).await?;
-        mappack_tracks.append(&mut page_results);
+        validator: &MappackValidator,
+    ) -> Result<MappackFetch, anyhow::Error> {
+        let mut request = self.client.get(mappack_url(mappack_id, None)?);
+        if let Some(etag) = &validator.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validator.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(MappackFetch::NotModified);
+        }
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let mut validator = MappackValidator {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
 
+        let (mut mappack_tracks, mut pagination_next_id) =
+            parse_mappack_page(response.error_for_status()?.json().await?);
+        if pagination_next_id.is_some() {
+            validator = MappackValidator::default();
+        }
         while pagination_next_id.is_some() {
+            let mut page_results;
             (page_results, pagination_next_id) = self
                 .paged_mappack_request_tracks(mappack_id, pagination_next_id)
                 .await?;
             mappack_tracks.append(&mut page_results);
         }
 
-        Ok(mappack_tracks)
+        Ok(MappackFetch::Updated {
+            maps: mappack_tracks,
+            validator,
+        })
     }
 
     async fn paged_mappack_request_tracks(
@@ -101,14 +151,7 @@ impl MappackLoader {
         mappack_id: &str,
         after_id: Option<i32>,
     ) -> Result<(Vec<GameMap>, Option<i32>), anyhow::Error> {
-        let query_extra = after_id
-            .map(|map_uid| format!("&after={}", map_uid))
-            .unwrap_or_default();
-        let url = Url::from_str(
-            &format!("{}{}?mappackid={}{}&count=100&fields=MapId,MapUid,OnlineMapId,Name,GbxMapName,Authors[],UploadedAt,UpdatedAt,Medals.Author,Medals.Gold,Medals.Silver,Medals.Bronze,Tags[],OnlineWR", BASE, ROUTE_MAPPACK, mappack_id, query_extra)
-        )?;
-        debug!("requesting TMX tracks: {}", url);
-
+        let url = mappack_url(mappack_id, after_id)?;
         let response: MapsResponse = self
             .client
             .get(url)
@@ -117,26 +160,40 @@ impl MappackLoader {
             .error_for_status()?
             .json()
             .await?;
+        Ok(parse_mappack_page(response))
+    }
+}
 
-        let more = response.more;
-        let maps: Vec<MapRecord> = response
-            .results
-            .into_iter()
-            .map(MapRecord::try_from)
-            .filter(|m| {
-                if let Err(e) = m {
-                    error!("invalid map loaded: {}", e);
-                }
-                m.is_ok()
-            })
-            .map(Result::unwrap) // Safety: Err has been filtered out above
-            .collect();
+fn mappack_url(mappack_id: &str, after_id: Option<i32>) -> Result<Url, anyhow::Error> {
+    let query_extra = after_id
+        .map(|map_uid| format!("&after={}", map_uid))
+        .unwrap_or_default();
+    let url = Url::from_str(
+        &format!("{}{}?mappackid={}{}&count=100&fields=MapId,MapUid,OnlineMapId,Name,GbxMapName,Authors[],UploadedAt,UpdatedAt,Medals.Author,Medals.Gold,Medals.Silver,Medals.Bronze,Tags[],OnlineWR", BASE, ROUTE_MAPPACK, mappack_id, query_extra)
+    )?;
+    debug!("requesting TMX tracks: {}", url);
+    Ok(url)
+}
 
-        let next_id = if more {
-            maps.last().map(|map| map.tmxid)
-        } else {
-            None
-        };
-        Ok((maps.into_iter().map(GameMap::TMX).collect(), next_id))
-    }
+fn parse_mappack_page(response: MapsResponse) -> (Vec<GameMap>, Option<i32>) {
+    let more = response.more;
+    let maps: Vec<MapRecord> = response
+        .results
+        .into_iter()
+        .map(MapRecord::try_from)
+        .filter(|m| {
+            if let Err(e) = m {
+                error!("invalid map loaded: {}", e);
+            }
+            m.is_ok()
+        })
+        .map(Result::unwrap) // Safety: Err has been filtered out above
+        .collect();
+
+    let next_id = if more {
+        maps.last().map(|map| map.tmxid)
+    } else {
+        None
+    };
+    (maps.into_iter().map(GameMap::TMX).collect(), next_id)
 }
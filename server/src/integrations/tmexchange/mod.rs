@@ -1,5 +1,6 @@
+pub mod ingestion;
 mod mappack;
 mod models;
 pub use mappack::*;
 
-const BASE: &'static str = "https://trackmania.exchange";
+pub const BASE: &str = "https://trackmania.exchange";
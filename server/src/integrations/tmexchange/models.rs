@@ -23,6 +23,8 @@ pub struct MapResponse {
     pub tags: Vec<TagModel>,
     #[serde(rename = "OnlineWR")]
     pub online_wr: Option<RecordModel>,
+    pub map_type: Option<String>,
+    pub vehicle: Option<i32>,
 }
 
 #[derive(Deserialize)]
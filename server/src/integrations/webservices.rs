@@ -1,27 +1,44 @@
+use std::time::Duration as StdDuration;
+
 use chrono::{DateTime, Duration, Utc};
 use parking_lot::RwLock;
-use reqwest::{Client, RequestBuilder, Response};
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use serde_with::{serde_as, TimestampSeconds};
 use thiserror::Error;
+use tokio::time::sleep;
+use tracing::warn;
+
+use super::ratelimit::RateLimiter;
 
 const NADEO_CORE_AUDIENCE: &'static str = "NadeoServices";
 const NADEO_LIVE_AUDIENCE: &'static str = "NadeoLiveServices";
 
-const ENDPOINT_AUTHENTICATE_TOKEN: &'static str =
-    "https://prod.trackmania.core.nadeo.online/v2/authentication/token/basic";
-const CORE_ENDPOINT_MAP_RECORDS_BY_ACCOUNT: &'static str =
-    "https://prod.trackmania.core.nadeo.online/v2/mapRecords/by-account";
-const LIVE_ENDPOINT_MAP_LEADERBOARDS: &'static str =
-    "https://live-services.trackmania.nadeo.live/api/token/leaderboard/group/Personal_Best/map/";
+const CORE_BASE: &str = "https://prod.trackmania.core.nadeo.online";
+const LIVE_BASE: &str = "https://live-services.trackmania.nadeo.live";
+
+const ENDPOINT_AUTHENTICATE_TOKEN: &str = "/v2/authentication/token/basic";
+const ENDPOINT_REFRESH_TOKEN: &str = "/v2/authentication/token/refresh";
+const CORE_ENDPOINT_MAP_RECORDS_BY_ACCOUNT: &str = "/v2/mapRecords/by-account";
+const CORE_ENDPOINT_MAPS: &str = "/maps/";
+const LIVE_ENDPOINT_MAP_LEADERBOARDS: &str = "/api/token/leaderboard/group/Personal_Best/map/";
+
+// Access tokens last an hour, renew them a bit earlier to avoid using an expired one.
+const TOKEN_LIFETIME_MINUTES: i64 = 50;
 
 pub struct NadeoWebserivcesClient {
     client: Client,
     username: String,
     password: String,
+    core_base: String,
+    live_base: String,
     core_credentials: RwLock<Option<WebservicesCredentialsInfo>>,
     live_credentials: RwLock<Option<WebservicesCredentialsInfo>>,
+    refresh_lock: tokio::sync::Mutex<()>,
+    limiter: RateLimiter,
+    max_retries: u32,
+    retry_backoff: StdDuration,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -64,6 +81,13 @@ pub struct WebservicesRecordScore {
     pub time: i32,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebservicesMapInfo {
+    pub map_id: String,
+    pub map_uid: String,
+}
+
 #[serde_as]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -81,10 +105,20 @@ pub struct WebservicesLeaderboardEntry {
 pub enum WebservicesError {
     #[error("invalid credentials for audience '{0:?}'")]
     NoCredientials(NadeoAudience),
+    #[error("authentication failed: {0}")]
+    Authentication(Box<WebservicesError>),
+    #[error("request is not authorized")]
+    Unauthorized,
+    #[error("rate limited by the server")]
+    RateLimited,
+    #[error("unexpected response status {0}")]
+    Status(StatusCode),
     #[error(transparent)]
     Reqwest(reqwest::Error),
     #[error("unexpected response format")]
     InvalidResponse,
+    #[error("request body cannot be sent more than once")]
+    UnclonableRequest,
     #[error(transparent)]
     SerdeJson(serde_json::Error),
 }
@@ -95,24 +129,55 @@ impl NadeoWebserivcesClient {
             client,
             username,
             password,
+            core_base: CORE_BASE.to_owned(),
+            live_base: LIVE_BASE.to_owned(),
             core_credentials: RwLock::new(None),
             live_credentials: RwLock::new(None),
+            refresh_lock: tokio::sync::Mutex::new(()),
+            limiter: RateLimiter::new(StdDuration::from_millis(500)),
+            max_retries: 3,
+            retry_backoff: StdDuration::from_secs(1),
         }
     }
 
-    async fn prepare_request(&self, audience: NadeoAudience) {
-        let credentials = match audience {
+    /// Send requests to other servers than the official ones.
+    pub fn with_base_urls(mut self, core_base: &str, live_base: &str) -> Self {
+        self.core_base = core_base.to_owned();
+        self.live_base = live_base.to_owned();
+        self
+    }
+
+    /// Set the minimum delay between two requests.
+    pub fn with_rate_limit(mut self, interval: StdDuration) -> Self {
+        self.limiter = RateLimiter::new(interval);
+        self
+    }
+
+    /// Set how many times a request is retried on a server error or rate limit, waiting
+    /// twice as long as the previous time between each attempt.
+    pub fn with_retries(mut self, max_retries: u32, backoff: StdDuration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    fn credentials(&self, audience: NadeoAudience) -> &RwLock<Option<WebservicesCredentialsInfo>> {
+        match audience {
             NadeoAudience::Core => &self.core_credentials,
             NadeoAudience::Live => &self.live_credentials,
-        };
+        }
+    }
 
-        let has_valid_credentials = credentials
+    async fn prepare_request(&self, audience: NadeoAudience) -> Result<(), WebservicesError> {
+        let has_valid_credentials = self
+            .credentials(audience)
             .read()
             .as_ref()
             .is_some_and(|c| c.expiration > Utc::now());
         if !has_valid_credentials {
-            self.refresh(audience).await;
+            self.refresh(audience).await?;
         }
+        Ok(())
     }
 
     async fn send_request(
@@ -120,20 +185,52 @@ impl NadeoWebserivcesClient {
         request: RequestBuilder,
         audience: NadeoAudience,
     ) -> Result<Response, WebservicesError> {
-        self.prepare_request(audience).await;
-        let Some(token) = self.get_request_token(audience) else {
-            return Err(WebservicesError::NoCredientials(audience));
-        };
+        let mut attempt = 0;
+        let mut refreshed = false;
+        loop {
+            self.prepare_request(audience).await?;
+            let Some(token) = self.get_request_token(audience) else {
+                return Err(WebservicesError::NoCredientials(audience));
+            };
+            let authenticated = request
+                .try_clone()
+                .ok_or(WebservicesError::UnclonableRequest)?
+                .header(header::AUTHORIZATION, format!("nadeo_v1 t={}", token));
 
-        // TODO: handle expired tokens
-        let authenticated = request.header(
-            reqwest::header::AUTHORIZATION,
-            format!("nadeo_v1 t={}", token),
-        );
-        authenticated
-            .send()
-            .await
-            .map_err(WebservicesError::Reqwest)
+            self.limiter.acquire().await;
+            let response = authenticated
+                .send()
+                .await
+                .map_err(WebservicesError::Reqwest)?;
+            let status = response.status();
+
+            if status == StatusCode::UNAUTHORIZED && !refreshed {
+                // the token was revoked or expired earlier than expected
+                refreshed = true;
+                self.refresh(audience).await?;
+                continue;
+            }
+            if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+                && attempt < self.max_retries
+            {
+                let delay =
+                    retry_after(&response).unwrap_or(self.retry_backoff * 2u32.pow(attempt));
+                attempt += 1;
+                warn!(
+                    "webservices request failed with status {}, retrying in {:?} ({}/{})",
+                    status, delay, attempt, self.max_retries
+                );
+                sleep(delay).await;
+                continue;
+            }
+
+            return match status {
+                status if status.is_success() => Ok(response),
+                StatusCode::UNAUTHORIZED => Err(WebservicesError::Unauthorized),
+                StatusCode::TOO_MANY_REQUESTS => Err(WebservicesError::RateLimited),
+                status => Err(WebservicesError::Status(status)),
+            };
+        }
     }
 
     fn get_request_token(&self, audience: NadeoAudience) -> Option<String> {
@@ -147,61 +244,90 @@ impl NadeoWebserivcesClient {
         token.cloned()
     }
 
-    async fn refresh_credentials(
-        &self,
-        audience: &str,
-        credentials: &RwLock<Option<WebservicesCredentialsInfo>>,
-    ) {
-        match self
-            .get_audience_token(audience, &self.username, &self.password)
-            .await
-        {
-            Ok(token) => {
-                let mut writer = credentials.write();
-                *writer = Some(WebservicesCredentialsInfo {
-                    token,
-                    expiration: Utc::now() + Duration::minutes(30),
-                });
-            }
-            Err(e) => tracing::error!(
-                "failed to refresh credentials for audience '{}': {}",
-                audience,
-                e
-            ),
+    /// Renew the credentials of an audience, using its refresh token if there is one
+    /// and authenticating with the account again otherwise.
+    pub async fn refresh(&self, audience: NadeoAudience) -> Result<(), WebservicesError> {
+        let seen_token = self.get_request_token(audience);
+        let _guard = self.refresh_lock.lock().await;
+        let credentials = self.credentials(audience);
+        // another task may have renewed the credentials while this one was waiting
+        let renewed = credentials.read().as_ref().is_some_and(|c| {
+            c.expiration > Utc::now() && Some(&c.token.access_token) != seen_token.as_ref()
+        });
+        if renewed {
+            return Ok(());
         }
-    }
+        let refresh_token = credentials
+            .read()
+            .as_ref()
+            .map(|c| c.token.refresh_token.clone());
 
-    pub async fn refresh(&self, audience: NadeoAudience) {
-        match audience {
-            NadeoAudience::Core => {
-                self.refresh_credentials(NADEO_CORE_AUDIENCE, &self.core_credentials)
-                    .await
-            }
-            NadeoAudience::Live => {
-                self.refresh_credentials(NADEO_LIVE_AUDIENCE, &self.live_credentials)
-                    .await
-            }
+        let token = match refresh_token {
+            Some(refresh_token) => match self.refresh_token(&refresh_token).await {
+                Ok(token) => Ok(token),
+                Err(e) => {
+                    warn!("could not use refresh token for audience '{audience:?}': {e}");
+                    self.get_audience_token(audience).await
+                }
+            },
+            None => self.get_audience_token(audience).await,
         }
+        .map_err(|e| WebservicesError::Authentication(Box::new(e)))?;
+
+        *credentials.write() = Some(WebservicesCredentialsInfo {
+            token,
+            expiration: Utc::now() + Duration::minutes(TOKEN_LIFETIME_MINUTES),
+        });
+        Ok(())
     }
 
     async fn get_audience_token(
         &self,
-        audience: &str,
-        username: &str,
-        password: &str,
-    ) -> Result<WebserivcesToken, reqwest::Error> {
+        audience: NadeoAudience,
+    ) -> Result<WebserivcesToken, WebservicesError> {
         let body = json!({
-            "audience": audience
+            "audience": match audience {
+                NadeoAudience::Core => NADEO_CORE_AUDIENCE,
+                NadeoAudience::Live => NADEO_LIVE_AUDIENCE,
+            }
         });
 
         let request = self
             .client
-            .post(ENDPOINT_AUTHENTICATE_TOKEN)
-            .basic_auth(username, Some(password))
+            .post(format!("{}{}", self.core_base, ENDPOINT_AUTHENTICATE_TOKEN))
+            .basic_auth(&self.username, Some(&self.password))
             .json(&body);
-        let response: WebserivcesToken = request.send().await?.error_for_status()?.json().await?;
+        self.send_token_request(request).await
+    }
 
-        Ok(response)
+    async fn refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<WebserivcesToken, WebservicesError> {
+        let request = self
+            .client
+            .post(format!("{}{}", self.core_base, ENDPOINT_REFRESH_TOKEN))
+            .header(
+                header::AUTHORIZATION,
+                format!("nadeo_v1 t={}", refresh_token),
+            );
+        self.send_token_request(request).await
+    }
+
+    async fn send_token_request(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<WebserivcesToken, WebservicesError> {
+        self.limiter.acquire().await;
+        let response = request.send().await.map_err(WebservicesError::Reqwest)?;
+        match response.status() {
+            status if status.is_success() => {
+                response.json().await.map_err(WebservicesError::Reqwest)
+            }
+            StatusCode::UNAUTHORIZED => Err(WebservicesError::Unauthorized),
+            StatusCode::TOO_MANY_REQUESTS => Err(WebservicesError::RateLimited),
+            status => Err(WebservicesError::Status(status)),
+        }
     }
 
     pub async fn core_get_map_records(
@@ -211,7 +337,10 @@ impl NadeoWebserivcesClient {
     ) -> Result<Vec<WebserivcesMapRecord>, WebservicesError> {
         let request = self
             .client
-            .get(CORE_ENDPOINT_MAP_RECORDS_BY_ACCOUNT)
+            .get(format!(
+                "{}{}",
+                self.core_base, CORE_ENDPOINT_MAP_RECORDS_BY_ACCOUNT
+            ))
             .query(&[("accountIdList", account_ids.join(","))])
             .query(&[("mapId", map_id)]);
         let response: Vec<WebserivcesMapRecord> = self
@@ -224,13 +353,30 @@ impl NadeoWebserivcesClient {
         Ok(response)
     }
 
+    /// Get the maps uploaded to Nadeo services among the given UIDs.
+    pub async fn core_get_maps(
+        &self,
+        map_uids: &[String],
+    ) -> Result<Vec<WebservicesMapInfo>, WebservicesError> {
+        let request = self
+            .client
+            .get(format!("{}{}", self.core_base, CORE_ENDPOINT_MAPS))
+            .query(&[("mapUidList", map_uids.join(","))]);
+        self.send_request(request, NadeoAudience::Core)
+            .await?
+            .json()
+            .await
+            .map_err(WebservicesError::Reqwest)
+    }
+
     pub async fn live_get_map_leaderboard(
         &self,
         map_uid: &str,
     ) -> Result<Vec<WebservicesLeaderboardEntry>, WebservicesError> {
-        let request = self
-            .client
-            .get(format!("{}{}/top", LIVE_ENDPOINT_MAP_LEADERBOARDS, map_uid));
+        let request = self.client.get(format!(
+            "{}{}{}/top",
+            self.live_base, LIVE_ENDPOINT_MAP_LEADERBOARDS, map_uid
+        ));
         let response: Value = self
             .send_request(request, NadeoAudience::Live)
             .await?
@@ -255,3 +401,117 @@ impl NadeoWebserivcesClient {
         }
     }
 }
+
+/// The delay requested by the server before trying again, if any.
+fn retry_after(response: &Response) -> Option<StdDuration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(StdDuration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    const TOKEN_A: &str = r#"{"accessToken":"access-a","refreshToken":"refresh-a"}"#;
+    const TOKEN_B: &str = r#"{"accessToken":"access-b","refreshToken":"refresh-b"}"#;
+    const LEADERBOARD: &str = r#"{"tops":[{"top":[{"accountId":"a","zoneId":"z","zoneName":"World","position":1,"score":42000,"timestamp":1700000000}]}]}"#;
+
+    /// Serve the given responses in order, one per connection, and record the requests received.
+    async fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 8192];
+                let read = stream.read(&mut buffer).await.unwrap();
+                log.lock()
+                    .push(String::from_utf8_lossy(&buffer[..read]).into_owned());
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{}", address), requests)
+    }
+
+    fn mock_client(base: &str) -> NadeoWebserivcesClient {
+        NadeoWebserivcesClient::new(Client::new(), "user".to_owned(), "pass".to_owned())
+            .with_base_urls(base, base)
+            .with_rate_limit(StdDuration::ZERO)
+            .with_retries(2, StdDuration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_and_rate_limits() {
+        let (base, requests) = mock_server(vec![
+            (200, TOKEN_A),
+            (503, ""),
+            (429, ""),
+            (200, LEADERBOARD),
+        ])
+        .await;
+        let leaderboard = mock_client(&base)
+            .live_get_map_leaderboard("map")
+            .await
+            .unwrap();
+
+        assert_eq!(leaderboard[0].score, 42000);
+        assert_eq!(requests.lock().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let (base, _) = mock_server(vec![(200, TOKEN_A), (500, ""), (500, ""), (500, "")]).await;
+        let result = mock_client(&base).live_get_map_leaderboard("map").await;
+
+        assert!(matches!(
+            result,
+            Err(WebservicesError::Status(StatusCode::INTERNAL_SERVER_ERROR))
+        ));
+    }
+
+    #[tokio::test]
+    async fn refreshes_token_after_unauthorized() {
+        let (base, requests) = mock_server(vec![
+            (200, TOKEN_A),
+            (401, ""),
+            (200, TOKEN_B),
+            (200, LEADERBOARD),
+        ])
+        .await;
+        let result = mock_client(&base).live_get_map_leaderboard("map").await;
+
+        assert!(result.is_ok());
+        let requests = requests.lock();
+        assert!(requests[2].starts_with("POST /v2/authentication/token/refresh"));
+        assert!(requests[2].contains("nadeo_v1 t=refresh-a"));
+        assert!(requests[3].contains("nadeo_v1 t=access-b"));
+    }
+
+    #[tokio::test]
+    async fn reports_failed_authentication() {
+        let (base, _) = mock_server(vec![(401, "")]).await;
+        let result = mock_client(&base).live_get_map_leaderboard("map").await;
+
+        assert!(matches!(result, Err(WebservicesError::Authentication(_))));
+    }
+}
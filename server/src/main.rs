@@ -2,7 +2,10 @@ use reqwest::{
     header::{HeaderMap, USER_AGENT},
     ClientBuilder,
 };
-use std::net::{Ipv4Addr, SocketAddrV4};
+use std::{
+    net::{Ipv4Addr, SocketAddrV4},
+    time::Duration,
+};
 use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
@@ -36,12 +39,20 @@ async fn main() {
     // Load configuration
     config::initialize();
     config::enumerate_keys();
+    config::start_watch_task();
 
     // Database setup
     info!("opening main database store");
     store::initialize_primary_store("db/main.db").await;
     info!("opening mapcache database");
     orm::mapcache::start_database("db/mapcache.db").await;
+    server::mapload::load_blacklist().await;
+    core::presets::load_presets().await;
+    core::schedule::load_schedules().await;
+    core::persistence::restore_snapshots().await;
+    core::daily::start_rotation_task();
+    server::mapload::start_world_record_task();
+    integrations::tmexchange::ingestion::start_ingestion_task();
 
     // Initialize integrations
     let mut headers = HeaderMap::new();
@@ -57,11 +68,23 @@ async fn main() {
         .split_once(':')
         .expect("malformed keys.websevices value, expected username:password");
     let nadeo_client =
-        NadeoWebserivcesClient::new(client.clone(), username.to_string(), password.to_string());
+        NadeoWebserivcesClient::new(client.clone(), username.to_string(), password.to_string())
+            .with_rate_limit(Duration::from_millis(
+                config::get_integer("webservices.request_interval").unwrap_or(500) as u64,
+            ))
+            .with_retries(
+                config::get_integer("webservices.max_retries").unwrap_or(3) as u32,
+                Duration::from_millis(
+                    config::get_integer("webservices.retry_backoff").unwrap_or(1000) as u64,
+                ),
+            );
     integrations::NADEOSERVICES_CLIENT
         .set(nadeo_client)
         .map_err(|_| ())
         .expect("failed to initialize NadeoWebservices");
+    server::maphealth::start_health_task();
+    core::schedule::start_schedule_task();
+    core::persistence::start_snapshot_task();
 
     let hook_url = config::get_string("keys.hook_endpoint");
     if let Some(url) = hook_url {
@@ -93,5 +116,8 @@ async fn main() {
     server.bind(local_addr.into());
 
     info!("TCP connection listener bound at address {}", local_addr);
-    server.run().await;
+    tokio::spawn(server.run());
+
+    server::shutdown::wait_for_signal().await;
+    server::shutdown::shutdown().await;
 }
@@ -0,0 +1,44 @@
+use futures::executor::block_on;
+use sqlx::{Connection, SqliteConnection};
+
+use super::execute;
+
+/// Copy of the server blacklist, so that map queries can exclude it with a subquery.
+pub(super) const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS "blacklist" (
+    "tmxid" INTEGER NOT NULL,
+    PRIMARY KEY("tmxid")
+);
+"#;
+
+async fn replace_blacklist(conn: &mut SqliteConnection, tmxids: &[i32]) -> Result<(), sqlx::Error> {
+    let mut transaction = conn.begin().await?;
+    sqlx::query("DELETE FROM blacklist")
+        .execute(&mut *transaction)
+        .await?;
+    for tmxid in tmxids {
+        sqlx::query("INSERT OR IGNORE INTO blacklist(tmxid) VALUES (?)")
+            .bind(tmxid)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    transaction.commit().await
+}
+
+/// Replace the copy of the blacklist with the maps of the server blacklist.
+pub async fn set_blacklist(tmxids: Vec<i32>) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| block_on(replace_blacklist(&mut conn, &tmxids))).await
+}
+
+/// Add a map to the copy of the blacklist.
+pub async fn add_map(tmxid: i32) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("INSERT OR IGNORE INTO blacklist(tmxid) VALUES (?)")
+                .bind(tmxid)
+                .execute(&mut *conn),
+        )
+        .map(|_| ())
+    })
+    .await
+}
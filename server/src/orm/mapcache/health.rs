@@ -0,0 +1,242 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::executor::block_on;
+use serde::Serialize;
+use sqlx::{Connection, FromRow, Row, SqliteConnection};
+
+use super::execute;
+
+pub(super) const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS "map_health" (
+    "tmxid" INTEGER NOT NULL,
+    "reason" TEXT NOT NULL,
+    "detected_at" TIMESTAMP NOT NULL,
+    PRIMARY KEY("tmxid")
+);
+"#;
+
+/// Why a map was excluded from the pool.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    MissingWebservicesId,
+    InvalidMedals,
+    DeletedFromTmx,
+    DeletedFromNadeo,
+}
+
+impl ExclusionReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::MissingWebservicesId => "missing_webservices_id",
+            Self::InvalidMedals => "invalid_medals",
+            Self::DeletedFromTmx => "deleted_from_tmx",
+            Self::DeletedFromNadeo => "deleted_from_nadeo",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct ExcludedMap {
+    pub tmxid: i32,
+    pub track_name: Option<String>,
+    pub reason: String,
+    pub detected_at: NaiveDateTime,
+}
+
+/// The reason a map cannot be played from its cached data alone, if any.
+const LOCAL_REASON: &str = "CASE WHEN webservices_id IS NULL OR webservices_id = '' THEN 'missing_webservices_id' WHEN author_time <= 0 OR gold_time <= 0 OR silver_time <= 0 OR bronze_time <= 0 THEN 'invalid_medals' END";
+
+/// Flag the maps whose cached data is unusable, and clear flags of maps that have since been fixed.
+pub async fn check_cached_maps(conn: &mut SqliteConnection) -> Result<u64, sqlx::Error> {
+    let mut transaction = conn.begin().await?;
+    sqlx::query(&format!("DELETE FROM map_health WHERE reason IN ('missing_webservices_id', 'invalid_medals') AND tmxid IN (SELECT tmxid FROM maps WHERE ({}) IS NULL)", LOCAL_REASON))
+        .execute(&mut *transaction)
+        .await?;
+    let flagged = sqlx::query(&format!("INSERT OR IGNORE INTO map_health(tmxid, reason, detected_at) SELECT tmxid, {0}, ? FROM maps WHERE ({0}) IS NOT NULL", LOCAL_REASON))
+        .bind(Utc::now())
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    transaction.commit().await?;
+    Ok(flagged)
+}
+
+/// Flag the maps whose cached data is unusable. Returns the number of newly flagged maps.
+pub async fn flag_invalid_maps() -> Result<u64, sqlx::Error> {
+    execute(|mut conn| block_on(check_cached_maps(&mut conn))).await
+}
+
+/// Get the TMX id and UID of maps that are not flagged and were not checked against TMX and
+/// Nadeo since the given date, the least recently checked first. Maps that were missing from the
+/// last check are always checked again first.
+pub async fn get_unchecked_maps(
+    checked_before: DateTime<Utc>,
+    limit: u32,
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("SELECT tmxid, uid FROM maps WHERE tmxid NOT IN (SELECT tmxid FROM map_health) AND (health_misses > 0 OR health_checked_at IS NULL OR health_checked_at < ?) ORDER BY health_misses > 0 DESC, health_checked_at IS NOT NULL, health_checked_at LIMIT ?")
+                .bind(checked_before)
+                .bind(limit)
+                .fetch_all(&mut *conn),
+        )
+        .map(|rows| rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    })
+    .await
+}
+
+/// Record that maps were checked. Maps found missing are only flagged once they have been
+/// missing from `max_misses` consecutive checks, so that an incomplete response doesn't exclude
+/// them. Returns the number of newly flagged maps.
+pub async fn record_check_results(
+    conn: &mut SqliteConnection,
+    checked: &[i32],
+    missing: &[(i32, ExclusionReason)],
+    max_misses: i64,
+) -> Result<u64, sqlx::Error> {
+    let now = Utc::now();
+    let mut flagged = 0;
+    let mut transaction = conn.begin().await?;
+    for tmxid in checked {
+        sqlx::query("UPDATE maps SET health_checked_at = ?, health_misses = 0 WHERE tmxid = ?")
+            .bind(now)
+            .bind(tmxid)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    for (tmxid, reason) in missing {
+        sqlx::query("UPDATE maps SET health_misses = health_misses + 1 WHERE tmxid = ?")
+            .bind(tmxid)
+            .execute(&mut *transaction)
+            .await?;
+        flagged += sqlx::query(
+            "INSERT OR IGNORE INTO map_health(tmxid, reason, detected_at) SELECT tmxid, ?, ? FROM maps WHERE tmxid = ? AND health_misses >= ?",
+        )
+        .bind(reason.as_str())
+        .bind(now)
+        .bind(tmxid)
+        .bind(max_misses)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    }
+    transaction.commit().await?;
+    Ok(flagged)
+}
+
+/// Record the results of a check against TMX and Nadeo. Returns the number of newly flagged maps.
+pub async fn save_check_results(
+    checked: Vec<i32>,
+    missing: Vec<(i32, ExclusionReason)>,
+    max_misses: i64,
+) -> Result<u64, sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(record_check_results(
+            &mut conn, &checked, &missing, max_misses,
+        ))
+    })
+    .await
+}
+
+/// Remove flagged maps from the cache. They stay in the report so they are not ingested again.
+pub async fn prune_flagged_maps() -> Result<u64, sqlx::Error> {
+    execute(|mut conn| {
+        block_on(
+            sqlx::query("DELETE FROM maps WHERE tmxid IN (SELECT tmxid FROM map_health)")
+                .execute(&mut *conn),
+        )
+        .map(|result| result.rows_affected())
+    })
+    .await
+}
+
+/// Get every map excluded from the pool, the most recently detected first.
+pub async fn get_excluded_maps() -> Result<Vec<ExcludedMap>, sqlx::Error> {
+    execute(|mut conn| {
+        block_on(
+            sqlx::query_as("SELECT h.tmxid, m.track_name, h.reason, h.detected_at FROM map_health h LEFT JOIN maps m ON m.tmxid = h.tmxid ORDER BY h.detected_at DESC")
+                .fetch_all(&mut *conn),
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orm::mapcache::MAPS_SCHEMA;
+
+    #[tokio::test]
+    async fn cached_maps_are_flagged_until_fixed() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(MAPS_SCHEMA).execute(&mut conn).await.unwrap();
+        sqlx::raw_sql(SCHEMA).execute(&mut conn).await.unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO maps(tmxid, uid, webservices_id, userid, username, track_name, gbx_name, author_time, gold_time, silver_time, bronze_time, uploaded_at, updated_at) VALUES
+            (1, 'a', 'wa', 1, 'x', 'Fine', 'Fine', 40000, 43000, 49000, 60000, '2024-01-01', '2024-01-01'),
+            (2, 'b', NULL, 1, 'x', 'Offline', 'Offline', 40000, 43000, 49000, 60000, '2024-01-01', '2024-01-01'),
+            (3, 'c', 'wc', 1, 'x', 'No medals', 'No medals', 0, 0, 0, 0, '2024-01-01', '2024-01-01');",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        assert_eq!(check_cached_maps(&mut conn).await.unwrap(), 2);
+        let reasons: Vec<(i32, String)> =
+            sqlx::query_as("SELECT tmxid, reason FROM map_health ORDER BY tmxid")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, "missing_webservices_id".to_string()),
+                (3, "invalid_medals".to_string())
+            ]
+        );
+
+        sqlx::raw_sql("UPDATE maps SET webservices_id = 'wb' WHERE tmxid = 2")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(check_cached_maps(&mut conn).await.unwrap(), 0);
+        let remaining: Vec<(i32,)> = sqlx::query_as("SELECT tmxid FROM map_health")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(remaining, vec![(3,)]);
+    }
+
+    #[tokio::test]
+    async fn missing_maps_are_flagged_after_consecutive_misses() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(MAPS_SCHEMA).execute(&mut conn).await.unwrap();
+        sqlx::raw_sql(SCHEMA).execute(&mut conn).await.unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO maps(tmxid, uid, webservices_id, userid, username, track_name, gbx_name, author_time, gold_time, silver_time, bronze_time, uploaded_at, updated_at) VALUES
+            (1, 'a', 'wa', 1, 'x', 'Unlisted', 'Unlisted', 40000, 43000, 49000, 60000, '2024-01-01', '2024-01-01'),
+            (2, 'b', 'wb', 1, 'x', 'Deleted', 'Deleted', 40000, 43000, 49000, 60000, '2024-01-01', '2024-01-01');",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        let missing = [
+            (1, ExclusionReason::DeletedFromTmx),
+            (2, ExclusionReason::DeletedFromTmx),
+        ];
+        let flagged = record_check_results(&mut conn, &[], &missing, 2).await;
+        assert_eq!(flagged.unwrap(), 0);
+        // the first map shows up again, which resets its misses
+        let flagged = record_check_results(&mut conn, &[1], &missing[1..], 2).await;
+        assert_eq!(flagged.unwrap(), 1);
+        let flagged = record_check_results(&mut conn, &[], &missing[..1], 2).await;
+        assert_eq!(flagged.unwrap(), 0);
+
+        let flagged: Vec<(i32, String)> = sqlx::query_as("SELECT tmxid, reason FROM map_health")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(flagged, vec![(2, "deleted_from_tmx".to_string())]);
+    }
+}
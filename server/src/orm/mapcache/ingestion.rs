@@ -0,0 +1,133 @@
+use futures::executor::block_on;
+use sqlx::{Connection, Row, SqliteConnection};
+
+use super::{execute, record::MapRecord};
+
+pub(super) const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS "ingestion_state" (
+    "key" TEXT NOT NULL,
+    "value" INTEGER,
+    PRIMARY KEY("key")
+);
+"#;
+
+const CURSOR_KEY: &str = "cursor";
+const HIGH_WATER_MARK_KEY: &str = "high_water_mark";
+
+/// Insert or update maps fetched from TMX. World record times fetched by the server are kept.
+pub async fn upsert_maps(
+    conn: &mut SqliteConnection,
+    maps: &[MapRecord],
+) -> Result<(), sqlx::Error> {
+    let mut transaction = conn.begin().await?;
+    for map in maps {
+        sqlx::query("INSERT INTO maps(tmxid, uid, webservices_id, userid, username, track_name, gbx_name, author_time, gold_time, silver_time, bronze_time, uploaded_at, updated_at, tags, style) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT(tmxid) DO UPDATE SET uid=excluded.uid, webservices_id=excluded.webservices_id, userid=excluded.userid, username=excluded.username, track_name=excluded.track_name, gbx_name=excluded.gbx_name, author_time=excluded.author_time, gold_time=excluded.gold_time, silver_time=excluded.silver_time, bronze_time=excluded.bronze_time, uploaded_at=excluded.uploaded_at, updated_at=excluded.updated_at, tags=excluded.tags, style=excluded.style")
+            .bind(map.tmxid)
+            .bind(&map.uid)
+            .bind(&map.webservices_id)
+            .bind(map.userid)
+            .bind(&map.username)
+            .bind(&map.track_name)
+            .bind(&map.gbx_name)
+            .bind(map.author_time)
+            .bind(map.gold_time)
+            .bind(map.silver_time)
+            .bind(map.bronze_time)
+            .bind(map.uploaded_at)
+            .bind(map.updated_at)
+            .bind(&map.tags)
+            .bind(&map.style)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    transaction.commit().await
+}
+
+/// Save maps fetched from TMX to the mapcache.
+pub async fn save_maps(maps: Vec<MapRecord>) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| block_on(upsert_maps(&mut conn, &maps))).await
+}
+
+/// Get the TMX id after which ingestion resumes, or `None` to start from the latest maps.
+pub async fn get_cursor() -> Result<Option<i32>, sqlx::Error> {
+    get_state(CURSOR_KEY).await
+}
+
+/// Save the TMX id after which ingestion resumes.
+pub async fn set_cursor(cursor: Option<i32>) -> Result<(), sqlx::Error> {
+    set_state(CURSOR_KEY, cursor).await
+}
+
+/// Get the TMX id of the newest map ingested, or `None` if no map was ingested yet.
+pub async fn get_high_water_mark() -> Result<Option<i32>, sqlx::Error> {
+    get_state(HIGH_WATER_MARK_KEY).await
+}
+
+/// Save the TMX id of the newest map ingested.
+pub async fn set_high_water_mark(tmxid: i32) -> Result<(), sqlx::Error> {
+    set_state(HIGH_WATER_MARK_KEY, Some(tmxid)).await
+}
+
+async fn get_state(key: &'static str) -> Result<Option<i32>, sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("SELECT value FROM ingestion_state WHERE key = ?")
+                .bind(key)
+                .fetch_optional(&mut *conn),
+        )
+        .map(|row| row.and_then(|row| row.get(0)))
+    })
+    .await
+}
+
+async fn set_state(key: &'static str, value: Option<i32>) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("INSERT OR REPLACE INTO ingestion_state(key, value) VALUES (?, ?)")
+                .bind(key)
+                .bind(value)
+                .execute(&mut *conn),
+        )
+        .map(|_| ())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orm::mapcache::MAPS_SCHEMA;
+
+    fn record(tmxid: i32, track_name: &str) -> MapRecord {
+        MapRecord {
+            track_name: track_name.to_string(),
+            gbx_name: track_name.to_string(),
+            ..MapRecord::test(tmxid)
+        }
+    }
+
+    #[tokio::test]
+    async fn upsert_keeps_world_records() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(MAPS_SCHEMA).execute(&mut conn).await.unwrap();
+
+        upsert_maps(&mut conn, &[record(1, "First"), record(2, "Second")])
+            .await
+            .unwrap();
+        sqlx::query("UPDATE maps SET wr_time = 38000 WHERE tmxid = 1")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        upsert_maps(&mut conn, &[record(1, "Renamed")])
+            .await
+            .unwrap();
+
+        let rows = sqlx::query("SELECT track_name, wr_time FROM maps ORDER BY tmxid")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get::<String, _>(0), "Renamed");
+        assert_eq!(rows[0].get::<Option<i32>, _>(1), Some(38000));
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use futures::executor::block_on;
+use sqlx::{Connection, FromRow, Row};
+
+use super::{execute, record::MapRecord};
+
+pub(super) const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS "mappacks" (
+    "mappack_id" INTEGER NOT NULL,
+    "fetched_at" TIMESTAMP NOT NULL,
+    "etag" TEXT,
+    "last_modified" TEXT,
+    PRIMARY KEY("mappack_id")
+);
+CREATE TABLE IF NOT EXISTS "mappack_maps" (
+    "mappack_id" INTEGER NOT NULL,
+    "tmxid" INTEGER NOT NULL,
+    "uid" CHAR(28) NOT NULL,
+    "webservices_id" TEXT,
+    "userid" INTEGER NOT NULL,
+    "username" TEXT NOT NULL,
+    "track_name" TEXT NOT NULL,
+    "gbx_name" TEXT NOT NULL,
+    "wr_time" INTEGER,
+    "author_time" INTEGER NOT NULL,
+    "gold_time" INTEGER NOT NULL,
+    "silver_time" INTEGER NOT NULL,
+    "bronze_time" INTEGER NOT NULL,
+    "uploaded_at" TIMESTAMP NOT NULL,
+    "updated_at" TIMESTAMP NOT NULL,
+    "tags" TEXT NOT NULL,
+    "style" TEXT,
+    PRIMARY KEY("mappack_id", "tmxid")
+);
+"#;
+
+/// The maps of a mappack as they were last fetched from TMX.
+pub struct CachedMappack {
+    pub fetched_at: DateTime<Utc>,
+    /// HTTP validators of the TMX response the maps were read from.
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub maps: Vec<MapRecord>,
+}
+
+/// Get the cached contents of a mappack, if it was fetched before.
+pub async fn get_mappack(mappack_id: u32) -> Result<Option<CachedMappack>, sqlx::Error> {
+    execute(move |mut conn| {
+        let row = block_on(
+            sqlx::query(
+                "SELECT fetched_at, etag, last_modified FROM mappacks WHERE mappack_id = ?",
+            )
+            .bind(mappack_id)
+            .fetch_optional(&mut *conn),
+        )?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let maps = block_on(
+            sqlx::query("SELECT * FROM mappack_maps WHERE mappack_id = ?")
+                .bind(mappack_id)
+                .fetch_all(&mut *conn),
+        )?
+        .iter()
+        .map(|r| MapRecord::from_row(r).expect("MapRecord from_row failed"))
+        .collect();
+        Ok(Some(CachedMappack {
+            fetched_at: row.get(0),
+            etag: row.get(1),
+            last_modified: row.get(2),
+            maps,
+        }))
+    })
+    .await
+}
+
+/// Replace the cached contents of a mappack, along with the validators of the TMX response.
+pub async fn save_mappack(
+    mappack_id: u32,
+    maps: Vec<MapRecord>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(async {
+            let mut transaction = conn.begin().await?;
+            sqlx::query("DELETE FROM mappack_maps WHERE mappack_id = ?")
+                .bind(mappack_id)
+                .execute(&mut *transaction)
+                .await?;
+            for map in &maps {
+                sqlx::query("INSERT OR REPLACE INTO mappack_maps VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                    .bind(mappack_id)
+                    .bind(map.tmxid)
+                    .bind(&map.uid)
+                    .bind(&map.webservices_id)
+                    .bind(map.userid)
+                    .bind(&map.username)
+                    .bind(&map.track_name)
+                    .bind(&map.gbx_name)
+                    .bind(map.wr_time)
+                    .bind(map.author_time)
+                    .bind(map.gold_time)
+                    .bind(map.silver_time)
+                    .bind(map.bronze_time)
+                    .bind(map.uploaded_at)
+                    .bind(map.updated_at)
+                    .bind(&map.tags)
+                    .bind(&map.style)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            sqlx::query("INSERT OR REPLACE INTO mappacks(mappack_id, fetched_at, etag, last_modified) VALUES (?, ?, ?, ?)")
+                .bind(mappack_id)
+                .bind(Utc::now())
+                .bind(etag)
+                .bind(last_modified)
+                .execute(&mut *transaction)
+                .await?;
+            transaction.commit().await
+        })
+    })
+    .await
+}
+
+/// Mark the cached contents of a mappack as fresh, after TMX reported that it did not change.
+pub async fn touch_mappack(mappack_id: u32) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("UPDATE mappacks SET fetched_at = ? WHERE mappack_id = ?")
+                .bind(Utc::now())
+                .bind(mappack_id)
+                .execute(&mut *conn),
+        )
+        .map(|_| ())
+    })
+    .await
+}
@@ -1,21 +1,115 @@
 use once_cell::sync::OnceCell;
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
 
 use super::Connection;
 
+pub mod blacklist;
+pub mod health;
+pub mod ingestion;
+pub mod mappack;
 pub mod record;
+pub mod worldrecord;
 
 static MAPCACHE_POOL: OnceCell<SqlitePool> = OnceCell::new();
 
+const MAPS_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS "maps" (
+    "tmxid" INTEGER NOT NULL,
+    "uid" CHAR(28) UNIQUE,
+    "webservices_id" TEXT UNIQUE,
+    "userid" INTEGER NOT NULL,
+    "username" TEXT NOT NULL,
+    "track_name" TEXT NOT NULL,
+    "gbx_name" TEXT NOT NULL,
+    "author_time" INTEGER NOT NULL,
+    "gold_time" INTEGER NOT NULL,
+    "silver_time" INTEGER NOT NULL,
+    "bronze_time" INTEGER NOT NULL,
+    "uploaded_at" TIMESTAMP NOT NULL,
+    "updated_at" TIMESTAMP NOT NULL,
+    "tags" TEXT,
+    "style" TEXT,
+    "wr_time" INTEGER,
+    "wr_fetched_at" TIMESTAMP,
+    "health_checked_at" TIMESTAMP,
+    "health_misses" INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY("tmxid")
+);
+"#;
+
 pub async fn start_database(url: &str) {
     let pool = SqlitePoolOptions::new()
         .max_connections(3)
         .connect(url)
         .await
         .expect("database should be started");
+    for schema in [
+        MAPS_SCHEMA,
+        mappack::SCHEMA,
+        ingestion::SCHEMA,
+        health::SCHEMA,
+        blacklist::SCHEMA,
+    ] {
+        sqlx::raw_sql(schema)
+            .execute(&pool)
+            .await
+            .expect("mapcache tables should be created");
+    }
+    add_missing_columns(&pool).await;
     MAPCACHE_POOL.get_or_init(|| pool);
 }
 
+/// Close the mapcache database, waiting for the queries in progress to complete.
+pub async fn close_database() {
+    if let Some(pool) = MAPCACHE_POOL.get() {
+        pool.close().await;
+    }
+}
+
+/// Add the columns maintained by the server to tables created by an older mapcache script
+/// or server version.
+async fn add_missing_columns(pool: &SqlitePool) {
+    add_table_columns(
+        pool,
+        "maps",
+        &[
+            ("wr_time", "INTEGER"),
+            ("wr_fetched_at", "TIMESTAMP"),
+            ("health_checked_at", "TIMESTAMP"),
+            ("health_misses", "INTEGER NOT NULL DEFAULT 0"),
+        ],
+    )
+    .await;
+    add_table_columns(
+        pool,
+        "mappacks",
+        &[("etag", "TEXT"), ("last_modified", "TEXT")],
+    )
+    .await;
+}
+
+async fn add_table_columns(pool: &SqlitePool, table: &str, missing: &[(&str, &str)]) {
+    let columns: Vec<String> =
+        sqlx::query(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await
+            .expect("table columns should be listed")
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+    for (column, kind) in missing {
+        if !columns.iter().any(|c| c == column) {
+            sqlx::query(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, kind
+            ))
+            .execute(pool)
+            .await
+            .expect("table columns should be added");
+        }
+    }
+}
+
 pub async fn execute<F, R>(f: F) -> R
 where
     F: FnOnce(Connection) -> R + Send + 'static,
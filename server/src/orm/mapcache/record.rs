@@ -1,9 +1,9 @@
-use crate::core::util::serialize::serialize_time;
-use chrono::NaiveDateTime;
-use serde::Serialize;
+use crate::core::util::serialize::{deserialize_time, serialize_time};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-#[derive(Serialize, Clone, Debug, FromRow)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
 pub struct MapRecord {
     pub tmxid: i32,
     pub uid: String,
@@ -14,14 +14,54 @@ pub struct MapRecord {
     pub gbx_name: String,
     #[sqlx(default)]
     pub wr_time: Option<i32>,
+    #[sqlx(default)]
+    #[serde(skip)]
+    pub wr_fetched_at: Option<DateTime<Utc>>,
     pub author_time: i32,
     pub gold_time: i32,
     pub silver_time: i32,
     pub bronze_time: i32,
-    #[serde(serialize_with = "serialize_time")]
+    #[serde(
+        serialize_with = "serialize_time",
+        deserialize_with = "deserialize_time"
+    )]
     pub uploaded_at: NaiveDateTime,
-    #[serde(serialize_with = "serialize_time")]
+    #[serde(
+        serialize_with = "serialize_time",
+        deserialize_with = "deserialize_time"
+    )]
     pub updated_at: NaiveDateTime,
     pub tags: String,
     pub style: Option<String>,
+    /// Where the map file is downloaded from, for maps that are not played from TMX.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+#[cfg(test)]
+impl MapRecord {
+    /// A playable map for tests, named after its TMX id.
+    pub fn test(tmxid: i32) -> Self {
+        Self {
+            tmxid,
+            uid: format!("uid{}", tmxid),
+            webservices_id: None,
+            userid: 1,
+            username: "author".to_string(),
+            track_name: format!("Map {}", tmxid),
+            gbx_name: format!("Map {}", tmxid),
+            wr_time: None,
+            wr_fetched_at: None,
+            author_time: 40000,
+            gold_time: 43000,
+            silver_time: 49000,
+            bronze_time: 60000,
+            uploaded_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+            tags: String::new(),
+            style: None,
+            download_url: None,
+        }
+    }
 }
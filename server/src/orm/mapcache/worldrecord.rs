@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use futures::executor::block_on;
+use sqlx::Row;
+
+use super::execute;
+
+/// Save the world record time of a map, or the absence of one, along with when it was fetched.
+pub async fn save_world_record(tmxid: i32, wr_time: Option<i32>) -> Result<(), sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("UPDATE maps SET wr_time = ?, wr_fetched_at = ? WHERE tmxid = ?")
+                .bind(wr_time)
+                .bind(Utc::now())
+                .bind(tmxid)
+                .execute(&mut *conn),
+        )
+        .map(|_| ())
+    })
+    .await
+}
+
+/// Get the TMX id and UID of maps in the pool whose world record was never fetched or was
+/// fetched before the given date, the most outdated first.
+pub async fn get_outdated_world_records(
+    max_author_time: i64,
+    fetched_before: DateTime<Utc>,
+    limit: u32,
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    execute(move |mut conn| {
+        block_on(
+            sqlx::query("SELECT tmxid, uid FROM maps WHERE author_time <= ? AND (wr_fetched_at IS NULL OR wr_fetched_at < ?) ORDER BY wr_fetched_at IS NOT NULL, wr_fetched_at LIMIT ?")
+                .bind(max_author_time)
+                .bind(fetched_before)
+                .bind(limit)
+                .fetch_all(&mut *conn),
+        )
+        .map(|rows| rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    })
+    .await
+}
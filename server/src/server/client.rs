@@ -6,11 +6,13 @@ use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::select;
 use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::watch;
 use tokio::time::{timeout_at, Instant};
 use tracing::{debug, error, warn};
 
 use super::handshake;
 use super::requests::BaseRequest;
+use super::shutdown;
 use crate::server::context::ClientContext;
 use crate::server::handlers::handle_request;
 use crate::transport::client::tcpnative::NativeClientProtocol;
@@ -29,6 +31,7 @@ pub struct NetClient {
     timeout: Duration,
     callback_mode: ClientCallbackImplementation,
     context: Option<ClientContext>,
+    closing: watch::Receiver<bool>,
 }
 
 impl NetClient {
@@ -43,6 +46,7 @@ impl NetClient {
             timeout,
             callback_mode: ClientCallbackImplementation::None,
             context: None,
+            closing: shutdown::subscribe_closing(),
         }
     }
 
@@ -86,11 +90,13 @@ impl NetClient {
 
     /// Run the main loop of the client.
     pub async fn run(mut self) {
+        let _guard = shutdown::track_connection();
         let mut timeout_deadline = Instant::now() + self.timeout;
         loop {
             let next_message_received =
                 timeout_at(timeout_deadline, self.protocol.receive_message().fuse());
             let outgoing_message_queued = self.receiver.recv().fuse();
+            let server_closing = self.closing.changed().fuse();
 
             select! {
                 timed_result = next_message_received => match timed_result {
@@ -130,6 +136,16 @@ impl NetClient {
                         );
                         break;
                     }
+                },
+                _ = server_closing => {
+                    // send the messages still queued before closing the connection
+                    while let Ok(message) = self.receiver.try_recv() {
+                        if self.protocol.write(message).await.is_err() {
+                            break;
+                        }
+                    }
+                    debug!(cid = self.cid, "closed connection for server shutdown");
+                    break;
                 }
             }
         }
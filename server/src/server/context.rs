@@ -4,7 +4,7 @@ use tracing::debug;
 
 use crate::{
     core::{
-        directory::{Owned, Shared},
+        directory::{Owned, Shared, CLIENTS_CHANNEL},
         livegame::LiveMatch,
         models::team::TeamIdentifier,
         room::GameRoom,
@@ -22,6 +22,9 @@ pub struct ClientContext {
 
 impl ClientContext {
     pub fn new(profile: PlayerProfile, writer: NetMessager) -> Self {
+        CLIENTS_CHANNEL
+            .lock()
+            .subscribe(profile.uid, writer.clone());
         Self {
             room: None,
             game: None,
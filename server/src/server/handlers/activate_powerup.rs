@@ -1,4 +1,5 @@
 use crate::{
+    core::models::livegame::PowerupTarget,
     datatypes::Powerup,
     server::{
         context::ClientContext,
@@ -11,23 +12,18 @@ use serde_json::Value;
 #[derive(Deserialize, Debug)]
 pub struct ActivatePowerup {
     powerup: Powerup,
-    board_index: usize,
-    forwards: bool,
-    player_uid: i32,
-    choice: i32,
+    item_id: Option<u32>,
+    #[serde(flatten)]
+    target: PowerupTarget,
 }
 
 pub fn handle(ctx: &mut ClientContext, args: ActivatePowerup) -> Value {
     ctx.game_sync();
     if let Some(game) = ctx.game_match() {
-        match game.lock().activate_powerup(
-            ctx.profile.uid,
-            args.powerup,
-            args.board_index,
-            args.forwards,
-            args.choice,
-            args.player_uid,
-        ) {
+        match game
+            .lock()
+            .activate_powerup(ctx.profile.uid, args.powerup, args.item_id, args.target)
+        {
             Ok(()) => ok(),
             Err(msg) => error(&msg),
         }
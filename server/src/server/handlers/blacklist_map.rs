@@ -0,0 +1,34 @@
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+        mapload,
+    },
+    store::blacklist::BlacklistedMap,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct BlacklistMap {
+    tmxid: i32,
+    #[serde(default)]
+    reason: String,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: BlacklistMap) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can blacklist maps.");
+    }
+
+    mapload::blacklist_map(BlacklistedMap {
+        tmxid: args.tmxid,
+        reason: args.reason,
+        added_by: ctx.profile.uid,
+        created_at: Utc::now(),
+    });
+    ok()
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    core::schedule,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CancelScheduledRoom {
+    join_code: String,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: CancelScheduledRoom) -> Value {
+    match schedule::cancel(
+        ctx.profile.uid,
+        &args.join_code,
+        config::is_admin(&ctx.profile.account_id),
+    ) {
+        Ok(()) => ok(),
+        Err(e) => error(&e.to_string()),
+    }
+}
@@ -0,0 +1,38 @@
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::info;
+
+use crate::{
+    config,
+    core::directory::ROOMS,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CloseRoom {
+    join_code: String,
+    /// Message shown to the players of the room.
+    message: Option<String>,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: CloseRoom) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can close rooms.");
+    }
+
+    let Some(room) = ROOMS.find(args.join_code.clone()) else {
+        return error(&format!("No room was found with code {}.", args.join_code));
+    };
+    room.lock().close_room(
+        args.message
+            .unwrap_or("This room was closed by a server administrator.".to_owned()),
+    );
+    info!(
+        "room {} was closed by administrator {}",
+        args.join_code, ctx.profile.name
+    );
+    ok()
+}
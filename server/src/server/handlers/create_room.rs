@@ -3,19 +3,32 @@ use serde_json::Value;
 
 use crate::{
     config,
-    core::{directory, gamecommon::setup_room, models::team::BaseTeam, room::GameRoom},
-    datatypes::{MatchConfiguration, RoomConfiguration},
+    core::{
+        directory,
+        gamecommon::setup_room,
+        models::team::BaseTeam,
+        presets,
+        room::GameRoom,
+        validation::{self, Limits},
+    },
+    datatypes::{MatchConfiguration, PracticeGoal, RoomConfiguration},
     server::{
         context::{ClientContext, RoomContext},
-        handlers::{error, response},
+        handlers::{error, invalid_fields, response},
+        shutdown,
     },
 };
 
 #[derive(Deserialize, Debug)]
 pub struct CreateRoom {
+    #[serde(default)]
     config: RoomConfiguration,
+    #[serde(default)]
     match_config: MatchConfiguration,
+    #[serde(default)]
     teams: Vec<BaseTeam>,
+    /// Create the room with the settings of a saved preset instead.
+    preset_id: Option<i64>,
 }
 
 #[derive(Serialize, Debug)]
@@ -26,13 +39,44 @@ pub struct CreateRoomResponse {
     pub teams: Vec<BaseTeam>,
 }
 
-pub fn handle(ctx: &mut ClientContext, args: CreateRoom) -> Value {
-    if args.teams.len() < 2 {
+pub fn handle(ctx: &mut ClientContext, mut args: CreateRoom) -> Value {
+    if shutdown::is_draining() {
+        return error("The server is about to restart, new rooms cannot be created.");
+    }
+    if let Some(id) = args.preset_id {
+        let Some(preset) = presets::get_preset(ctx.profile.uid, id) else {
+            return error("This preset does not exist or is not yours.");
+        };
+        args.config = preset.config;
+        args.match_config = preset.match_config;
+        // official presets don't have teams, keep the ones of the player
+        if !preset.teams.is_empty() {
+            args.teams = preset.teams;
+        }
+    }
+
+    if args.match_config.practice_goal != PracticeGoal::None {
+        if args.teams.is_empty() {
+            return error(
+                "No team was provided to create a practice room. Please configure a team in the Teams Editor."
+            );
+        }
+        // practice rooms are solo, they are never listed nor joinable
+        args.config.public = false;
+        args.config.size = 1;
+        args.teams.truncate(1);
+    } else if args.teams.len() < 2 {
         return error(
             "Not enough teams to create a new room. Please configure at least 2 teams in the Teams Editor."
         );
     }
 
+    if let Err(errors) =
+        validation::validate(&args.config, &args.match_config, &Limits::from_config())
+    {
+        return invalid_fields(&errors);
+    }
+
     if let Some(room) = ctx.game_room() {
         ctx.trace("already in a room, leaving previous game");
         room.lock().player_remove(ctx.profile.uid);
@@ -59,7 +103,7 @@ pub fn handle(ctx: &mut ClientContext, args: CreateRoom) -> Value {
     response(CreateRoomResponse {
         name: room.name().to_owned(),
         join_code: room.join_code().to_owned(),
-        max_teams: config::get_integer("behavior.max_teams").unwrap_or(6) as usize,
+        max_teams: config::get_integer("behaviour.max_teams").unwrap_or(6) as usize,
         teams: room.teams().into_iter().map(BaseTeam::to_owned).collect(),
     })
 }
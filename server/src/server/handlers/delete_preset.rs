@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::presets,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeletePreset {
+    id: i64,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: DeletePreset) -> Value {
+    match presets::delete_preset(ctx.profile.uid, args.id) {
+        Ok(()) => ok(),
+        Err(e) => error(&e.to_string()),
+    }
+}
@@ -2,10 +2,11 @@ use serde::Deserialize;
 use serde_json::Value;
 
 use crate::{
+    core::validation::{self, Limits},
     datatypes::{MatchConfiguration, RoomConfiguration},
     server::{
         context::ClientContext,
-        handlers::{error, ok},
+        handlers::{error, invalid_fields, ok},
     },
 };
 
@@ -21,6 +22,11 @@ pub fn handle(ctx: &mut ClientContext, args: EditConfig) -> Value {
         if !lock.get_player(ctx.profile.uid).unwrap().operator {
             return error("You are not a room operator.");
         }
+        if let Err(errors) =
+            validation::validate(&args.config, &args.match_config, &Limits::from_config())
+        {
+            return invalid_fields(&errors);
+        }
 
         lock.set_configs(args.config, args.match_config);
         ok()
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::info;
+
+use crate::{
+    config,
+    core::{directory::MATCHES, models::livegame::MatchPhase},
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EndMatch {
+    uid: String,
+}
+
+/// End a match as a draw.
+pub fn handle(ctx: &mut ClientContext, args: EndMatch) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can end matches.");
+    }
+
+    let Some(game) = MATCHES.find(args.uid.clone()) else {
+        return error(&format!("match with uid {} not found", args.uid));
+    };
+    let mut game = game.lock();
+    if game.phase() == MatchPhase::Ended {
+        return error("This match has already ended.");
+    }
+    game.draw_end_game();
+    drop(game);
+    info!(
+        "match {} was ended by administrator {}",
+        args.uid, ctx.profile.name
+    );
+    ok()
+}
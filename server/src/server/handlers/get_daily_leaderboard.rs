@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    core::daily,
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+    store::daily::DailyLeaderboardEntry,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct GetDailyLeaderboard {
+    date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DailyLeaderboard {
+    pub date: NaiveDate,
+    pub entries: Vec<DailyLeaderboardEntry>,
+}
+
+pub fn handle(_ctx: &mut ClientContext, args: GetDailyLeaderboard) -> Value {
+    let date = args.date.unwrap_or_else(daily::today);
+    match daily::get_leaderboard(date) {
+        Some(entries) => response(DailyLeaderboard { date, entries }),
+        None => error(&format!("no daily challenge leaderboard for {}", date)),
+    }
+}
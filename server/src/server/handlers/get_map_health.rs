@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    config,
+    integrations::tmexchange::ingestion::{self, IngestionMetrics},
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+        maphealth::{self, HealthReport},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct GetMapHealth {}
+
+#[derive(Serialize)]
+pub struct MapHealthResponse {
+    #[serde(flatten)]
+    pub report: HealthReport,
+    pub ingestion: IngestionMetrics,
+}
+
+pub fn handle(ctx: &mut ClientContext, _args: GetMapHealth) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can view the map pool health.");
+    }
+
+    response(MapHealthResponse {
+        report: maphealth::report(),
+        ingestion: ingestion::metrics(),
+    })
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    core::directory::MATCHES,
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct GetMatchState {
+    uid: String,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: GetMatchState) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can inspect matches.");
+    }
+
+    match MATCHES.find(args.uid.clone()) {
+        Some(game) => response(game.lock().get_state()),
+        None => error(&format!("match with uid {} not found", args.uid)),
+    }
+}
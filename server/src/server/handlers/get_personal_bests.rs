@@ -0,0 +1,30 @@
+use futures::executor::block_on;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::error;
+
+use crate::{
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+    store::{self, practice::PracticeResult},
+};
+
+#[derive(Deserialize, Debug)]
+pub struct GetPersonalBests {}
+
+#[derive(Serialize, Debug)]
+pub struct PersonalBests {
+    pub bests: Vec<PracticeResult>,
+}
+
+pub fn handle(ctx: &mut ClientContext, _args: GetPersonalBests) -> Value {
+    match block_on(store::practice::get_personal_bests(ctx.profile.uid)) {
+        Ok(bests) => response(PersonalBests { bests }),
+        Err(e) => {
+            error!("failed to load personal bests: {}", e);
+            error("Personal bests could not be loaded.")
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    core::presets,
+    server::{context::ClientContext, handlers::response},
+    store::preset::RoomPreset,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct GetPresets {}
+
+#[derive(Serialize, Debug)]
+pub struct PresetsResponse {
+    pub presets: Vec<RoomPreset>,
+}
+
+pub fn handle(ctx: &mut ClientContext, _args: GetPresets) -> Value {
+    response(PresetsResponse {
+        presets: presets::get_presets(ctx.profile.uid),
+    })
+}
@@ -5,8 +5,10 @@ use crate::{
     core::{
         directory::{PUB_ROOMS_CHANNEL, ROOMS},
         models::room::NetworkRoom,
+        schedule,
     },
     server::{context::ClientContext, handlers::response},
+    store::schedule::ScheduledRoom,
 };
 
 #[derive(Deserialize, Debug)]
@@ -15,6 +17,8 @@ pub struct GetPublicRooms {}
 #[derive(Serialize, Debug)]
 pub struct PublicRoomsList {
     pub rooms: Vec<NetworkRoom>,
+    /// Public rooms scheduled to open later.
+    pub scheduled: Vec<ScheduledRoom>,
 }
 
 pub fn handle(ctx: &mut ClientContext, _args: GetPublicRooms) -> Value {
@@ -29,5 +33,8 @@ pub fn handle(ctx: &mut ClientContext, _args: GetPublicRooms) -> Value {
         .map(|r| NetworkRoom::from(&*r.lock()))
         .collect();
 
-    response(PublicRoomsList { rooms })
+    response(PublicRoomsList {
+        rooms,
+        scheduled: schedule::get_listed(),
+    })
 }
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::presets,
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ImportPreset {
+    share_code: String,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: ImportPreset) -> Value {
+    match presets::import_preset(ctx.profile.uid, args.share_code.trim()) {
+        Ok(preset) => response(preset),
+        Err(e) => error(&e.to_string()),
+    }
+}
@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::daily,
+    server::{
+        context::{ClientContext, GameContext},
+        handlers::{error, join_match::JoinMatchOk, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct JoinDailyChallenge {}
+
+pub fn handle(ctx: &mut ClientContext, _args: JoinDailyChallenge) -> Value {
+    if let Some(room) = ctx.game_room() {
+        ctx.trace("already joined a room, leaving previous game");
+        room.lock().player_remove(ctx.profile.uid);
+    }
+
+    match daily::start_daily_match(ctx) {
+        Ok(livematch) => {
+            let state = livematch.lock().get_state();
+            ctx.game = Some(GameContext::new(ctx.profile.clone(), &livematch));
+            response(JoinMatchOk { state })
+        }
+        Err(e) => error(&e.to_string()),
+    }
+}
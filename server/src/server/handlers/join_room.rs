@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
-    core::{directory::ROOMS, models::room::RoomTeam, room::JoinRoomError},
+    core::{directory::ROOMS, models::room::RoomTeam, room::JoinRoomError, schedule},
     datatypes::{MatchConfiguration, RoomConfiguration},
     server::{
         context::{ClientContext, RoomContext},
@@ -45,6 +45,11 @@ pub fn handle(ctx: &mut ClientContext, args: JoinRoom) -> Value {
             teams: lock.teams_as_model(),
             is_host
         })
+    } else if let Some(scheduled) = schedule::find(&args.join_code) {
+        error(&format!(
+            "This room is scheduled to start on {}, it opens shortly before.",
+            scheduled.start_at.format("%Y-%m-%d %H:%M UTC")
+        ))
     } else {
         error(&format!("{}", JoinRoomError::DoesNotExist(args.join_code)))
     }
@@ -0,0 +1,101 @@
+use std::sync::{Arc, Weak};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::{serde_as, TimestampSeconds};
+
+use crate::{
+    config,
+    core::{
+        directory::{MATCHES, ROOMS},
+        models::livegame::MatchPhase,
+    },
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ListGames {}
+
+#[serde_as]
+#[derive(Serialize, Debug)]
+pub struct RoomSummary {
+    pub join_code: String,
+    pub name: String,
+    pub public: bool,
+    pub players: usize,
+    #[serde_as(as = "TimestampSeconds")]
+    pub created: DateTime<Utc>,
+    pub match_uid: Option<String>,
+}
+
+#[serde_as]
+#[derive(Serialize, Debug)]
+pub struct MatchSummary {
+    pub uid: String,
+    pub room_code: Option<String>,
+    pub players: usize,
+    pub phase: MatchPhase,
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub started: Option<DateTime<Utc>>,
+    pub daily: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GamesList {
+    pub rooms: Vec<RoomSummary>,
+    pub matches: Vec<MatchSummary>,
+}
+
+pub fn handle(ctx: &mut ClientContext, _args: ListGames) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can list rooms and matches.");
+    }
+
+    let rooms: Vec<_> = ROOMS.lock().values().cloned().collect();
+    let matches: Vec<_> = MATCHES.lock().values().cloned().collect();
+
+    let room_summaries: Vec<RoomSummary> = rooms
+        .iter()
+        .map(|room| {
+            let room = room.lock();
+            RoomSummary {
+                join_code: room.join_code().to_owned(),
+                name: room.config().name.clone(),
+                public: room.config().public,
+                players: room.players().len(),
+                created: *room.created(),
+                match_uid: room.match_uid(),
+            }
+        })
+        .collect();
+
+    // rooms are found by address, locking them while holding a match could deadlock
+    let match_summaries = matches
+        .iter()
+        .map(|game| {
+            let game = game.lock();
+            let parent = Weak::as_ptr(game.parent_room());
+            MatchSummary {
+                uid: game.uid().clone(),
+                room_code: rooms
+                    .iter()
+                    .zip(&room_summaries)
+                    .find(|(room, _)| Arc::as_ptr(room) == parent)
+                    .map(|(_, summary)| summary.join_code.clone()),
+                players: game.player_count(),
+                phase: game.phase(),
+                started: *game.start_date(),
+                daily: game.daily(),
+            }
+        })
+        .collect();
+
+    response(GamesList {
+        rooms: room_summaries,
+        matches: match_summaries,
+    })
+}
@@ -6,38 +6,71 @@ use thiserror::Error;
 
 use ping::Ping;
 
+use crate::core::validation::FieldError;
 use crate::server::{
     context::ClientContext,
     handlers::{
-        activate_powerup::ActivatePowerup, change_player_team::ChangePlayerTeam,
-        change_team::ChangeTeam, create_room::CreateRoom, create_team::CreateTeam,
-        delete_team::DeleteTeam, edit_config::EditConfig, get_dice_choices::GetDiceChoices,
-        get_public_rooms::GetPublicRooms, join_match::JoinMatch, join_room::JoinRoom,
-        poll_vote::SubmitPollVote, reload_maps::ReloadMaps, send_chat::SendChatMessage,
+        activate_powerup::ActivatePowerup, blacklist_map::BlacklistMap,
+        cancel_scheduled_room::CancelScheduledRoom, change_player_team::ChangePlayerTeam,
+        change_team::ChangeTeam, close_room::CloseRoom, create_preset::CreatePreset,
+        create_room::CreateRoom, create_team::CreateTeam, delete_preset::DeletePreset,
+        delete_team::DeleteTeam, edit_config::EditConfig, end_match::EndMatch,
+        get_daily_leaderboard::GetDailyLeaderboard, get_dice_choices::GetDiceChoices,
+        get_map_health::GetMapHealth, get_match_state::GetMatchState,
+        get_personal_bests::GetPersonalBests, get_presets::GetPresets,
+        get_public_rooms::GetPublicRooms, import_preset::ImportPreset,
+        join_daily_challenge::JoinDailyChallenge, join_match::JoinMatch, join_room::JoinRoom,
+        list_games::ListGames, poll_vote::SubmitPollVote, refresh_mappack::RefreshMappack,
+        register_scheduled_room::RegisterScheduledRoom, reload_config::ReloadConfig,
+        reload_maps::ReloadMaps, schedule_room::ScheduleRoom, send_announcement::SendAnnouncement,
+        send_chat::SendChatMessage, set_drain_mode::SetDrainMode, set_handicap::SetHandicap,
         shuffle_teams::ShuffleTeams, start_match::StartMatch, submit_run::SubmitRun,
-        unsubscribe_roomlist::UnsubscribeRoomlist, vote_reroll::CastRerollVote,
+        unsubscribe_roomlist::UnsubscribeRoomlist, update_preset::UpdatePreset,
+        vote_reroll::CastRerollVote,
     },
 };
 
 mod activate_powerup;
+mod blacklist_map;
+mod cancel_scheduled_room;
 mod change_player_team;
 mod change_team;
+mod close_room;
+mod create_preset;
 mod create_room;
 mod create_team;
+mod delete_preset;
 mod delete_team;
 mod edit_config;
+mod end_match;
+mod get_daily_leaderboard;
 mod get_dice_choices;
+mod get_map_health;
+mod get_match_state;
+mod get_personal_bests;
+mod get_presets;
 mod get_public_rooms;
+mod import_preset;
+mod join_daily_challenge;
 mod join_match;
 mod join_room;
+mod list_games;
 mod ping;
 mod poll_vote;
+mod refresh_mappack;
+mod register_scheduled_room;
+mod reload_config;
 mod reload_maps;
+mod schedule_room;
+mod send_announcement;
 mod send_chat;
+mod set_drain_mode;
+mod set_handicap;
 mod shuffle_teams;
 mod start_match;
 mod submit_run;
 mod unsubscribe_roomlist;
+mod update_preset;
 mod vote_reroll;
 
 #[derive(Error, Debug)]
@@ -84,6 +117,28 @@ pub fn handle_request(
     define_request_handler!(SubmitPollVote, poll_vote::handle);
     define_request_handler!(ActivatePowerup, activate_powerup::handle);
     define_request_handler!(GetDiceChoices, get_dice_choices::handle);
+    define_request_handler!(JoinDailyChallenge, join_daily_challenge::handle);
+    define_request_handler!(GetDailyLeaderboard, get_daily_leaderboard::handle);
+    define_request_handler!(GetPersonalBests, get_personal_bests::handle);
+    define_request_handler!(SetHandicap, set_handicap::handle);
+    define_request_handler!(BlacklistMap, blacklist_map::handle);
+    define_request_handler!(RefreshMappack, refresh_mappack::handle);
+    define_request_handler!(GetMapHealth, get_map_health::handle);
+    define_request_handler!(GetPresets, get_presets::handle);
+    define_request_handler!(CreatePreset, create_preset::handle);
+    define_request_handler!(UpdatePreset, update_preset::handle);
+    define_request_handler!(DeletePreset, delete_preset::handle);
+    define_request_handler!(ImportPreset, import_preset::handle);
+    define_request_handler!(ScheduleRoom, schedule_room::handle);
+    define_request_handler!(CancelScheduledRoom, cancel_scheduled_room::handle);
+    define_request_handler!(RegisterScheduledRoom, register_scheduled_room::handle);
+    define_request_handler!(SetDrainMode, set_drain_mode::handle);
+    define_request_handler!(ListGames, list_games::handle);
+    define_request_handler!(GetMatchState, get_match_state::handle);
+    define_request_handler!(CloseRoom, close_room::handle);
+    define_request_handler!(EndMatch, end_match::handle);
+    define_request_handler!(SendAnnouncement, send_announcement::handle);
+    define_request_handler!(ReloadConfig, reload_config::handle);
 
     Err(RequestError::NoMatchedHandler(request.to_string()))
 }
@@ -104,3 +159,12 @@ pub fn error(message: &str) -> Value {
         "error": message,
     })
 }
+
+/// Return an error listing the settings that were rejected.
+pub fn invalid_fields(errors: &[FieldError]) -> Value {
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    json!({
+        "error": messages.join(" "),
+        "fields": errors,
+    })
+}
@@ -0,0 +1,31 @@
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{error, info};
+
+use crate::{
+    config,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+        mapload,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshMappack {
+    mappack_id: u32,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: RefreshMappack) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can refresh mappacks.");
+    }
+
+    tokio::spawn(async move {
+        match mapload::fetch_mappack(args.mappack_id).await {
+            Ok(maps) => info!("refreshed mappack {}: {} maps", args.mappack_id, maps.len()),
+            Err(e) => error!("failed to refresh mappack {}: {}", args.mappack_id, e),
+        }
+    });
+    ok()
+}
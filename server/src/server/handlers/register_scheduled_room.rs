@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::schedule,
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct RegisterScheduledRoom {
+    join_code: String,
+    /// Index of the team in the scheduled room, or unset to cancel the registration.
+    team: Option<usize>,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: RegisterScheduledRoom) -> Value {
+    match schedule::register(ctx.profile.uid, &args.join_code, args.team) {
+        Ok(room) => response(room),
+        Err(e) => error(&e.to_string()),
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    server::{
+        context::ClientContext,
+        handlers::{error, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ReloadConfig {}
+
+pub fn handle(ctx: &mut ClientContext, _args: ReloadConfig) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can reload the configuration.");
+    }
+
+    match config::reload() {
+        Ok(summary) => response(summary),
+        Err(e) => error(&e),
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_with::{serde_as, TimestampSeconds};
+
+use crate::{
+    core::{
+        models::team::BaseTeam,
+        schedule,
+        validation::{self, Limits},
+    },
+    datatypes::{MatchConfiguration, RoomConfiguration},
+    server::{
+        context::ClientContext,
+        handlers::{error, invalid_fields, response},
+    },
+};
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+pub struct ScheduleRoom {
+    config: RoomConfiguration,
+    match_config: MatchConfiguration,
+    teams: Vec<BaseTeam>,
+    #[serde_as(as = "TimestampSeconds")]
+    start_at: DateTime<Utc>,
+    /// Players required for the match to start automatically, one per team if unset.
+    #[serde(default)]
+    min_players: u32,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: ScheduleRoom) -> Value {
+    if let Err(errors) =
+        validation::validate(&args.config, &args.match_config, &Limits::from_config())
+    {
+        return invalid_fields(&errors);
+    }
+
+    match schedule::schedule_room(
+        ctx.profile.uid,
+        args.config,
+        args.match_config,
+        args.teams,
+        args.start_at,
+        args.min_players,
+    ) {
+        Ok(room) => response(room),
+        Err(e) => error(&e.to_string()),
+    }
+}
@@ -0,0 +1,32 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    core::{directory::CLIENTS_CHANNEL, events::server::ServerEvent},
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct SendAnnouncement {
+    message: String,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: SendAnnouncement) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can send announcements.");
+    }
+    if args.message.trim().is_empty() {
+        return error("The announcement is empty.");
+    }
+
+    CLIENTS_CHANNEL
+        .lock()
+        .broadcast(&ServerEvent::Announcement {
+            message: args.message,
+        });
+    ok()
+}
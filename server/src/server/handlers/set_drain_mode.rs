@@ -0,0 +1,25 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config,
+    server::{
+        context::ClientContext,
+        handlers::{error, ok},
+        shutdown,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct SetDrainMode {
+    enabled: bool,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: SetDrainMode) -> Value {
+    if !config::is_admin(&ctx.profile.account_id) {
+        return error("Only server administrators can change the drain mode.");
+    }
+
+    shutdown::set_draining(args.enabled);
+    ok()
+}
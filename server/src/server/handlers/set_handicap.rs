@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        models::team::{Handicap, TeamIdentifier},
+        validation,
+    },
+    server::{
+        context::ClientContext,
+        handlers::{error, invalid_fields, ok},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct SetHandicap {
+    team_id: Option<TeamIdentifier>,
+    player_uid: Option<i32>,
+    handicap: Handicap,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: SetHandicap) -> Value {
+    if let Some(room) = ctx.game_room() {
+        let mut lock = room.lock();
+        if !lock.get_player(ctx.profile.uid).unwrap().operator {
+            return error("You are not a room operator.");
+        }
+        if let Err(errors) = validation::validate_handicap(&args.handicap, lock.matchconfig()) {
+            return invalid_fields(&errors);
+        }
+
+        let result = match (args.team_id, args.player_uid) {
+            (Some(team), None) => lock.set_team_handicap(team, args.handicap),
+            (None, Some(uid)) => lock.set_player_handicap(uid, args.handicap),
+            _ => return error("A handicap applies to either a team or a player."),
+        };
+        if let Err(e) = result {
+            return error(&e.to_string());
+        }
+        ok()
+    } else {
+        error("Player is not in a room.")
+    }
+}
@@ -21,15 +21,19 @@ pub struct SubmitRun {
 pub fn handle(ctx: &mut ClientContext, args: SubmitRun) -> Value {
     ctx.game_sync();
     if let Some(game) = ctx.game_match() {
-        let claim = MapClaim {
+        let mut claim = MapClaim {
             player: ctx.get_player_ref(),
             team_id: ctx.game.as_ref().unwrap().team(),
             time: args.time,
             medal: args.medal,
             splits: args.splits,
             timestamp: Utc::now(),
+            time_allowance: 0,
         };
         let mut lock = game.lock();
+        if let Err(e) = lock.apply_handicap(&mut claim) {
+            return error(&e.to_string());
+        }
         lock.add_submitted_run(args.tile_index, claim);
     } else {
         return error("not in a game");
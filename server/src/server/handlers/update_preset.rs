@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        models::team::BaseTeam,
+        presets,
+        validation::{self, Limits},
+    },
+    datatypes::{MatchConfiguration, RoomConfiguration},
+    server::{
+        context::ClientContext,
+        handlers::{error, invalid_fields, response},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct UpdatePreset {
+    id: i64,
+    name: String,
+    config: RoomConfiguration,
+    match_config: MatchConfiguration,
+    #[serde(default)]
+    teams: Vec<BaseTeam>,
+}
+
+pub fn handle(ctx: &mut ClientContext, args: UpdatePreset) -> Value {
+    if let Err(errors) =
+        validation::validate(&args.config, &args.match_config, &Limits::from_config())
+    {
+        return invalid_fields(&errors);
+    }
+    match presets::update_preset(
+        ctx.profile.uid,
+        args.id,
+        args.name,
+        args.config,
+        args.match_config,
+        args.teams,
+    ) {
+        Ok(preset) => response(preset),
+        Err(e) => error(&e.to_string()),
+    }
+}
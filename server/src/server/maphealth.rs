@@ -0,0 +1,142 @@
+// Check that the maps in the mapcache can still be played
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+use crate::{
+    config,
+    integrations::{
+        self,
+        tmexchange::{ingestion::MapIngestion, BASE},
+    },
+    orm::mapcache::health::{self, ExcludedMap, ExclusionReason},
+};
+
+/// Maps excluded from the pool, as of the last health check.
+#[derive(Serialize, Clone, Default)]
+pub struct HealthReport {
+    pub checked_at: Option<DateTime<Utc>>,
+    pub excluded: Vec<ExcludedMap>,
+}
+
+static REPORT: Lazy<RwLock<HealthReport>> = Lazy::new(Default::default);
+
+pub fn report() -> HealthReport {
+    REPORT.read().clone()
+}
+
+/// Periodically check the maps of the pool, flag the ones that are broken and optionally remove
+/// them from the mapcache. Flagged maps are never drawn for matches.
+pub fn start_health_task() {
+    let interval = config::get_integer("maps.health_interval").unwrap_or(60);
+    if interval <= 0 {
+        info!("map health checks are disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let tmx = MapIngestion::new(
+            BASE,
+            config::get_integer("network.tmx_page_size").unwrap_or(100) as u32,
+        );
+        loop {
+            check_maps(&tmx).await;
+            refresh_report().await;
+            sleep(Duration::from_secs(interval as u64 * 60)).await;
+        }
+    });
+}
+
+async fn refresh_report() {
+    match health::get_excluded_maps().await {
+        Ok(excluded) => {
+            *REPORT.write() = HealthReport {
+                checked_at: Some(Utc::now()),
+                excluded,
+            }
+        }
+        Err(e) => error!("failed to load map health report: {}", e),
+    }
+}
+
+async fn check_maps(tmx: &MapIngestion) {
+    match health::flag_invalid_maps().await {
+        Ok(0) => (),
+        Ok(flagged) => info!("flagged {} maps with unusable data", flagged),
+        Err(e) => error!("failed to check cached maps: {}", e),
+    }
+
+    let max_age = TimeDelta::days(config::get_integer("maps.health_max_age").unwrap_or(30));
+    let batch = config::get_integer("maps.health_batch").unwrap_or(50) as u32;
+    let maps = match health::get_unchecked_maps(Utc::now() - max_age, batch).await {
+        Ok(maps) if maps.is_empty() => return,
+        Ok(maps) => maps,
+        Err(e) => {
+            error!("failed to list maps to check: {}", e);
+            return;
+        }
+    };
+
+    let ids: Vec<i32> = maps.iter().map(|(tmxid, _)| *tmxid).collect();
+    let on_tmx = match tmx.existing_maps(&ids).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            error!("failed to check maps on TMX: {}", e);
+            return;
+        }
+    };
+    let uids: Vec<String> = maps.iter().map(|(_, uid)| uid.clone()).collect();
+    let on_nadeo = match integrations::NADEOSERVICES_CLIENT
+        .wait()
+        .core_get_maps(&uids)
+        .await
+    {
+        Ok(existing) => existing,
+        Err(e) => {
+            error!("failed to check maps on Nadeo services: {}", e);
+            return;
+        }
+    };
+
+    let missing: Vec<(i32, ExclusionReason)> = maps
+        .iter()
+        .filter_map(|(tmxid, uid)| {
+            if !on_tmx.contains(tmxid) {
+                Some((*tmxid, ExclusionReason::DeletedFromTmx))
+            } else if !on_nadeo.iter().any(|map| &map.map_uid == uid) {
+                Some((*tmxid, ExclusionReason::DeletedFromNadeo))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let checked: Vec<i32> = ids
+        .iter()
+        .filter(|tmxid| !missing.iter().any(|(missing_id, _)| missing_id == *tmxid))
+        .copied()
+        .collect();
+    let max_misses = config::get_integer("maps.health_misses").unwrap_or(3);
+    match health::save_check_results(checked, missing, max_misses).await {
+        Ok(0) => (),
+        Ok(flagged) => info!(
+            "flagged {} of {} checked maps as deleted",
+            flagged,
+            ids.len()
+        ),
+        Err(e) => error!("failed to save map health checks: {}", e),
+    }
+
+    if config::get_boolean("maps.health_prune").unwrap_or(false) {
+        match health::prune_flagged_maps().await {
+            Ok(0) => (),
+            Ok(pruned) => info!("pruned {} broken maps from the mapcache", pruned),
+            Err(e) => error!("failed to prune broken maps: {}", e),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Boards made of maps picked by the host
+
+use std::collections::HashSet;
+
+use futures::executor::block_on;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use once_cell::sync::Lazy;
+use sqlx::FromRow;
+use tracing::error;
+
+use crate::{
+    config,
+    core::models::map::GameMap,
+    datatypes::CustomMap,
+    integrations::tmexchange::{ingestion::MapIngestion, BASE},
+    orm::mapcache::{self, ingestion, record::MapRecord},
+};
+
+use super::{is_blacklisted, placeholders, LoadRequest, LoadedMaps, MapProvider};
+
+static TMX: Lazy<MapIngestion> = Lazy::new(|| {
+    MapIngestion::new(
+        BASE,
+        config::get_integer("network.tmx_page_size").unwrap_or(100) as u32,
+    )
+});
+
+/// The UID of a picked map, if it was picked by UID. The client sends an empty string when unset.
+pub fn picked_uid(pick: &CustomMap) -> Option<&str> {
+    pick.uid.as_deref().filter(|uid| !uid.is_empty())
+}
+
+/// The TMX id of a picked map, if it was picked by id. The client sends 0 when unset.
+pub fn picked_tmxid(pick: &CustomMap) -> Option<i32> {
+    pick.tmxid.filter(|id| *id > 0).map(|id| id as i32)
+}
+
+/// The index of the cell a picked map is placed on, if any.
+pub fn picked_cell(pick: &CustomMap) -> Option<usize> {
+    pick.position
+        .filter(|position| *position > 0)
+        .map(|position| position as usize - 1)
+}
+
+fn pick_matches(pick: &CustomMap, map: &GameMap) -> bool {
+    match map {
+        GameMap::TMX(record) => {
+            picked_uid(pick) == Some(record.uid.as_str())
+                || picked_tmxid(pick) == Some(record.tmxid)
+        }
+        _ => false,
+    }
+}
+
+fn pick_name(pick: &CustomMap) -> String {
+    match (picked_uid(pick), picked_tmxid(pick)) {
+        (Some(uid), _) => uid.to_owned(),
+        (None, Some(tmxid)) => format!("#{}", tmxid),
+        (None, None) => "?".to_owned(),
+    }
+}
+
+/// Put the maps in the order of the board: maps placed on a cell first go there, the others fill
+/// the remaining cells in the order they were picked. Maps beyond the board are kept as spares.
+pub fn arrange_board(mut maps: Vec<GameMap>, picks: &[CustomMap], cells: usize) -> Vec<GameMap> {
+    let mut slots: Vec<Option<GameMap>> = vec![None; cells];
+    let mut unplaced = Vec::new();
+    for pick in picks {
+        let Some(index) = maps.iter().position(|map| pick_matches(pick, map)) else {
+            continue;
+        };
+        let map = maps.remove(index);
+        match picked_cell(pick) {
+            Some(cell) if cell < cells && slots[cell].is_none() => slots[cell] = Some(map),
+            _ => unplaced.push(map),
+        }
+    }
+    // maps that matched no pick stay after the picked ones
+    unplaced.extend(maps);
+
+    let mut unplaced = unplaced.into_iter();
+    let mut board = Vec::new();
+    for slot in slots {
+        match slot.or_else(|| unplaced.next()) {
+            Some(map) => board.push(map),
+            None => break,
+        }
+    }
+    board.extend(unplaced);
+    board
+}
+
+async fn cache_load_picks(ids: Vec<i32>, uids: Vec<String>) -> Result<Vec<MapRecord>, sqlx::Error> {
+    let mut conditions = Vec::new();
+    if !ids.is_empty() {
+        conditions.push(format!("tmxid IN ({})", placeholders(ids.len())));
+    }
+    if !uids.is_empty() {
+        conditions.push(format!("uid IN ({})", placeholders(uids.len())));
+    }
+    if conditions.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = format!("SELECT * FROM maps WHERE {}", conditions.join(" OR "));
+    mapcache::execute(move |mut conn| {
+        let query = ids
+            .iter()
+            .fold(sqlx::query(&sql), |query, id| query.bind(id));
+        let query = uids.iter().fold(query, |query, uid| query.bind(uid));
+        block_on(query.fetch_all(&mut *conn)).map(|v| {
+            v.iter()
+                .map(|r| MapRecord::from_row(r).expect("MapRecord from_row failed"))
+                .collect()
+        })
+    })
+    .await
+}
+
+/// Find the picked maps in the mapcache, and on TMX for the ones that are not cached.
+async fn resolve_picks(picks: &[CustomMap]) -> Result<Vec<MapRecord>, anyhow::Error> {
+    let ids: Vec<i32> = picks.iter().filter_map(picked_tmxid).collect();
+    let uids: Vec<String> = picks
+        .iter()
+        .filter_map(picked_uid)
+        .map(str::to_owned)
+        .collect();
+    let mut maps = cache_load_picks(ids.clone(), uids.clone()).await?;
+
+    let missing_ids: Vec<i32> = ids
+        .into_iter()
+        .filter(|id| !maps.iter().any(|map| map.tmxid == *id))
+        .collect();
+    let missing_uids: Vec<String> = uids
+        .into_iter()
+        .filter(|uid| !maps.iter().any(|map| &map.uid == uid))
+        .collect();
+    if !missing_ids.is_empty() || !missing_uids.is_empty() {
+        let fetched = TMX.get_maps(&missing_ids, &missing_uids).await?;
+        if let Err(e) = ingestion::save_maps(fetched.clone()).await {
+            error!("failed to cache picked maps: {}", e);
+        }
+        maps.extend(fetched);
+    }
+    Ok(maps)
+}
+
+/// Maps picked one by one by the host, in the order they were picked.
+pub struct CustomProvider;
+
+impl MapProvider for CustomProvider {
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        async move {
+            let picks = &request.config.custom_maps;
+            // picks were checked against the board when the configuration was set
+            let mut warnings = Vec::new();
+            let resolved: Vec<GameMap> = resolve_picks(picks)
+                .await?
+                .into_iter()
+                .map(GameMap::TMX)
+                .collect();
+
+            let mut seen = HashSet::new();
+            let mut maps = Vec::new();
+            for pick in picks {
+                match resolved.iter().find(|map| pick_matches(pick, map)) {
+                    Some(map) if is_blacklisted(map) => warnings.push(format!(
+                        "map {} is blacklisted on this server",
+                        pick_name(pick)
+                    )),
+                    Some(map) => {
+                        if seen.insert(map.sort_key()) {
+                            maps.push(map.clone());
+                        }
+                    }
+                    None => warnings.push(format!("map {} could not be found", pick_name(pick))),
+                }
+            }
+            Ok(LoadedMaps { maps, warnings })
+        }
+        .boxed()
+    }
+
+    fn mixable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(tmxid: i32) -> GameMap {
+        GameMap::TMX(MapRecord::test(tmxid))
+    }
+
+    fn pick(tmxid: u32, position: Option<u32>) -> CustomMap {
+        CustomMap {
+            uid: Some(String::new()),
+            tmxid: Some(tmxid),
+            position,
+        }
+    }
+
+    fn ids(maps: &[GameMap]) -> Vec<i32> {
+        maps.iter()
+            .map(|map| match map {
+                GameMap::TMX(record) => record.tmxid,
+                _ => 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn board_keeps_picked_order_and_cells() {
+        let picks = vec![
+            pick(1, None),
+            pick(2, Some(4)),
+            pick(3, None),
+            pick(4, Some(1)),
+            pick(5, None),
+        ];
+        // maps come back from the cache and TMX in any order
+        let maps = vec![map(5), map(3), map(1), map(4), map(2)];
+        assert_eq!(ids(&arrange_board(maps, &picks, 4)), vec![4, 1, 3, 2, 5]);
+    }
+}
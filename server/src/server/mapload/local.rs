@@ -0,0 +1,182 @@
+// Load maps from a manifest on the server, for events played without access to TMX
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::{
+    config,
+    core::{models::map::GameMap, util::rng},
+    orm::mapcache::record::MapRecord,
+};
+
+use super::{LoadRequest, LoadedMaps, MapProvider, MaploadResult};
+
+#[derive(Deserialize)]
+struct Manifest {
+    maps: Vec<LocalMap>,
+}
+
+/// A map listed in the local manifest.
+#[derive(Deserialize, Debug)]
+pub struct LocalMap {
+    pub uid: String,
+    pub name: String,
+    pub author: String,
+    pub author_time: i32,
+    pub gold_time: i32,
+    pub silver_time: i32,
+    pub bronze_time: i32,
+    pub tmxid: Option<i32>,
+    pub webservices_id: Option<String>,
+    pub wr_time: Option<i32>,
+    pub url: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<i32>,
+    pub style: Option<String>,
+}
+
+impl LocalMap {
+    /// Convert to a map record. Maps that are not on TMX get a negative id so they can't
+    /// be mistaken for a TMX map, and must have a URL for players to download them from.
+    fn into_record(self, index: usize) -> Result<MapRecord, anyhow::Error> {
+        if self.tmxid.is_none() && self.url.is_none() {
+            return Err(anyhow!(
+                "map '{}' has neither a tmxid nor a url to download it from",
+                self.name
+            ));
+        }
+        Ok(MapRecord {
+            tmxid: self.tmxid.unwrap_or(-(index as i32) - 1),
+            uid: self.uid,
+            webservices_id: self.webservices_id,
+            userid: 0,
+            username: self.author,
+            gbx_name: self.name.clone(),
+            track_name: self.name,
+            wr_time: self.wr_time,
+            wr_fetched_at: None,
+            author_time: self.author_time,
+            gold_time: self.gold_time,
+            silver_time: self.silver_time,
+            bronze_time: self.bronze_time,
+            uploaded_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+            tags: self
+                .tags
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(","),
+            style: self.style,
+            download_url: self.url,
+        })
+    }
+}
+
+/// Parse a manifest, in JSON if the path ends with `.json` and in TOML otherwise.
+pub fn parse_manifest(path: &Path, contents: &str) -> Result<Vec<MapRecord>, anyhow::Error> {
+    let manifest: Manifest = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(contents)?,
+        _ => toml::from_str(contents)?,
+    };
+    manifest
+        .maps
+        .into_iter()
+        .enumerate()
+        .map(|(i, map)| map.into_record(i))
+        .collect()
+}
+
+async fn read_manifest() -> Result<Vec<MapRecord>, anyhow::Error> {
+    let path = config::get_string("maps.local_manifest")
+        .ok_or(anyhow!("no local map manifest configured"))?;
+    let contents = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| anyhow!("could not read {}: {}", path, e))?;
+    parse_manifest(Path::new(&path), &contents)
+}
+
+/// Maps listed in the local manifest. The manifest is read again on every load, so it can be
+/// edited while the server runs.
+pub struct LocalProvider;
+
+impl MapProvider for LocalProvider {
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        async move {
+            let mut maps = read_manifest().await?;
+            maps.shuffle(&mut rng::from_optional_seed(request.seed));
+            maps.truncate(request.count as usize);
+            Ok(maps
+                .into_iter()
+                .map(GameMap::TMX)
+                .collect::<Vec<_>>()
+                .into())
+        }
+        .boxed()
+    }
+
+    /// World records can't be fetched offline, only maps with a time in the manifest are kept.
+    fn refresh(&self, maps: Vec<GameMap>) -> BoxFuture<'static, MaploadResult> {
+        async move {
+            Ok(maps
+                .into_iter()
+                .filter(|map| matches!(map, GameMap::TMX(record) if record.wr_time.is_some()))
+                .collect())
+        }
+        .boxed()
+    }
+
+    /// Records can't be checked offline, all maps are assumed to be undiscovered.
+    fn verify(
+        &self,
+        maps: Vec<GameMap>,
+        _account_ids: Vec<String>,
+    ) -> BoxFuture<'static, MaploadResult> {
+        async move { Ok(maps) }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../../data/local_maps.example.toml");
+
+    #[test]
+    fn example_manifest_is_parsed() {
+        let maps = parse_manifest(Path::new("local_maps.toml"), EXAMPLE).unwrap();
+        assert_eq!(maps.len(), 2);
+        assert_eq!(maps[0].tmxid, 184512);
+        assert_eq!(maps[0].tags, "3,37");
+        assert_eq!(maps[0].wr_time, Some(39875));
+        assert_eq!(maps[0].download_url, None);
+        assert_eq!(maps[1].tmxid, -2);
+        assert_eq!(maps[1].username, "mudkip");
+        assert_eq!(maps[1].webservices_id, None);
+        assert_eq!(
+            maps[1].download_url.as_deref(),
+            Some("http://192.168.1.10:8000/ShortDirt.Map.Gbx")
+        );
+    }
+
+    #[test]
+    fn json_manifest_is_parsed() {
+        let json = r#"{"maps": [{"uid": "abc", "name": "Flat", "author": "x", "author_time": 10000, "gold_time": 11000, "silver_time": 12000, "bronze_time": 15000, "tmxid": 12}]}"#;
+        let maps = parse_manifest(Path::new("maps.json"), json).unwrap();
+        assert_eq!(maps.len(), 1);
+        assert_eq!(maps[0].track_name, "Flat");
+        assert_eq!(maps[0].tags, "");
+    }
+
+    #[test]
+    fn maps_without_download_source_are_rejected() {
+        let json = r#"{"maps": [{"uid": "abc", "name": "Flat", "author": "x", "author_time": 10000, "gold_time": 11000, "silver_time": 12000, "bronze_time": 15000}]}"#;
+        assert!(parse_manifest(Path::new("maps.json"), json).is_err());
+    }
+}
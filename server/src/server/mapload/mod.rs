@@ -0,0 +1,613 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::executor::block_on;
+use futures::Future;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::seq::SliceRandom;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{FromRow, Row, Sqlite};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::core::models::map::GameMap;
+use crate::core::room::GameRoom;
+use crate::core::util::rng;
+use crate::datatypes::{Gamemode, MapFilters, MatchConfiguration, Medal};
+use crate::integrations::tmexchange::{MappackFetch, MappackLoader, MappackValidator};
+use crate::server::shutdown;
+use crate::store::blacklist::{self, BlacklistedMap};
+use crate::{config, integrations};
+use crate::{
+    core::directory::Shared,
+    orm::mapcache::{
+        self,
+        mappack::{self, CachedMappack},
+        record::MapRecord,
+        worldrecord,
+    },
+};
+
+pub use custom::{arrange_board, picked_cell, picked_tmxid, picked_uid};
+pub use sources::{register_provider, LoadRequest, LoadedMaps, MapProvider};
+
+mod custom;
+mod local;
+mod sources;
+
+pub type MaploadResult = Result<Vec<GameMap>, anyhow::Error>;
+static MAPPACK_LOADER: Lazy<MappackLoader> = Lazy::new(|| MappackLoader::new());
+static BLACKLIST: Lazy<RwLock<HashSet<i32>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// Matches maps having the bound tag id in their comma separated list of tags.
+const TAG_CONDITION: &str = "(',' || tags || ',') LIKE ('%,' || ? || ',%')";
+
+/// A value bound to a parameter of a map query.
+#[derive(Debug, Clone, PartialEq)]
+enum Argument {
+    Integer(i64),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Conditions restricting the maps drawn from the cache, along with their arguments.
+#[derive(Debug, Clone, Default)]
+struct MapQuery {
+    conditions: Vec<String>,
+    arguments: Vec<Argument>,
+}
+
+impl MapQuery {
+    /// Create the conditions applied to every map drawn from the cache: the server's author time
+    /// limit, the room's filters, maps flagged by health checks and the server blacklist.
+    fn new(filters: &MapFilters) -> Self {
+        let mut query = Self::default();
+        let server_max = config::get_integer("maps.max_author_millis").unwrap_or(180000);
+        // the client sends zeroes and empty strings for filters that are not set
+        let max_author_time = filters
+            .max_author_time
+            .filter(|max| *max > 0)
+            .map_or(server_max, |max| server_max.min(max as i64));
+        query.condition("author_time <= ?", [Argument::Integer(max_author_time)]);
+        if let Some(min) = filters.min_author_time.filter(|min| *min > 0) {
+            query.condition("author_time >= ?", [Argument::Integer(min as i64)]);
+        }
+
+        if !filters.include_tags.is_empty() {
+            let any_tag = vec![TAG_CONDITION; filters.include_tags.len()].join(" OR ");
+            query.condition(
+                &format!("({})", any_tag),
+                filters
+                    .include_tags
+                    .iter()
+                    .map(|tag| Argument::Integer(*tag as i64)),
+            );
+        }
+        for tag in &filters.exclude_tags {
+            query.condition(
+                &format!("NOT {}", TAG_CONDITION),
+                [Argument::Integer(*tag as i64)],
+            );
+        }
+
+        if let Some(style) = filters.style.as_ref().filter(|style| !style.is_empty()) {
+            query.condition("style = ? COLLATE NOCASE", [Argument::Text(style.clone())]);
+        }
+        if let Some(after) = filters.uploaded_after.filter(|after| *after > 0) {
+            query.condition(
+                "CAST(strftime('%s', uploaded_at) AS INTEGER) >= ?",
+                [Argument::Integer(after as i64)],
+            );
+        }
+        if let Some(before) = filters.uploaded_before.filter(|before| *before > 0) {
+            query.condition(
+                "CAST(strftime('%s', uploaded_at) AS INTEGER) < ?",
+                [Argument::Integer(before as i64)],
+            );
+        }
+
+        if !filters.excluded_authors.is_empty() {
+            query.condition(
+                &format!(
+                    "username COLLATE NOCASE NOT IN ({})",
+                    placeholders(filters.excluded_authors.len())
+                ),
+                filters
+                    .excluded_authors
+                    .iter()
+                    .map(|author| Argument::Text(author.clone())),
+            );
+        }
+
+        query.condition("tmxid NOT IN (SELECT tmxid FROM map_health)", []);
+        query.condition("tmxid NOT IN (SELECT tmxid FROM blacklist)", []);
+        query
+    }
+
+    /// Only draw maps whose world record time was fetched recently enough to be trusted.
+    fn require_world_record(&mut self) {
+        self.condition(
+            "wr_time IS NOT NULL AND wr_fetched_at >= ?",
+            [Argument::Timestamp(world_record_freshness_limit())],
+        );
+    }
+
+    fn condition(&mut self, sql: &str, arguments: impl IntoIterator<Item = Argument>) {
+        self.conditions.push(sql.to_owned());
+        self.arguments.extend(arguments);
+    }
+
+    fn where_clause(&self) -> String {
+        self.conditions.join(" AND ")
+    }
+
+    fn bind<'q>(
+        &'q self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        self.arguments
+            .iter()
+            .fold(query, |query, argument| match argument {
+                Argument::Integer(value) => query.bind(*value),
+                Argument::Text(value) => query.bind(value.as_str()),
+                Argument::Timestamp(value) => query.bind(*value),
+            })
+    }
+}
+
+/// World record times fetched before this date are outdated.
+fn world_record_freshness_limit() -> DateTime<Utc> {
+    Utc::now() - TimeDelta::days(config::get_integer("maps.wr_max_age").unwrap_or(7))
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Load the server blacklist from the store and copy it to the mapcache. Only call this once,
+/// at startup.
+pub async fn load_blacklist() {
+    let tmxids: Vec<i32> = match blacklist::get_blacklist().await {
+        Ok(maps) => maps.into_iter().map(|map| map.tmxid).collect(),
+        Err(e) => {
+            error!("failed to load the map blacklist: {}", e);
+            return;
+        }
+    };
+    *BLACKLIST.write() = tmxids.iter().copied().collect();
+    if let Err(e) = mapcache::blacklist::set_blacklist(tmxids).await {
+        error!("failed to copy the map blacklist to the mapcache: {}", e);
+    }
+}
+
+/// Exclude a map from all future map selections and save it to the store.
+pub fn blacklist_map(map: BlacklistedMap) {
+    BLACKLIST.write().insert(map.tmxid);
+    let write = shutdown::track_write();
+    tokio::spawn(async move {
+        let _write = write;
+        if let Err(e) = mapcache::blacklist::add_map(map.tmxid).await {
+            error!(
+                "failed to add blacklisted map {} to the mapcache: {}",
+                map.tmxid, e
+            );
+        }
+        if let Err(e) = blacklist::add_map(&map).await {
+            error!("failed to save blacklisted map {}: {}", map.tmxid, e);
+        }
+    });
+}
+
+pub fn is_blacklisted(map: &GameMap) -> bool {
+    match map {
+        GameMap::TMX(record) => BLACKLIST.read().contains(&record.tmxid),
+        _ => false,
+    }
+}
+
+pub fn load_maps(room: Shared<GameRoom>, config: &MatchConfiguration, userdata: u32) {
+    tokio::spawn(fetch_and_load_source(
+        room,
+        LoadRequest::new(config),
+        userdata,
+    ));
+}
+
+pub fn reload_maps(
+    room: Shared<GameRoom>,
+    config: &MatchConfiguration,
+    maps: Vec<GameMap>,
+    userdata: u32,
+) {
+    match sources::get_provider(config.selection) {
+        Ok(provider) => {
+            tokio::spawn(fetch_and_load(room, provider.refresh(maps), userdata));
+        }
+        Err(e) => error!("maps loading error: {}", e),
+    }
+}
+
+pub fn verify_map_records(
+    room: Shared<GameRoom>,
+    config: &MatchConfiguration,
+    maps: Vec<GameMap>,
+    account_ids: Vec<String>,
+    userdata: u32,
+) {
+    match sources::get_provider(config.selection) {
+        Ok(provider) => {
+            tokio::spawn(fetch_and_load(
+                room,
+                provider.verify(maps, account_ids),
+                userdata,
+            ));
+        }
+        Err(e) => error!("maps loading error: {}", e),
+    }
+}
+
+pub async fn gather_maps(config: &MatchConfiguration) -> MaploadResult {
+    sources::load(LoadRequest::new(config))
+        .await
+        .map(|loaded| loaded.maps)
+}
+
+/// The number of maps to load for a match, including spares for rerolls and replacements.
+pub fn map_count(config: &MatchConfiguration) -> u32 {
+    let mut number_of_grids = 1;
+    if config.rerolls || config.mode == Gamemode::Frenzy || config.discovery {
+        number_of_grids += 1;
+    }
+    if config.avoid_recent_maps {
+        // load spare maps to replace the ones that were played recently
+        number_of_grids += 1;
+    }
+    config.grid_size * config.grid_size * number_of_grids
+}
+
+/// The conditions on maps drawn from the cache for a match.
+fn map_query(config: &MatchConfiguration) -> MapQuery {
+    let mut query = MapQuery::new(&config.filters);
+    if config.target_medal == Medal::WR {
+        query.require_world_record();
+    }
+    query
+}
+
+async fn fetch_and_load<F: Future<Output = MaploadResult>>(
+    room: Shared<GameRoom>,
+    fut: F,
+    userdata: u32,
+) {
+    match fut.await {
+        Ok(maps) => {
+            if let Some(room) = room.upgrade() {
+                room.lock().maps_load_callback(maps, userdata);
+            }
+        }
+        Err(e) => {
+            error!("maps loading error: {}", e);
+        }
+    }
+}
+
+async fn fetch_and_load_source(room: Shared<GameRoom>, request: LoadRequest, userdata: u32) {
+    let loaded = match sources::load(request).await {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!("maps loading error: {}", e);
+            return;
+        }
+    };
+    for warning in &loaded.warnings {
+        warn!("maps loading: {}", warning);
+    }
+    if let Some(room) = room.upgrade() {
+        let mut lock = room.lock();
+        lock.set_load_warnings(loaded.warnings, userdata);
+        lock.maps_load_callback(loaded.maps, userdata);
+    }
+}
+
+async fn cache_load_mxrandom(count: u32, filters: MapQuery) -> MaploadResult {
+    mapcache::execute(move |mut conn| {
+        let sql = format!(
+            "SELECT * FROM maps WHERE tmxid IN (SELECT tmxid FROM maps WHERE {} ORDER BY RANDOM() LIMIT ?)",
+            filters.where_clause()
+        );
+        let query = filters.bind(sqlx::query(&sql)).bind(count as i32);
+        block_on(query.fetch_all(&mut *conn)).map(|v| {
+            v.iter()
+                .map(|r| GameMap::TMX(MapRecord::from_row(r).expect("MapRecord from_row failed")))
+                .collect()
+        })
+    })
+    .await
+    .map_err(anyhow::Error::from)
+}
+
+async fn cache_load_tag(count: u32, tag: i32, mut filters: MapQuery) -> MaploadResult {
+    filters.condition(TAG_CONDITION, [Argument::Integer(tag as i64)]);
+    cache_load_mxrandom(count, filters).await
+}
+
+/// Draw maps from the cache in an order that only depends on the seed, so the same seed
+/// always gives the same selection as long as the cache contents don't change.
+pub async fn cache_load_seeded(count: u32, seed: u64) -> MaploadResult {
+    let filters = MapQuery::new(&MapFilters::default());
+    let candidates: Vec<i32> = mapcache::execute(move |mut conn| {
+        let sql = format!(
+            "SELECT tmxid FROM maps WHERE {} ORDER BY tmxid",
+            filters.where_clause()
+        );
+        let query = filters.bind(sqlx::query(&sql));
+        block_on(query.fetch_all(&mut *conn)).map(|v| v.iter().map(|r| r.get(0)).collect())
+    })
+    .await?;
+
+    cache_load_by_ids(seeded_selection(candidates, count, seed)).await
+}
+
+/// Pick `count` ids out of the candidates in a random order driven by the seed.
+pub fn seeded_selection(mut candidates: Vec<i32>, count: u32, seed: u64) -> Vec<i32> {
+    candidates.shuffle(&mut rng::from_seed(seed));
+    candidates.truncate(count as usize);
+    candidates
+}
+
+/// Load specific maps from the cache, keeping the order of the given ids.
+/// Ids of maps that are not in the cache are skipped.
+pub async fn cache_load_by_ids(ids: Vec<i32>) -> MaploadResult {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = format!(
+        "SELECT * FROM maps WHERE tmxid IN ({})",
+        placeholders(ids.len())
+    );
+    let order = ids.clone();
+    let mut maps: Vec<MapRecord> = mapcache::execute(move |mut conn| {
+        let query = ids
+            .iter()
+            .fold(sqlx::query(&sql), |query, id| query.bind(id));
+        block_on(query.fetch_all(&mut *conn)).map(|v| {
+            v.iter()
+                .map(|r| MapRecord::from_row(r).expect("MapRecord from_row failed"))
+                .collect()
+        })
+    })
+    .await?;
+
+    maps.sort_by_key(|map| order.iter().position(|id| *id == map.tmxid));
+    Ok(maps.into_iter().map(GameMap::TMX).collect())
+}
+
+/// Load the maps of a mappack, using the mapcache copy if it was fetched recently enough.
+/// An outdated copy is still used when TMX cannot be reached.
+async fn network_load_mappack(mappack_id: u32) -> MaploadResult {
+    let cached = mappack::get_mappack(mappack_id)
+        .await
+        .inspect_err(|e| error!("failed to read cached mappack {}: {}", mappack_id, e))
+        .unwrap_or_default();
+    let ttl = TimeDelta::seconds(config::get_integer("maps.mappack_ttl").unwrap_or(3600));
+
+    let maps = match cached {
+        Some(cached) if Utc::now() - cached.fetched_at < ttl => cached.maps,
+        Some(cached) => match revalidate_mappack(mappack_id, &cached).await {
+            Ok(Some(maps)) => maps,
+            Ok(None) => cached.maps,
+            Err(e) => {
+                warn!(
+                    "could not fetch mappack {}, using cached maps instead: {}",
+                    mappack_id, e
+                );
+                cached.maps
+            }
+        },
+        None => fetch_mappack(mappack_id).await?,
+    };
+    Ok(maps
+        .into_iter()
+        .map(GameMap::TMX)
+        .filter(|map| !is_blacklisted(map))
+        .collect())
+}
+
+/// Fetch the maps of a mappack from TMX and update its copy in the mapcache.
+pub async fn fetch_mappack(mappack_id: u32) -> Result<Vec<MapRecord>, anyhow::Error> {
+    let fetch = MAPPACK_LOADER
+        .get_mappack_tracks(&mappack_id.to_string(), &MappackValidator::default())
+        .await?;
+    match fetch {
+        MappackFetch::Updated { maps, validator } => {
+            Ok(save_fetched_mappack(mappack_id, maps, validator).await)
+        }
+        MappackFetch::NotModified => Err(anyhow!("unexpected unmodified mappack response")),
+    }
+}
+
+/// Ask TMX whether a cached mappack changed, and fetch its maps again if it did.
+/// Returns `None` when the cached copy is still up to date.
+async fn revalidate_mappack(
+    mappack_id: u32,
+    cached: &CachedMappack,
+) -> Result<Option<Vec<MapRecord>>, anyhow::Error> {
+    let validator = MappackValidator {
+        etag: cached.etag.clone(),
+        last_modified: cached.last_modified.clone(),
+    };
+    let fetch = MAPPACK_LOADER
+        .get_mappack_tracks(&mappack_id.to_string(), &validator)
+        .await?;
+    match fetch {
+        MappackFetch::Updated { maps, validator } => Ok(Some(
+            save_fetched_mappack(mappack_id, maps, validator).await,
+        )),
+        MappackFetch::NotModified => {
+            if let Err(e) = mappack::touch_mappack(mappack_id).await {
+                error!("failed to update cached mappack {}: {}", mappack_id, e);
+            }
+            Ok(None)
+        }
+    }
+}
+
+async fn save_fetched_mappack(
+    mappack_id: u32,
+    maps: Vec<GameMap>,
+    validator: MappackValidator,
+) -> Vec<MapRecord> {
+    let maps: Vec<MapRecord> = maps
+        .into_iter()
+        .filter_map(|map| match map {
+            GameMap::TMX(record) => Some(record),
+            _ => None,
+        })
+        .collect();
+    if let Err(e) = mappack::save_mappack(
+        mappack_id,
+        maps.clone(),
+        validator.etag,
+        validator.last_modified,
+    )
+    .await
+    {
+        error!("failed to cache mappack {}: {}", mappack_id, e);
+    }
+    maps
+}
+
+async fn save_world_record(tmxid: i32, wr_time: Option<i32>) {
+    if let Err(e) = worldrecord::save_world_record(tmxid, wr_time).await {
+        error!("failed to save world record of map {}: {}", tmxid, e);
+    }
+}
+
+/// Periodically fetch the world records of maps in the pool that are missing or outdated,
+/// so that matches targeting world records don't have to wait for them.
+pub fn start_world_record_task() {
+    let interval = config::get_integer("maps.wr_refresh_interval").unwrap_or(10);
+    if interval <= 0 {
+        info!("world record refresh is disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(interval as u64 * 60)).await;
+            let outdated = worldrecord::get_outdated_world_records(
+                config::get_integer("maps.max_author_millis").unwrap_or(180000),
+                // refresh records a day before they are no longer considered fresh
+                world_record_freshness_limit() + TimeDelta::days(1),
+                config::get_integer("maps.wr_refresh_batch").unwrap_or(50) as u32,
+            )
+            .await;
+            let maps = match outdated {
+                Ok(maps) => maps,
+                Err(e) => {
+                    error!("failed to list outdated world records: {}", e);
+                    continue;
+                }
+            };
+
+            for (tmxid, uid) in maps {
+                match integrations::NADEOSERVICES_CLIENT
+                    .wait()
+                    .live_get_map_leaderboard(&uid)
+                    .await
+                {
+                    Ok(records) => {
+                        save_world_record(tmxid, records.first().map(|entry| entry.score)).await
+                    }
+                    Err(e) => error!("failed to fetch world record of map {}: {}", tmxid, e),
+                }
+            }
+        }
+    });
+}
+
+/// Keep the maps that have a world record, fetching it again when the cached time is outdated.
+pub async fn maps_get_world_record(maps: Vec<GameMap>) -> MaploadResult {
+    let freshness_limit = world_record_freshness_limit();
+    let mut valid_maps = Vec::new();
+    for map in maps {
+        let valid_map = match map {
+            GameMap::TMX(ref mxmap) => match mxmap.wr_time {
+                Some(_)
+                    if mxmap
+                        .wr_fetched_at
+                        .is_some_and(|fetched_at| fetched_at >= freshness_limit) =>
+                {
+                    Some(map)
+                }
+                _ => {
+                    let world_record = match integrations::NADEOSERVICES_CLIENT
+                        .wait()
+                        .live_get_map_leaderboard(&mxmap.uid)
+                        .await
+                    {
+                        Ok(records) => {
+                            let world_record = records.first().map(|entry| entry.score);
+                            save_world_record(mxmap.tmxid, world_record).await;
+                            world_record
+                        }
+                        Err(e) => {
+                            error!("{}", e);
+                            None
+                        }
+                    };
+
+                    if world_record.is_some() {
+                        let mut new_map = mxmap.clone();
+                        new_map.wr_time = world_record;
+                        new_map.wr_fetched_at = Some(Utc::now());
+                        Some(GameMap::TMX(new_map))
+                    } else {
+                        None
+                    }
+                }
+            },
+            // campaign maps are not in the mapcache, they keep their place in mixed boards
+            GameMap::Campaign(_) => Some(map),
+        };
+
+        if let Some(map) = valid_map {
+            valid_maps.push(map);
+        }
+    }
+    Ok(valid_maps)
+}
+
+pub async fn maps_verify_discovery(maps: Vec<GameMap>, account_ids: Vec<String>) -> MaploadResult {
+    let mut valid_maps = Vec::new();
+    for map in maps {
+        let valid_map = match map {
+            GameMap::TMX(ref mxmap) => match mxmap.webservices_id {
+                Some(ref webservices_id) => {
+                    match integrations::NADEOSERVICES_CLIENT
+                        .wait()
+                        .core_get_map_records(webservices_id, &account_ids)
+                        .await
+                    {
+                        Ok(records) => records.first().is_none(),
+                        Err(e) => {
+                            error!("{}", e);
+                            false
+                        }
+                    }
+                }
+                None => false,
+            },
+            _ => false,
+        };
+
+        if valid_map {
+            valid_maps.push(map);
+        }
+    }
+    Ok(valid_maps)
+}
@@ -0,0 +1,484 @@
+// Map sources that rooms can draw their maps from
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::seq::SliceRandom;
+
+use crate::core::models::map::GameMap;
+use crate::core::util::rng;
+use crate::datatypes::{CampaignMap, MapMode, MapSource, MatchConfiguration};
+
+use super::custom::CustomProvider;
+use super::local::LocalProvider;
+use super::{
+    cache_load_mxrandom, cache_load_tag, map_count, map_query, maps_get_world_record,
+    maps_verify_discovery, network_load_mappack, MaploadResult,
+};
+
+/// A request for maps from a source, on behalf of a room.
+#[derive(Debug, Clone)]
+pub struct LoadRequest {
+    /// The configuration of the room, for its filters and target medal.
+    pub config: MatchConfiguration,
+    /// The selection to load maps from.
+    pub source: MapSource,
+    /// The number of maps wanted. Sources may give more or fewer.
+    pub count: u32,
+    /// The seed set by the host, so that sources drawing maps at random give the same maps.
+    pub seed: Option<u64>,
+}
+
+impl LoadRequest {
+    pub fn new(config: &MatchConfiguration) -> Self {
+        Self {
+            config: config.clone(),
+            source: MapSource {
+                selection: config.selection,
+                weight: 1,
+                mappack_id: config.mappack_id,
+                map_tag: config.map_tag,
+            },
+            count: map_count(config),
+            seed: rng::configured_seed(config.seed),
+        }
+    }
+
+    /// The same request, for a share of the maps from another source.
+    fn with_source(&self, source: MapSource, count: u32) -> Self {
+        Self {
+            config: self.config.clone(),
+            source,
+            count,
+            seed: self.seed,
+        }
+    }
+}
+
+/// Maps given by a source, with a warning for anything that kept it from giving all of them.
+#[derive(Debug, Default)]
+pub struct LoadedMaps {
+    pub maps: Vec<GameMap>,
+    pub warnings: Vec<String>,
+}
+
+impl From<Vec<GameMap>> for LoadedMaps {
+    fn from(maps: Vec<GameMap>) -> Self {
+        Self {
+            maps,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// A place maps are loaded from. Providers are registered for a `MapMode`, and the maps they
+/// give are checked again before a match starts with `refresh` and `verify`.
+pub trait MapProvider: Send + Sync {
+    /// A short description of the source, shown in load warnings.
+    fn name(&self, source: &MapSource) -> String {
+        format!("{:?}", source.selection)
+    }
+
+    /// Load maps for a room.
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>>;
+
+    /// Fetch the world records of maps that are missing one, dropping the maps without a record.
+    fn refresh(&self, maps: Vec<GameMap>) -> BoxFuture<'static, MaploadResult> {
+        maps_get_world_record(maps).boxed()
+    }
+
+    /// Keep only the maps that none of the given players have played.
+    fn verify(
+        &self,
+        maps: Vec<GameMap>,
+        account_ids: Vec<String>,
+    ) -> BoxFuture<'static, MaploadResult> {
+        maps_verify_discovery(maps, account_ids).boxed()
+    }
+
+    /// Whether the source can be mixed with others in a `MapMode::Mixed` board.
+    fn mixable(&self) -> bool {
+        true
+    }
+}
+
+/// The number of maps in each official campaign.
+const CAMPAIGN_MAP_COUNT: i32 = 25;
+
+type ProviderEntry = (MapMode, Arc<dyn MapProvider>);
+
+static PROVIDERS: Lazy<RwLock<Vec<ProviderEntry>>> = Lazy::new(|| {
+    RwLock::new(vec![
+        (MapMode::RandomTMX, Arc::new(RandomProvider)),
+        (MapMode::Tags, Arc::new(TagProvider)),
+        (MapMode::Mappack, Arc::new(MappackProvider)),
+        (MapMode::Campaign, Arc::new(CampaignProvider)),
+        (MapMode::Mixed, Arc::new(MixedProvider)),
+        (MapMode::Local, Arc::new(LocalProvider)),
+        (MapMode::Custom, Arc::new(CustomProvider)),
+    ])
+});
+
+/// Register the provider of a map selection, replacing the previous one if any.
+pub fn register_provider(selection: MapMode, provider: impl MapProvider + 'static) {
+    let mut providers = PROVIDERS.write();
+    providers.retain(|(mode, _)| *mode != selection);
+    providers.push((selection, Arc::new(provider)));
+}
+
+pub fn get_provider(selection: MapMode) -> Result<Arc<dyn MapProvider>, anyhow::Error> {
+    PROVIDERS
+        .read()
+        .iter()
+        .find(|(mode, _)| *mode == selection)
+        .map(|(_, provider)| provider.clone())
+        .ok_or(anyhow!("no map source available for {:?}", selection))
+}
+
+/// Load maps from the provider of the requested source.
+pub async fn load(request: LoadRequest) -> Result<LoadedMaps, anyhow::Error> {
+    get_provider(request.source.selection)?.load(request).await
+}
+
+/// Random maps from the mapcache.
+struct RandomProvider;
+
+impl MapProvider for RandomProvider {
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        cache_load_mxrandom(request.count, map_query(&request.config))
+            .map(|result| result.map(LoadedMaps::from))
+            .boxed()
+    }
+}
+
+/// Random maps from the mapcache having a given tag.
+struct TagProvider;
+
+impl MapProvider for TagProvider {
+    fn name(&self, source: &MapSource) -> String {
+        format!("Tag {}", source.map_tag.unwrap_or_default())
+    }
+
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        async move {
+            let tag = request
+                .source
+                .map_tag
+                .ok_or(anyhow!("no map tag selected"))?;
+            cache_load_tag(request.count, tag, map_query(&request.config))
+                .await
+                .map(LoadedMaps::from)
+        }
+        .boxed()
+    }
+}
+
+/// All maps of a TMX mappack.
+struct MappackProvider;
+
+impl MapProvider for MappackProvider {
+    fn name(&self, source: &MapSource) -> String {
+        format!("Mappack #{}", source.mappack_id.unwrap_or_default())
+    }
+
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        async move {
+            let mappack_id = request
+                .source
+                .mappack_id
+                .ok_or(anyhow!("no mappack selected"))?;
+            network_load_mappack(mappack_id).await.map(LoadedMaps::from)
+        }
+        .boxed()
+    }
+}
+
+/// Random maps of the official campaigns selected by the room.
+struct CampaignProvider;
+
+impl MapProvider for CampaignProvider {
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        async move {
+            let campaigns = request.config.campaign_selection.unwrap_or_default();
+            if campaigns.is_empty() {
+                return Err(anyhow!("no campaign selected"));
+            }
+            let mut maps: Vec<GameMap> = campaigns
+                .iter()
+                .flat_map(|campaign_id| {
+                    (0..CAMPAIGN_MAP_COUNT).map(|map| {
+                        GameMap::Campaign(CampaignMap {
+                            campaign_id: *campaign_id as i32,
+                            map,
+                        })
+                    })
+                })
+                .collect();
+            maps.shuffle(&mut rng::from_optional_seed(request.seed));
+            maps.truncate(request.count as usize);
+            Ok(LoadedMaps::from(maps))
+        }
+        .boxed()
+    }
+}
+
+/// Maps drawn from several sources, each giving its weighted share of the maps.
+struct MixedProvider;
+
+impl MapProvider for MixedProvider {
+    fn load(&self, request: LoadRequest) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+        load_mixed(request).map(Ok).boxed()
+    }
+
+    fn mixable(&self) -> bool {
+        false
+    }
+}
+
+/// Split a number of maps between sources in proportion to their weights.
+fn source_quotas(weights: &[u32], count: u32) -> Vec<u32> {
+    let total: u64 = weights.iter().map(|w| *w as u64).sum();
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+    let shares: Vec<u64> = weights.iter().map(|w| *w as u64 * count as u64).collect();
+    let mut quotas: Vec<u32> = shares.iter().map(|share| (share / total) as u32).collect();
+
+    // hand out the rounding leftovers to the sources with the largest remainders
+    let missing = count - quotas.iter().sum::<u32>();
+    let mut remainders: Vec<(usize, u64)> = shares
+        .iter()
+        .map(|share| share % total)
+        .enumerate()
+        .collect();
+    remainders.sort_by_key(|(_, remainder)| Reverse(*remainder));
+    for (i, _) in remainders.into_iter().take(missing as usize) {
+        quotas[i] += 1;
+    }
+    quotas
+}
+
+async fn load_source(request: LoadRequest) -> Result<LoadedMaps, anyhow::Error> {
+    let provider = get_provider(request.source.selection)?;
+    if !provider.mixable() {
+        return Err(anyhow!("this map selection cannot be mixed with others"));
+    }
+    provider.load(request).await
+}
+
+/// Maps drawn from one of the sources of a mixed board, with its shares of the board and spares.
+struct SourceDraw {
+    maps: Vec<GameMap>,
+    board_quota: usize,
+    spare_quota: usize,
+}
+
+/// Draw maps from the sources of the room. Each source gets its weighted share of the board cells,
+/// and separately of the spare maps kept for rerolls and replacements. Duplicates are removed, and
+/// sources that fall short are made up for with the leftovers of the other sources. A warning is
+/// given for each source that could not give its full share.
+async fn load_mixed(request: LoadRequest) -> LoadedMaps {
+    let sources = &request.config.map_sources;
+    if sources.is_empty() {
+        return LoadedMaps {
+            maps: Vec::new(),
+            warnings: vec!["no map sources selected".to_owned()],
+        };
+    }
+    let weights: Vec<u32> = sources.iter().map(|source| source.weight).collect();
+    let cells = request.config.grid_size * request.config.grid_size;
+    let board_count = request.count.min(cells);
+    let board_quotas = source_quotas(&weights, board_count);
+    let spare_quotas = source_quotas(&weights, request.count - board_count);
+
+    let mut rng = rng::from_optional_seed(request.seed);
+    let mut seen = HashSet::new();
+    let mut draws = Vec::new();
+    let mut warnings = Vec::new();
+    for ((source, board_quota), spare_quota) in sources.iter().zip(board_quotas).zip(spare_quotas) {
+        let quota = board_quota + spare_quota;
+        if quota == 0 {
+            continue;
+        }
+        let name = get_provider(source.selection)
+            .map(|provider| provider.name(source))
+            .unwrap_or_else(|_| format!("{:?}", source.selection));
+        let mut loaded = match load_source(request.with_source(source.clone(), quota)).await {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warnings.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        warnings.extend(
+            loaded
+                .warnings
+                .iter()
+                .map(|warning| format!("{}: {}", name, warning)),
+        );
+        loaded.maps.retain(|map| seen.insert(map.sort_key()));
+        loaded.maps.shuffle(&mut rng);
+
+        if loaded.maps.len() < quota as usize {
+            warnings.push(format!(
+                "{}: only {} of {} maps could be loaded",
+                name,
+                loaded.maps.len(),
+                quota
+            ));
+        }
+        draws.push(SourceDraw {
+            maps: loaded.maps,
+            board_quota: board_quota as usize,
+            spare_quota: spare_quota as usize,
+        });
+    }
+
+    LoadedMaps {
+        maps: mix_draws(
+            draws,
+            board_count as usize,
+            (request.count - board_count) as usize,
+        ),
+        warnings,
+    }
+}
+
+/// Put together the maps drawn from each source: the board comes first, followed by the spares.
+fn mix_draws(draws: Vec<SourceDraw>, board_count: usize, spare_count: usize) -> Vec<GameMap> {
+    let mut board = Vec::new();
+    let mut spares = Vec::new();
+    let mut leftovers = Vec::new();
+    for mut draw in draws {
+        let mut rest = draw.maps.split_off(draw.board_quota.min(draw.maps.len()));
+        leftovers.extend(rest.split_off(draw.spare_quota.min(rest.len())));
+        board.extend(draw.maps);
+        spares.extend(rest);
+    }
+
+    // the board is completed first, using the spares once there are no leftovers
+    let missing = board_count.saturating_sub(board.len());
+    let from_leftovers = missing.min(leftovers.len());
+    board.extend(leftovers.drain(..from_leftovers));
+    let from_spares = (missing - from_leftovers).min(spares.len());
+    board.extend(spares.drain(..from_spares));
+
+    spares.extend(leftovers);
+    spares.truncate(spare_count);
+    board.extend(spares);
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_quotas_follow_weights() {
+        assert_eq!(source_quotas(&[60, 40], 25), vec![15, 10]);
+        assert_eq!(source_quotas(&[1, 1, 1], 25), vec![9, 8, 8]);
+        assert_eq!(source_quotas(&[3, 0], 9), vec![9, 0]);
+        assert_eq!(source_quotas(&[0, 0], 9), vec![0, 0]);
+    }
+
+    fn maps(source: &str, count: usize) -> Vec<GameMap> {
+        (0..count)
+            .map(|i| {
+                GameMap::Campaign(CampaignMap {
+                    campaign_id: if source == "a" { 1 } else { 2 },
+                    map: i as i32,
+                })
+            })
+            .collect()
+    }
+
+    fn from_campaign(maps: &[GameMap], campaign: i32) -> usize {
+        maps.iter()
+            .filter(|map| matches!(map, GameMap::Campaign(m) if m.campaign_id == campaign))
+            .count()
+    }
+
+    #[test]
+    fn quotas_apply_to_board_and_spares_separately() {
+        let draw = |source, board_quota, spare_quota| SourceDraw {
+            maps: maps(source, 50),
+            board_quota,
+            spare_quota,
+        };
+        let mixed = mix_draws(vec![draw("a", 15, 15), draw("b", 10, 10)], 25, 25);
+        assert_eq!(mixed.len(), 50);
+        assert_eq!(from_campaign(&mixed[..25], 1), 15);
+        assert_eq!(from_campaign(&mixed[..25], 2), 10);
+        assert_eq!(from_campaign(&mixed[25..], 1), 15);
+
+        // a source falling short leaves its board share to the other source
+        let short = SourceDraw {
+            maps: maps("b", 4),
+            board_quota: 10,
+            spare_quota: 10,
+        };
+        let mixed = mix_draws(vec![draw("a", 15, 15), short], 25, 25);
+        assert_eq!(from_campaign(&mixed[..25], 2), 4);
+        assert_eq!(mixed.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn seeded_campaign_draw_is_repeated() {
+        let config = MatchConfiguration {
+            selection: MapMode::Campaign,
+            campaign_selection: Some(vec![1, 2, 3]),
+            seed: Some(1234),
+            ..Default::default()
+        };
+        let draw = || async {
+            CampaignProvider
+                .load(LoadRequest::new(&config))
+                .await
+                .unwrap()
+                .maps
+                .iter()
+                .map(GameMap::sort_key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(draw().await, draw().await);
+    }
+
+    struct EmptyProvider;
+
+    impl MapProvider for EmptyProvider {
+        fn load(
+            &self,
+            _request: LoadRequest,
+        ) -> BoxFuture<'static, Result<LoadedMaps, anyhow::Error>> {
+            async {
+                Ok(LoadedMaps {
+                    maps: Vec::new(),
+                    warnings: vec!["nothing here".to_owned()],
+                })
+            }
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn registered_provider_is_used() {
+        let config = MatchConfiguration {
+            selection: MapMode::Campaign,
+            ..Default::default()
+        };
+        assert!(load(LoadRequest::new(&config)).await.is_err());
+
+        register_provider(MapMode::Campaign, EmptyProvider);
+        let loaded = load(LoadRequest::new(&config)).await;
+        // the registry is shared with the other tests, put the campaign provider back first
+        register_provider(MapMode::Campaign, CampaignProvider);
+        assert_eq!(loaded.unwrap().warnings, vec!["nothing here".to_owned()]);
+        assert!(load(LoadRequest::new(&config)).await.is_err());
+    }
+}
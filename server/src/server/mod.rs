@@ -10,8 +10,10 @@ mod client;
 pub mod context;
 pub mod handlers;
 pub mod handshake;
+pub mod maphealth;
 pub mod mapload;
 pub mod requests;
+pub mod shutdown;
 pub mod tasks;
 mod version;
 mod token;
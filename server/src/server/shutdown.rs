@@ -0,0 +1,160 @@
+// Graceful shutdown of the server, and drain mode refusing new rooms and matches
+
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration as StdDuration,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use tokio::{
+    sync::watch,
+    time::{sleep, Instant},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config,
+    core::{
+        directory::{CLIENTS_CHANNEL, MATCHES},
+        events::server::ServerEvent,
+        persistence,
+    },
+    orm, store,
+};
+
+const DRAIN_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(1);
+/// Time given to connections to send their last messages before the server exits.
+const CLOSE_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+/// Time given to pending store writes to complete before the databases are closed.
+const WRITES_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+static PENDING_WRITES: AtomicUsize = AtomicUsize::new(0);
+static CLOSING: Lazy<watch::Sender<bool>> = Lazy::new(|| watch::channel(false).0);
+
+/// Whether the server refuses new rooms and matches.
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::Relaxed)
+}
+
+/// Enable or disable drain mode. Players are notified when it is enabled.
+pub fn set_draining(draining: bool) {
+    let was_draining = DRAINING.swap(draining, Ordering::Relaxed);
+    if draining && !was_draining {
+        info!("drain mode enabled, new rooms and matches are refused");
+        CLIENTS_CHANNEL.lock().broadcast(&maintenance_notice(None));
+    } else if !draining && was_draining {
+        info!("drain mode disabled");
+    }
+}
+
+fn maintenance_notice(deadline: Option<DateTime<Utc>>) -> ServerEvent {
+    ServerEvent::MaintenanceNotice {
+        message: "The server will restart soon for maintenance. Running matches can be finished, but new ones cannot be started.".to_owned(),
+        deadline,
+    }
+}
+
+/// Get notified when the server closes all connections.
+pub fn subscribe_closing() -> watch::Receiver<bool> {
+    CLOSING.subscribe()
+}
+
+/// Counts a connection as open for as long as it is held.
+pub struct ConnectionGuard;
+
+/// Count a new connection, until the returned guard is dropped.
+pub fn track_connection() -> ConnectionGuard {
+    CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+    ConnectionGuard
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a store write made in the background as pending for as long as it is held.
+pub struct WriteGuard;
+
+/// Count a new pending store write, until the returned guard is dropped. The databases are only
+/// closed on shutdown once all writes are done.
+pub fn track_write() -> WriteGuard {
+    PENDING_WRITES.fetch_add(1, Ordering::Relaxed);
+    WriteGuard
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        PENDING_WRITES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Wait for the server to be asked to stop, by SIGTERM or Ctrl+C.
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("SIGTERM handler not created");
+        tokio::select! {
+            _ = terminate.recv() => (),
+            _ = tokio::signal::ctrl_c() => (),
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Stop the server gracefully: refuse new rooms and matches, give running matches some time to
+/// end, save what is left to be restored after the restart, then close all connections.
+pub async fn shutdown() {
+    let timeout =
+        Duration::seconds(config::get_integer("behaviour.shutdown_timeout").unwrap_or(300));
+    let deadline = Utc::now() + timeout;
+    info!(
+        "shutting down, waiting up to {}s for matches to end",
+        timeout.num_seconds()
+    );
+    DRAINING.store(true, Ordering::Relaxed);
+    CLIENTS_CHANNEL
+        .lock()
+        .broadcast(&maintenance_notice(Some(deadline)));
+
+    while Utc::now() < deadline {
+        let remaining = MATCHES.lock().len();
+        if remaining == 0 {
+            break;
+        }
+        sleep(DRAIN_CHECK_INTERVAL).await;
+    }
+    let remaining = MATCHES.lock().len();
+    if remaining > 0 {
+        warn!(
+            "{} matches are still running and will be restored",
+            remaining
+        );
+    }
+
+    persistence::save_snapshots().await;
+    let writes_deadline = Instant::now() + WRITES_TIMEOUT;
+    while PENDING_WRITES.load(Ordering::Relaxed) > 0 && Instant::now() < writes_deadline {
+        sleep(StdDuration::from_millis(100)).await;
+    }
+    let pending = PENDING_WRITES.load(Ordering::Relaxed);
+    if pending > 0 {
+        warn!("{} store writes did not complete and are lost", pending);
+    }
+    store::close_primary_store().await;
+    orm::mapcache::close_database().await;
+
+    let _ = CLOSING.send(true);
+    let closing_deadline = Instant::now() + CLOSE_TIMEOUT;
+    while CONNECTIONS.load(Ordering::Relaxed) > 0 && Instant::now() < closing_deadline {
+        sleep(StdDuration::from_millis(100)).await;
+    }
+    info!("server stopped");
+}
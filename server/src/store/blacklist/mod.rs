@@ -0,0 +1,41 @@
+mod models;
+
+use super::{execute_with_arguments, get_store, query_all, StoreResult};
+pub use models::*;
+use sqlx::Row;
+
+/// Add a map to the blacklist, replacing any previous entry for it.
+pub async fn add_map(map: &BlacklistedMap) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "INSERT OR REPLACE INTO map_blacklist(tmxid, reason, added_by, created_at) VALUES (?, ?, ?, ?)",
+        |query| {
+            query
+                .bind(map.tmxid)
+                .bind(&map.reason)
+                .bind(map.added_by)
+                .bind(map.created_at)
+        },
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Get all blacklisted maps.
+pub async fn get_blacklist() -> StoreResult<Vec<BlacklistedMap>> {
+    query_all(
+        get_store(),
+        "SELECT tmxid, reason, added_by, created_at FROM map_blacklist",
+    )
+    .await
+    .map(|rows| {
+        rows.into_iter()
+            .map(|row| BlacklistedMap {
+                tmxid: row.get(0),
+                reason: row.get(1),
+                added_by: row.get(2),
+                created_at: row.get(3),
+            })
+            .collect()
+    })
+}
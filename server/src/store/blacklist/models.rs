@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::{serde_as, TimestampSeconds};
+
+/// Model of a map excluded from every map selection on the server.
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+pub struct BlacklistedMap {
+    pub tmxid: i32,
+    pub reason: String,
+    pub added_by: i32,
+    #[serde_as(as = "TimestampSeconds")]
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,92 @@
+mod models;
+
+use super::{
+    execute_with_arguments, get_store, query_all_with_arguments, query_with_arguments, StoreResult,
+};
+pub use models::*;
+use sqlx::Row;
+
+/// Create the board of a daily challenge. Existing boards are never replaced.
+pub async fn create_daily_challenge(challenge: &DailyChallenge) -> StoreResult {
+    let maps = challenge
+        .map_ids
+        .iter()
+        .map(i32::to_string)
+        .collect::<Vec<String>>()
+        .join(",");
+    execute_with_arguments(
+        get_store(),
+        "INSERT OR IGNORE INTO daily_challenges(timedate, maps) VALUES (?, ?)",
+        |query| query.bind(&challenge.timedate).bind(maps),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Find the board of a daily challenge.
+pub async fn get_daily_challenge(timedate: &str) -> StoreResult<DailyChallenge> {
+    query_with_arguments(
+        get_store(),
+        "SELECT timedate, maps FROM daily_challenges WHERE timedate = ?",
+        |query| query.bind(timedate),
+    )
+    .await
+    .map(|row| DailyChallenge {
+        timedate: row.get(0),
+        map_ids: row
+            .get::<String, usize>(1)
+            .split(',')
+            .filter_map(|id| id.parse().ok())
+            .collect(),
+    })
+}
+
+/// Save a player's result after a daily challenge match has ended.
+pub async fn create_daily_result(result: &DailyResult) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "INSERT INTO daily_results(player_uid, match_uid, timedate, tiles_claimed, first_bingo_millis) VALUES (?, ?, ?, ?, ?)",
+        |query| {
+            query
+                .bind(result.player_uid)
+                .bind(&result.match_uid)
+                .bind(&result.timedate)
+                .bind(result.tiles_claimed)
+                .bind(result.first_bingo_millis)
+        },
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Get the best result of each player in a daily challenge, fastest bingos first.
+pub async fn get_daily_leaderboard(
+    timedate: &str,
+    limit: u32,
+) -> StoreResult<Vec<DailyLeaderboardEntry>> {
+    query_all_with_arguments(
+        get_store(),
+        "SELECT player_uid, username, tiles_claimed, first_bingo_millis FROM (
+            SELECT daily_results.*, players.username, ROW_NUMBER() OVER (
+                PARTITION BY player_uid
+                ORDER BY first_bingo_millis IS NULL, first_bingo_millis, tiles_claimed DESC
+            ) AS attempt_rank
+            FROM daily_results JOIN players ON players.uid = daily_results.player_uid
+            WHERE timedate = ?
+        ) WHERE attempt_rank = 1
+        ORDER BY first_bingo_millis IS NULL, first_bingo_millis, tiles_claimed DESC
+        LIMIT ?",
+        |query| query.bind(timedate).bind(limit),
+    )
+    .await
+    .map(|rows| {
+        rows.into_iter()
+            .map(|row| DailyLeaderboardEntry {
+                player_uid: row.get(0),
+                name: row.get(1),
+                tiles_claimed: row.get(2),
+                first_bingo_millis: row.get(3),
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+/// Model of the board of a daily challenge.
+#[derive(Debug)]
+pub struct DailyChallenge {
+    pub timedate: String,
+    pub map_ids: Vec<i32>,
+}
+
+/// Model of a player's result on a daily challenge.
+#[derive(Debug)]
+pub struct DailyResult {
+    pub player_uid: i32,
+    pub match_uid: String,
+    pub timedate: String,
+    pub tiles_claimed: i32,
+    pub first_bingo_millis: Option<i64>,
+}
+
+/// A player's best result in a daily challenge leaderboard.
+#[derive(Serialize, Debug, Clone)]
+pub struct DailyLeaderboardEntry {
+    pub player_uid: i32,
+    pub name: String,
+    pub tiles_claimed: i32,
+    pub first_bingo_millis: Option<i64>,
+}
@@ -1,6 +1,9 @@
 mod models;
 
-use super::{execute_with_arguments, get_store, query_with_arguments, StoreResult};
+use super::{
+    execute_with_arguments, get_store, query_all_with_arguments, query_with_arguments, StoreResult,
+};
+use chrono::{DateTime, Utc};
 pub use models::*;
 use sqlx::Row;
 
@@ -11,12 +14,14 @@ const MAX_RECORDS_PER_INSERT: usize = 100;
 pub async fn save_match_record(record: &Match) -> StoreResult {
     execute_with_arguments(
     get_store(),
-    "INSERT INTO matches(uid, started_at, ended_at, mvp_player_uid) VALUES (?, ?, ?, ?) ON CONFLICT(uid) DO UPDATE SET started_at=excluded.started_at, ended_at=excluded.ended_at, mvp_player_uid=excluded.mvp_player_uid",
+    "INSERT INTO matches(uid, started_at, ended_at, mvp_player_uid, seed, daily_timedate) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT(uid) DO UPDATE SET started_at=excluded.started_at, ended_at=excluded.ended_at, mvp_player_uid=excluded.mvp_player_uid, seed=excluded.seed, daily_timedate=excluded.daily_timedate",
     |query| {
         query.bind(&record.uid)
         .bind(record.started_at)
         .bind(record.ended_at)
         .bind(record.mvp_player_uid)
+        .bind(record.seed as i64)
+        .bind(&record.daily_timedate)
     }).await.map(|_| ())
 }
 
@@ -24,7 +29,7 @@ pub async fn save_match_record(record: &Match) -> StoreResult {
 pub async fn get_match_record(uid: &str) -> StoreResult<Match> {
     query_with_arguments(
         get_store(),
-        "SELECT uid, started_at, ended_at, mvp_player_uid FROM matches WHERE uid = ?",
+        "SELECT uid, started_at, ended_at, mvp_player_uid, seed, daily_timedate FROM matches WHERE uid = ?",
         |query| query.bind(uid),
     )
     .await
@@ -33,6 +38,8 @@ pub async fn get_match_record(uid: &str) -> StoreResult<Match> {
         started_at: row.get(1),
         ended_at: row.get(2),
         mvp_player_uid: row.get(3),
+        seed: row.get::<Option<i64>, usize>(4).unwrap_or_default() as u64,
+        daily_timedate: row.get(5),
     })
 }
 
@@ -56,9 +63,40 @@ pub async fn create_match_result(match_uid: &str, result: MatchResult) -> StoreR
     Ok(())
 }
 
+/// Record the TMX maps that were on the board of a match.
+pub async fn create_match_maps(match_uid: &str, tmxids: &[i32]) -> StoreResult {
+    for chunk in tmxids.chunks(MAX_RECORDS_PER_INSERT) {
+        let query_arguments = &", (?, ?)".repeat(chunk.len())[2..];
+        let query = format!(
+            "INSERT OR IGNORE INTO matches_maps(match_uid, tmxid) VALUES {}",
+            query_arguments
+        );
+        execute_with_arguments(get_store(), &query, |query| {
+            chunk
+                .iter()
+                .fold(query, |query, tmxid| query.bind(match_uid).bind(tmxid))
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Get the TMX maps a player had on a board in matches started after the given date.
+pub async fn get_recent_maps(player_uid: i32, since: DateTime<Utc>) -> StoreResult<Vec<i32>> {
+    query_all_with_arguments(
+        get_store(),
+        "SELECT DISTINCT matches_maps.tmxid FROM matches_maps JOIN matches ON matches.uid = matches_maps.match_uid JOIN matches_players ON matches_players.match_uid = matches_maps.match_uid WHERE matches_players.player_uid = ? AND matches.started_at >= ?",
+        |query| query.bind(player_uid).bind(since),
+    )
+    .await
+    .map(|rows| rows.into_iter().map(|row| row.get(0)).collect())
+}
+
 /// Create an entry for a Bingo live match that has just ended.
-pub async fn write_match_end(record: Match, result: MatchResult) -> StoreResult {
+pub async fn write_match_end(record: Match, result: MatchResult, maps: Vec<i32>) -> StoreResult {
     save_match_record(&record).await?;
     create_match_result(&record.uid, result).await?;
+    create_match_maps(&record.uid, &maps).await?;
     Ok(())
 }
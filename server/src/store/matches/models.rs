@@ -7,6 +7,8 @@ pub struct Match {
     pub started_at: DateTime<Utc>,
     pub ended_at: DateTime<Utc>,
     pub mvp_player_uid: Option<i32>,
+    pub seed: u64,
+    pub daily_timedate: Option<String>,
 }
 
 /// The outcome of a match for a single player.
@@ -3,23 +3,53 @@ use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
 use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
 use tracing::{error, info};
 
-static DATABASE_VERSIONS: [&'static str; 4] = [
+static DATABASE_VERSIONS: [&str; 12] = [
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v1.sql")),
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v2.sql")),
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v3.sql")),
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v4.sql")),
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v5.sql")),
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v6.sql")),
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v7.sql")),
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v8.sql")),
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/versions/v9.sql")),
+    include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/versions/v10.sql"
+    )),
+    include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/versions/v11.sql"
+    )),
+    include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/versions/v12.sql"
+    )),
 ];
 
 static PRIMARY_STORE: OnceLock<SqlitePool> = OnceLock::new();
 
+pub mod blacklist;
+pub mod daily;
 pub mod matches;
 mod operations;
 pub mod player;
+pub mod practice;
+pub mod preset;
+pub mod schedule;
+pub mod snapshot;
 
 use operations::*;
 
 pub type StoreResult<V = ()> = Result<V, sqlx::Error>;
 
+/// Close the primary database, waiting for the queries in progress to complete.
+pub async fn close_primary_store() {
+    if let Some(pool) = PRIMARY_STORE.get() {
+        pool.close().await;
+    }
+}
+
 /// Creates an empty file if the path specified doesn't exist.
 fn file_create(path: &str) {
     if let Ok(false) = Path::new(path).try_exists() {
@@ -47,6 +77,20 @@ pub async fn initialize_primary_store(path: &str) {
     PRIMARY_STORE.get_or_init(|| connection_pool);
 }
 
+/// Initialize the primary database in a temporary file, for tests that go through the store.
+#[cfg(test)]
+pub async fn initialize_test_store() {
+    static INITIALIZED: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    INITIALIZED
+        .get_or_init(|| async {
+            // tests run on separate runtimes, which may drop the connections of an in-memory database
+            let path = std::env::temp_dir().join(format!("bingo-test-{}.db", std::process::id()));
+            let _ = fs::remove_file(&path);
+            initialize_primary_store(path.to_str().expect("temporary path should be valid")).await;
+        })
+        .await;
+}
+
 /// Run SQL migrations to update the database to the latest version.
 async fn apply_database_updates(pool: &SqlitePool, configuration: &HashMap<String, String>) {
     let mut version: usize = match configuration
@@ -0,0 +1,86 @@
+mod models;
+
+use super::{
+    execute_with_arguments, get_store, query_all_with_arguments, query_with_arguments, StoreResult,
+};
+use crate::datatypes::PracticeGoal;
+pub use models::*;
+use sqlx::{sqlite::SqliteRow, Row};
+
+const RESULT_COLUMNS: &str = "practice_results.id, player_uid, match_uid, practice_results.config_key, goal, completed, tiles_claimed, duration_millis, created_at";
+
+fn goal_from_int(value: u8) -> PracticeGoal {
+    match value {
+        1 => PracticeGoal::Bingo,
+        2 => PracticeGoal::Blackout,
+        3 => PracticeGoal::TimeAttack,
+        _ => PracticeGoal::None,
+    }
+}
+
+fn result_from_row(row: SqliteRow) -> PracticeResult {
+    PracticeResult {
+        player_uid: row.get(1),
+        match_uid: row.get(2),
+        config_key: row.get(3),
+        goal: goal_from_int(row.get(4)),
+        completed: row.get(5),
+        tiles_claimed: row.get(6),
+        duration_millis: row.get(7),
+        created_at: row.get(8),
+    }
+}
+
+/// Save the result of a practice match and return its identifier.
+pub async fn create_practice_result(result: &PracticeResult) -> StoreResult<i64> {
+    execute_with_arguments(
+        get_store(),
+        "INSERT INTO practice_results(player_uid, match_uid, config_key, goal, completed, tiles_claimed, duration_millis, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        |query| {
+            query
+                .bind(result.player_uid)
+                .bind(&result.match_uid)
+                .bind(&result.config_key)
+                .bind(result.goal as u8)
+                .bind(result.completed)
+                .bind(result.tiles_claimed)
+                .bind(result.duration_millis)
+                .bind(result.created_at)
+        },
+    )
+    .await
+    .map(|result| result.last_insert_rowid())
+}
+
+/// Get a player's personal best for a practice configuration.
+pub async fn get_personal_best(player_uid: i32, config_key: &str) -> StoreResult<PracticeResult> {
+    query_with_arguments(
+        get_store(),
+        &format!("SELECT {RESULT_COLUMNS} FROM practice_bests JOIN practice_results ON practice_results.id = practice_bests.result_id WHERE practice_bests.player_uid = ? AND practice_bests.config_key = ?"),
+        |query| query.bind(player_uid).bind(config_key),
+    )
+    .await
+    .map(result_from_row)
+}
+
+/// Get all personal bests of a player, most recent first.
+pub async fn get_personal_bests(player_uid: i32) -> StoreResult<Vec<PracticeResult>> {
+    query_all_with_arguments(
+        get_store(),
+        &format!("SELECT {RESULT_COLUMNS} FROM practice_bests JOIN practice_results ON practice_results.id = practice_bests.result_id WHERE practice_bests.player_uid = ? ORDER BY created_at DESC"),
+        |query| query.bind(player_uid),
+    )
+    .await
+    .map(|rows| rows.into_iter().map(result_from_row).collect())
+}
+
+/// Mark a saved result as a player's personal best for its configuration.
+pub async fn set_personal_best(player_uid: i32, config_key: &str, result_id: i64) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "INSERT INTO practice_bests(player_uid, config_key, result_id) VALUES (?, ?, ?) ON CONFLICT(player_uid, config_key) DO UPDATE SET result_id=excluded.result_id",
+        |query| query.bind(player_uid).bind(config_key).bind(result_id),
+    )
+    .await
+    .map(|_| ())
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::{serde_as, TimestampSeconds};
+
+use crate::datatypes::PracticeGoal;
+
+/// Model of a player's result at the end of a practice match.
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+pub struct PracticeResult {
+    pub player_uid: i32,
+    pub match_uid: String,
+    pub config_key: String,
+    pub goal: PracticeGoal,
+    pub completed: bool,
+    pub tiles_claimed: i32,
+    pub duration_millis: i64,
+    #[serde_as(as = "TimestampSeconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl PracticeResult {
+    /// Whether this result beats another one for the same configuration.
+    /// Completed attempts are ranked by time, time attacks by tiles claimed.
+    pub fn beats(&self, other: &PracticeResult) -> bool {
+        match self.goal {
+            PracticeGoal::TimeAttack => self.tiles_claimed > other.tiles_claimed,
+            _ => {
+                (self.completed && !other.completed)
+                    || (self.completed == other.completed
+                        && if self.completed {
+                            self.duration_millis < other.duration_millis
+                        } else {
+                            self.tiles_claimed > other.tiles_claimed
+                        })
+            }
+        }
+    }
+}
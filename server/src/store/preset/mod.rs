@@ -0,0 +1,72 @@
+mod models;
+
+use super::{execute_with_arguments, get_store, query_all, StoreResult};
+pub use models::*;
+use sqlx::Row;
+use tracing::error;
+
+/// Insert a preset, or replace the preset with the same id.
+pub async fn save_preset(preset: &RoomPreset) -> StoreResult {
+    let config = serde_json::to_string(&preset.config).expect("room config should serialize");
+    let match_config =
+        serde_json::to_string(&preset.match_config).expect("match config should serialize");
+    let teams = serde_json::to_string(&preset.teams).expect("teams should serialize");
+    execute_with_arguments(
+        get_store(),
+        "INSERT OR REPLACE INTO room_presets(id, owner_uid, name, share_code, config, match_config, teams, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        |query| {
+            query
+                .bind(preset.id)
+                .bind(preset.owner_uid)
+                .bind(&preset.name)
+                .bind(&preset.share_code)
+                .bind(config)
+                .bind(match_config)
+                .bind(teams)
+                .bind(preset.updated_at)
+        },
+    )
+    .await
+    .map(|_| ())
+}
+
+pub async fn delete_preset(id: i64) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "DELETE FROM room_presets WHERE id = ?",
+        |query| query.bind(id),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Get the presets of all players. Presets that no longer match the configuration format are skipped.
+pub async fn get_presets() -> StoreResult<Vec<RoomPreset>> {
+    query_all(
+        get_store(),
+        "SELECT id, owner_uid, name, share_code, config, match_config, teams, updated_at FROM room_presets",
+    )
+    .await
+    .map(|rows| {
+        rows.into_iter()
+            .filter_map(|row| {
+                let id: i64 = row.get(0);
+                let parsed = (|| {
+                    Ok::<_, serde_json::Error>(RoomPreset {
+                        id,
+                        owner_uid: row.get(1),
+                        name: row.get(2),
+                        share_code: row.get(3),
+                        config: serde_json::from_str(row.get(4))?,
+                        match_config: serde_json::from_str(row.get(5))?,
+                        teams: serde_json::from_str(row.get(6))?,
+                        updated_at: row.get(7),
+                    })
+                })();
+                parsed
+                    .inspect_err(|e| error!("invalid room preset {}: {}", id, e))
+                    .ok()
+            })
+            .collect()
+    })
+}
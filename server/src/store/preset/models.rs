@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::{serde_as, TimestampSeconds};
+
+use crate::{
+    core::models::team::BaseTeam,
+    datatypes::{MatchConfiguration, RoomConfiguration},
+};
+
+/// Model of a saved room configuration. Presets defined by the server have no owner.
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+pub struct RoomPreset {
+    pub id: i64,
+    pub owner_uid: Option<i32>,
+    pub name: String,
+    pub share_code: Option<String>,
+    pub config: RoomConfiguration,
+    pub match_config: MatchConfiguration,
+    pub teams: Vec<BaseTeam>,
+    #[serde_as(as = "TimestampSeconds")]
+    pub updated_at: DateTime<Utc>,
+}
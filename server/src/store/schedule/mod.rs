@@ -0,0 +1,124 @@
+mod models;
+
+use std::collections::HashMap;
+
+use super::{execute_with_arguments, get_store, query_all, StoreResult};
+pub use models::*;
+use sqlx::Row;
+use tracing::error;
+
+/// Insert a scheduled room, or replace the room with the same id.
+pub async fn save_scheduled_room(room: &ScheduledRoom) -> StoreResult {
+    let config = serde_json::to_string(&room.config).expect("room config should serialize");
+    let match_config =
+        serde_json::to_string(&room.match_config).expect("match config should serialize");
+    let teams = serde_json::to_string(&room.teams).expect("teams should serialize");
+    execute_with_arguments(
+        get_store(),
+        "INSERT OR REPLACE INTO scheduled_rooms(id, host_uid, join_code, config, match_config, teams, start_at, min_players) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        |query| {
+            query
+                .bind(room.id)
+                .bind(room.host_uid)
+                .bind(&room.join_code)
+                .bind(config)
+                .bind(match_config)
+                .bind(teams)
+                .bind(room.start_at)
+                .bind(room.min_players)
+        },
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Delete a scheduled room along with its registrations.
+pub async fn delete_scheduled_room(id: i64) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "DELETE FROM scheduled_room_players WHERE room_id = ?",
+        |query| query.bind(id),
+    )
+    .await?;
+    execute_with_arguments(
+        get_store(),
+        "DELETE FROM scheduled_rooms WHERE id = ?",
+        |query| query.bind(id),
+    )
+    .await
+    .map(|_| ())
+}
+
+pub async fn save_registration(room_id: i64, registration: &Registration) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "INSERT OR REPLACE INTO scheduled_room_players(room_id, player_uid, team_index) VALUES (?, ?, ?)",
+        |query| {
+            query
+                .bind(room_id)
+                .bind(registration.player_uid)
+                .bind(registration.team_index as i64)
+        },
+    )
+    .await
+    .map(|_| ())
+}
+
+pub async fn delete_registration(room_id: i64, player_uid: i32) -> StoreResult {
+    execute_with_arguments(
+        get_store(),
+        "DELETE FROM scheduled_room_players WHERE room_id = ? AND player_uid = ?",
+        |query| query.bind(room_id).bind(player_uid),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Get all scheduled rooms with their registrations. Rooms that no longer match the configuration
+/// format are skipped.
+pub async fn get_scheduled_rooms() -> StoreResult<Vec<ScheduledRoom>> {
+    let mut registrations: HashMap<i64, Vec<Registration>> = HashMap::new();
+    for row in query_all(
+        get_store(),
+        "SELECT room_id, player_uid, team_index FROM scheduled_room_players",
+    )
+    .await?
+    {
+        registrations
+            .entry(row.get(0))
+            .or_default()
+            .push(Registration {
+                player_uid: row.get(1),
+                team_index: row.get::<i64, _>(2) as usize,
+            });
+    }
+
+    query_all(
+        get_store(),
+        "SELECT id, host_uid, join_code, config, match_config, teams, start_at, min_players FROM scheduled_rooms",
+    )
+    .await
+    .map(|rows| {
+        rows.into_iter()
+            .filter_map(|row| {
+                let id: i64 = row.get(0);
+                let parsed = (|| {
+                    Ok::<_, serde_json::Error>(ScheduledRoom {
+                        id,
+                        host_uid: row.get(1),
+                        join_code: row.get(2),
+                        config: serde_json::from_str(row.get(3))?,
+                        match_config: serde_json::from_str(row.get(4))?,
+                        teams: serde_json::from_str(row.get(5))?,
+                        start_at: row.get(6),
+                        min_players: row.get(7),
+                        registrations: registrations.remove(&id).unwrap_or_default(),
+                    })
+                })();
+                parsed
+                    .inspect_err(|e| error!("invalid scheduled room {}: {}", id, e))
+                    .ok()
+            })
+            .collect()
+    })
+}
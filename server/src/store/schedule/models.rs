@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::{serde_as, TimestampSeconds};
+
+use crate::{
+    core::models::team::BaseTeam,
+    datatypes::{MatchConfiguration, RoomConfiguration},
+};
+
+/// Model of a room scheduled by a host to open at a later time.
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+pub struct ScheduledRoom {
+    pub id: i64,
+    pub host_uid: i32,
+    pub join_code: String,
+    pub config: RoomConfiguration,
+    pub match_config: MatchConfiguration,
+    pub teams: Vec<BaseTeam>,
+    #[serde_as(as = "TimestampSeconds")]
+    pub start_at: DateTime<Utc>,
+    /// Number of players required in the room for the match to start automatically.
+    pub min_players: u32,
+    pub registrations: Vec<Registration>,
+}
+
+/// A player registered to a scheduled room, on the team at `team_index` in its teams.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    pub player_uid: i32,
+    pub team_index: usize,
+}
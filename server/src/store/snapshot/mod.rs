@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use sqlx::Row;
+use tracing::error;
+
+use super::{get_store, query_all_with_arguments, StoreResult};
+use crate::core::models::snapshot::{MatchSnapshot, RoomSnapshot};
+
+/// Replace all saved snapshots with the given rooms and matches.
+pub async fn replace_snapshots(
+    rooms: &[RoomSnapshot],
+    matches: &[MatchSnapshot],
+    saved_at: DateTime<Utc>,
+) -> StoreResult {
+    let mut transaction = get_store().begin().await?;
+    sqlx::query("DELETE FROM room_snapshots")
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query("DELETE FROM match_snapshots")
+        .execute(&mut *transaction)
+        .await?;
+    for room in rooms {
+        let data = serde_json::to_string(room).expect("room snapshot should serialize");
+        sqlx::query("INSERT INTO room_snapshots(join_code, data, saved_at) VALUES (?, ?, ?)")
+            .bind(&room.join_code)
+            .bind(data)
+            .bind(saved_at)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    for game in matches {
+        let data = serde_json::to_string(game).expect("match snapshot should serialize");
+        sqlx::query("INSERT INTO match_snapshots(uid, data, saved_at) VALUES (?, ?, ?)")
+            .bind(&game.uid)
+            .bind(data)
+            .bind(saved_at)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    transaction
+        .commit()
+        .await
+        .inspect_err(|e| error!("error saving snapshots: {e}"))
+}
+
+/// Get the room snapshots saved after the given time.
+pub async fn get_room_snapshots(since: DateTime<Utc>) -> StoreResult<Vec<RoomSnapshot>> {
+    get_snapshots(
+        "SELECT join_code, data FROM room_snapshots WHERE saved_at >= ?",
+        since,
+    )
+    .await
+}
+
+/// Get the match snapshots saved after the given time.
+pub async fn get_match_snapshots(since: DateTime<Utc>) -> StoreResult<Vec<MatchSnapshot>> {
+    get_snapshots(
+        "SELECT uid, data FROM match_snapshots WHERE saved_at >= ?",
+        since,
+    )
+    .await
+}
+
+/// Snapshots that no longer match the format of the current version are skipped.
+async fn get_snapshots<T: DeserializeOwned>(
+    sql: &str,
+    since: DateTime<Utc>,
+) -> StoreResult<Vec<T>> {
+    query_all_with_arguments(get_store(), sql, |query| query.bind(since))
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .filter_map(|row| {
+                    let key: String = row.get(0);
+                    serde_json::from_str(row.get(1))
+                        .inspect_err(|e| error!("invalid snapshot {}: {}", key, e))
+                        .ok()
+                })
+                .collect()
+        })
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use serde::Serialize;
 use serde_json::to_vec;
-use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{error::SendError, unbounded_channel};
 use tracing::error;
 
 use crate::transport::TransportWriteQueue;
@@ -13,6 +13,12 @@ pub fn new_messager(writer: TransportWriteQueue) -> NetMessager {
     Arc::new(NetMessageWriter::new(writer))
 }
 
+/// A messager of a player who is not connected, every message sent to it is dropped.
+pub fn closed_messager() -> NetMessager {
+    let (writer, _) = unbounded_channel();
+    new_messager(writer)
+}
+
 #[derive(Debug)]
 pub struct NetMessageWriter {
     writer: TransportWriteQueue,
@@ -28,6 +28,9 @@ class MapMode(Enum):
     TAGS = 1
     MAPPACK = 2
     CAMPAIGN = 3
+    MIXED = 4
+    LOCAL = 5
+    CUSTOM = 6
 
 # A Trackmania medal ranking.
 class Medal(Enum):
@@ -42,6 +45,7 @@ class Medal(Enum):
 class Gamemode(Enum):
     STANDARD = 0
     FRENZY = 1
+    POINTS = 2
 
 # When a connection to the server fails, give the client a hint of what it should do.
 class HandshakeFailureIntentCode(Enum):
@@ -59,6 +63,13 @@ class Powerup(Enum):
     RAINBOWTILE = 5
     GOLDENDICE = 6
 
+# The objective of a solo practice match.
+class PracticeGoal(Enum):
+    NONE = 0
+    BINGO = 1
+    BLACKOUT = 2
+    TIMEATTACK = 3
+
 # A simple reference to a registered player.
 class PlayerRef(BaseModel):
     uid: int
@@ -107,6 +118,15 @@ class MatchConfiguration(BaseModel):
     items_tick_multiplier: int = 1000
     rally_length: int = 600
     jail_length: int = 600
+    inventory_size: int = 1
+    shared_inventory: bool = False
+    seed: int | None
+    practice_goal: PracticeGoal = PracticeGoal.NONE
+    medal_ladder: bool = False
+    filters: MapFilters
+    avoid_recent_maps: bool = False
+    map_sources: list[MapSource]
+    custom_maps: list[CustomMap]
 
 # Item drawing probabilities for configuring the Frenzy gamemode.
 class FrenzyItemSettings(BaseModel):
@@ -117,6 +137,30 @@ class FrenzyItemSettings(BaseModel):
     rainbow: int = 3
     golden_dice: int = 3
 
+# One of the map selections mixed together on a board, with its share of the maps.
+class MapSource(BaseModel):
+    selection: MapMode = MapMode.RANDOMTMX
+    weight: int = 1
+    mappack_id: int | None
+    map_tag: int | None
+
+# A map picked by the host for a custom board, by UID or TMX id, optionally placed on a cell numbered from 1.
+class CustomMap(BaseModel):
+    uid: str | None
+    tmxid: int | None
+    position: int | None
+
+# Restrictions on the maps drawn from the map cache.
+class MapFilters(BaseModel):
+    min_author_time: int | None
+    max_author_time: int | None
+    include_tags: list[int]
+    exclude_tags: list[int]
+    style: str | None
+    uploaded_after: int | None
+    uploaded_before: int | None
+    excluded_authors: list[str]
+
 # Request to open a connection by the client using an exisiting token.
 class HandshakeRequest(BaseModel):
     version: str